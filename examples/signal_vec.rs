@@ -22,7 +22,11 @@ fn main() {
                     *sum -= old_value;
                     *sum += new_value;
                 }
-                VecChange::Move { .. } | VecChange::Swap { .. } | VecChange::Sort(_) => {}
+                VecChange::Move { .. }
+                | VecChange::Swap { .. }
+                | VecChange::Reverse { .. }
+                | VecChange::Rotate { .. }
+                | VecChange::Sort(_) => {}
             }
         }
     })
@@ -7,6 +7,13 @@
 //! [`ChangeFeedState::from_scan`] for mutable state derived from reactive sources, and
 //! [`ChangeFeedSignal::from_scan`] for derived values without direct mutation.
 //!
+//! By default, changes are kept until every reader has read them. A [`HistoryLimit`] bounds the
+//! retained changes instead; a reader that falls behind the limit gets
+//! [`ChangeFeedDelta::Initial`] on its next read and must rebuild from the current value.
+//!
+//! A change can be combined by [`ChangeFeedModel::coalesce`] with the last retained change while
+//! no reader has read that change, even if it was recorded by an earlier mutable borrow.
+//!
 //! Mutating [`ChangeFeedRefMut::current_mut`] does not record a change by itself. Every observable
 //! mutation must be paired with [`ChangeFeedRefMut::record`] in the same mutable borrow.
 //!
@@ -55,12 +62,13 @@
 
 use std::{
     any::Any,
-    cell::{Ref, RefCell, RefMut},
+    cell::{Cell, Ref, RefCell, RefMut},
     mem,
     rc::{Rc, Weak},
 };
 
 use derive_ex::Ex;
+use serde::{Deserialize, Serialize};
 
 use crate::{
     ActionContext, SignalContext,
@@ -86,6 +94,75 @@ pub trait ChangeFeedModel: 'static {
     fn release_change(&mut self, change: Self::Change) {
         drop(change);
     }
+
+    /// Returns the estimated number of bytes retained by `change`.
+    ///
+    /// Used to enforce [`HistoryLimit::with_max_bytes`]. The default implementation returns the
+    /// size of `Self::Change`.
+    fn change_size(&self, change: &Self::Change) -> usize {
+        let _ = change;
+        mem::size_of::<Self::Change>()
+    }
+
+    /// Combines `next` with `prev`, the last retained change.
+    ///
+    /// Called by [`ChangeFeedRefMut::record`] before `next` is appended, if `prev` was recorded by
+    /// the same mutable borrow or no reader has read up to `prev`, so readers never see the
+    /// separate changes. This method owns `next` and must release whatever it no longer
+    /// needs; a `prev` discarded by [`Coalesced::Cancelled`] is passed to
+    /// [`release_change`](Self::release_change). The default implementation never combines
+    /// changes.
+    fn coalesce(&mut self, prev: &mut Self::Change, next: Self::Change) -> Coalesced<Self::Change> {
+        let _ = prev;
+        Coalesced::Separate(next)
+    }
+}
+
+/// The result of [`ChangeFeedModel::coalesce`].
+pub enum Coalesced<C> {
+    /// The changes cannot be combined, and `next` is appended after `prev`.
+    Separate(C),
+    /// `next` has been merged into `prev`.
+    Merged,
+    /// The changes cancel each other out, and `prev` is discarded.
+    Cancelled,
+}
+
+/// Bounds the changes a change feed retains for readers that have not read them yet.
+///
+/// When the retained changes exceed the limit, the oldest changes are discarded even if a reader
+/// has not read them, and such a reader gets [`ChangeFeedDelta::Initial`] on its next read.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub struct HistoryLimit {
+    max_changes: usize,
+    max_bytes: usize,
+}
+
+impl HistoryLimit {
+    /// Retains every change until all readers have read it.
+    pub const UNBOUNDED: Self = Self {
+        max_changes: usize::MAX,
+        max_bytes: usize::MAX,
+    };
+
+    /// Limits the number of retained changes.
+    pub fn with_max_changes(self, max_changes: usize) -> Self {
+        Self {
+            max_changes,
+            ..self
+        }
+    }
+
+    /// Limits the total of [`ChangeFeedModel::change_size`] over the retained changes.
+    pub fn with_max_bytes(self, max_bytes: usize) -> Self {
+        Self { max_bytes, ..self }
+    }
+}
+
+impl Default for HistoryLimit {
+    fn default() -> Self {
+        Self::UNBOUNDED
+    }
 }
 
 #[derive(Clone, Copy)]
@@ -112,6 +189,7 @@ impl<M: ChangeFeedModel> History<M> {
 struct ChangeFeedStorageCell<M: ChangeFeedModel> {
     history: RefCell<History<M>>,
     reader_ops: RefCell<RefCountOps>,
+    limit: Cell<HistoryLimit>,
 }
 
 #[derive(Ex)]
@@ -123,14 +201,22 @@ impl<M: ChangeFeedModel> ChangeFeedStorage<M> {
         Self(Rc::new(ChangeFeedStorageCell {
             history: RefCell::new(History::new(current)),
             reader_ops: RefCell::new(RefCountOps::new()),
+            limit: Cell::new(HistoryLimit::UNBOUNDED),
         }))
     }
 
+    pub(crate) fn set_limit(&self, limit: HistoryLimit) {
+        self.0.limit.set(limit);
+        self.compact();
+    }
+
     fn borrow_since(&self, since: Option<Cursor>) -> ChangeFeedRef<'_, M> {
         self.compact();
+        let history = self.0.history.borrow();
+        let since = since.filter(|cursor| !history.changes.is_expired(cursor.0));
         ChangeFeedRef {
             storage: self,
-            history: Some(self.0.history.borrow()),
+            history: Some(history),
             since,
         }
     }
@@ -170,6 +256,7 @@ impl<M: ChangeFeedModel> ChangeFeedStorage<M> {
             history: Some(history),
             start,
             dirty: false,
+            is_prev_rewritten: false,
             finish: EditFinish::None,
         }
     }
@@ -207,8 +294,11 @@ impl<M: ChangeFeedModel> ChangeFeedStorage<M> {
             return;
         };
         let History { current, changes } = &mut *history;
+        let limit = self.0.limit.get();
         self.0.reader_ops.borrow_mut().apply(changes);
-        changes.clean(|change| current.release_change(change));
+        changes.clean(limit.max_changes, limit.max_bytes, |change| {
+            current.release_change(change)
+        });
     }
 }
 
@@ -261,7 +351,7 @@ enum EditFinish<'a> {
     None,
     Track(&'a mut bool),
     Notify {
-        sinks: &'a RefCell<SinkBindings>,
+        node: &'a dyn NotifyEdit,
         nc: &'a mut NotifyContext,
     },
     Schedule {
@@ -280,6 +370,7 @@ pub struct ChangeFeedRefMut<'a, M: ChangeFeedModel> {
     history: Option<RefMut<'a, History<M>>>,
     start: Cursor,
     dirty: bool,
+    is_prev_rewritten: bool,
     finish: EditFinish<'a>,
 }
 
@@ -298,7 +389,10 @@ impl<'a, M: ChangeFeedModel> ChangeFeedRefMut<'a, M> {
     }
 
     pub(crate) fn is_dirty(&self) -> bool {
-        debug_assert_eq!(self.dirty, self.start.0 != self.history().changes.end_age());
+        debug_assert_eq!(
+            self.dirty,
+            self.is_prev_rewritten || self.start.0 != self.history().changes.end_age()
+        );
         self.dirty
     }
 
@@ -318,15 +412,52 @@ impl<'a, M: ChangeFeedModel> ChangeFeedRefMut<'a, M> {
     /// Returns the changes recorded by this edit, in recording order.
     ///
     /// The returned changes are already reflected in [`current`](Self::current). Changes that
-    /// existed before this edit began are not included.
+    /// existed before this edit began are not included unless this edit combined a change with
+    /// them.
     pub fn changes(&self) -> impl Iterator<Item = &M::Change> + '_ {
         self.history().changes.items(self.start.0)
     }
 
     /// Appends a model-specific change to the feed.
+    ///
+    /// The change may be combined with the last retained change by [`ChangeFeedModel::coalesce`]
+    /// if that change belongs to this edit or no reader has read up to it. An edit whose changes
+    /// all cancel out within the edit does not notify dependants.
     pub fn record(&mut self, change: M::Change) {
-        self.history_mut().changes.push(change);
-        self.dirty = true;
+        let mut start = self.start.0;
+        let History { current, changes } = self.history.as_deref_mut().unwrap();
+        self.storage.0.reader_ops.borrow_mut().apply(changes);
+        let has_prev = changes.end_age() != start;
+        let can_coalesce = has_prev || !changes.is_end_referenced();
+        let change = match changes.last_mut() {
+            Some(prev) if can_coalesce => match current.coalesce(prev, change) {
+                Coalesced::Separate(change) => Some(change),
+                Coalesced::Merged => {
+                    let size = current.change_size(changes.last_mut().unwrap());
+                    changes.set_last_size(size);
+                    if !has_prev {
+                        start = changes.end_age().wrapping_sub(1);
+                        self.is_prev_rewritten = true;
+                    }
+                    None
+                }
+                Coalesced::Cancelled => {
+                    current.release_change(changes.pop().unwrap());
+                    if !has_prev {
+                        start = changes.end_age();
+                        self.is_prev_rewritten = true;
+                    }
+                    None
+                }
+            },
+            _ => Some(change),
+        };
+        if let Some(change) = change {
+            let size = current.change_size(&change);
+            changes.push(change, size);
+        }
+        self.dirty = self.is_prev_rewritten || changes.end_age() != start;
+        self.start = Cursor(start);
     }
 }
 
@@ -341,9 +472,7 @@ impl<M: ChangeFeedModel> Drop for ChangeFeedRefMut<'_, M> {
         match mem::replace(&mut self.finish, EditFinish::None) {
             EditFinish::None => {}
             EditFinish::Track(dirty) => *dirty = true,
-            EditFinish::Notify { sinks, nc } => {
-                sinks.borrow_mut().notify(DirtyLevel::Dirty, nc);
-            }
+            EditFinish::Notify { node, nc } => node.notify_edit(nc),
             EditFinish::Schedule { node, slot } => schedule_notify(node, slot),
         }
     }
@@ -428,6 +557,15 @@ impl<M: ChangeFeedModel> ChangeFeedSignal<M> {
         }
     }
 
+    /// Sets the limit of the changes retained for readers of this signal.
+    pub fn set_history_limit(&self, limit: HistoryLimit) {
+        self.0.storage().set_limit(limit);
+    }
+
+    pub(crate) fn current_ref_untracked(&self) -> Ref<'_, M> {
+        self.0.storage().current_ref()
+    }
+
     fn watch(&self, sc: &mut SignalContext<'_, '_>) {
         self.0.watch(self.0.clone().to_any(), sc);
     }
@@ -446,6 +584,11 @@ impl<M: ChangeFeedModel> ChangeFeedReader<M> {
         self.cursor.read()
     }
 
+    /// Advances this reader to the current end without registering a dependency.
+    pub(crate) fn read_untracked(&mut self) -> ChangeFeedRef<'_, M> {
+        self.cursor.read()
+    }
+
     /// Borrows the current value without advancing this reader.
     pub fn peek<'a, 'r: 'a>(&'a self, sc: &mut SignalContext<'r, '_>) -> ChangeFeedRef<'a, M> {
         self.source.watch(sc);
@@ -477,6 +620,7 @@ impl<M: ChangeFeedModel> ChangeFeedState<M> {
             storage: ChangeFeedStorage::new(initial),
             scan: None,
             sinks: RefCell::new(SinkBindings::new()),
+            edit_sink: RefCell::new(None),
         }))
     }
 
@@ -498,6 +642,7 @@ impl<M: ChangeFeedModel> ChangeFeedState<M> {
                 scan: Box::new(scan),
             })),
             sinks: RefCell::new(SinkBindings::new()),
+            edit_sink: RefCell::new(None),
         }))
     }
 
@@ -523,7 +668,7 @@ impl<M: ChangeFeedModel> ChangeFeedState<M> {
     pub fn borrow_mut<'a>(&'a self, ac: &'a mut ActionContext) -> ChangeFeedRefMut<'a, M> {
         self.0.update(&mut ac.rc());
         self.0.storage.begin_edit().with_finish(EditFinish::Notify {
-            sinks: &self.0.sinks,
+            node: &*self.0,
             nc: ac.nc(),
         })
     }
@@ -543,6 +688,14 @@ impl<M: ChangeFeedModel> ChangeFeedState<M> {
             })
     }
 
+    /// Sets a sink that is notified of every direct edit at `slot`.
+    ///
+    /// Unlike dependants, this sink is notified even if it has not read this state since the
+    /// previous notification, so it can follow each edit as it happens.
+    pub(crate) fn set_edit_sink(&self, sink: Weak<dyn BindSink>, slot: Slot) {
+        *self.0.edit_sink.borrow_mut() = Some((sink, slot));
+    }
+
     /// Returns a signal backed by this state.
     pub fn to_signal(&self) -> ChangeFeedSignal<M> {
         ChangeFeedSignal(self.0.clone())
@@ -553,15 +706,61 @@ impl<M: ChangeFeedModel> ChangeFeedState<M> {
         self.to_signal().reader()
     }
 
+    /// Sets the limit of the changes retained for readers of this state.
+    pub fn set_history_limit(&self, limit: HistoryLimit) {
+        self.0.storage.set_limit(limit);
+    }
+
     pub(crate) fn current_ref_untracked(&self) -> Ref<'_, M> {
         self.0.storage.current_ref()
     }
 }
 
+/// Serializes the current model obtained by [`ChangeFeedState::try_borrow_contextless`].
+///
+/// Derived state created by [`ChangeFeedState::from_scan`] is not evaluated,
+/// so the serialized model may not reflect source changes that have not been scanned yet.
+impl<M: ChangeFeedModel + Serialize> Serialize for ChangeFeedState<M> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let value = self
+            .try_borrow_contextless()
+            .map_err(serde::ser::Error::custom)?;
+        value.current().serialize(serializer)
+    }
+}
+impl<'de, M: ChangeFeedModel + Deserialize<'de>> Deserialize<'de> for ChangeFeedState<M> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        M::deserialize(deserializer).map(Self::new)
+    }
+}
+
 struct StateNode<M: ChangeFeedModel> {
     storage: ChangeFeedStorage<M>,
     scan: Option<RefCell<StateScanData<M>>>,
     sinks: RefCell<SinkBindings>,
+    edit_sink: RefCell<Option<(Weak<dyn BindSink>, Slot)>>,
+}
+
+trait NotifyEdit {
+    fn notify_edit(&self, nc: &mut NotifyContext);
+}
+
+impl<M: ChangeFeedModel> NotifyEdit for StateNode<M> {
+    fn notify_edit(&self, nc: &mut NotifyContext) {
+        self.sinks.borrow_mut().notify(DirtyLevel::Dirty, nc);
+        let edit_sink = self.edit_sink.borrow().clone();
+        if let Some((sink, slot)) = edit_sink
+            && let Some(sink) = sink.upgrade()
+        {
+            sink.notify(slot, DirtyLevel::Dirty, nc);
+        }
+    }
 }
 
 impl<M: ChangeFeedModel> StateNode<M> {
@@ -626,7 +825,7 @@ impl<M: ChangeFeedModel> BindSource for StateNode<M> {
 impl<M: ChangeFeedModel> BindSink for StateNode<M> {
     fn notify(self: Rc<Self>, slot: Slot, level: DirtyLevel, nc: &mut NotifyContext) {
         if slot == STATE_LOCAL_EDIT_SLOT {
-            self.sinks.borrow_mut().notify(level, nc);
+            self.notify_edit(nc);
             return;
         }
         let Some(scan) = &self.scan else {
@@ -1,5 +1,6 @@
 use std::{
     cell::{Cell, RefCell},
+    mem,
     rc::Rc,
 };
 
@@ -406,3 +407,94 @@ fn state_scan_contextless_borrow_does_not_resolve_source() {
     assert_eq!(state.try_borrow_contextless().unwrap().current().value, 1);
     assert_eq!(state.borrow(&mut rt.sc()).current().value, 2);
 }
+
+type Deltas = Vec<Option<Vec<i32>>>;
+
+fn lagging_reader_deltas(limit: HistoryLimit) -> (Deltas, Deltas) {
+    let mut rt = Runtime::new();
+    let state = ChangeFeedState::new(TestModel {
+        value: 0,
+        released: Rc::new(RefCell::new(Vec::new())),
+    });
+    state.set_history_limit(limit);
+    let mut reader = state.reader();
+    let mut lagging = state.reader();
+    drop(reader.read(&mut rt.sc()));
+    drop(lagging.read(&mut rt.sc()));
+
+    let mut deltas = Vec::new();
+    for value in 1..=3 {
+        set(&mut state.borrow_mut(rt.ac()), value);
+        deltas.push(delta(&reader.read(&mut rt.sc())));
+    }
+    let mut lagging_deltas = vec![delta(&lagging.read(&mut rt.sc()))];
+    set(&mut state.borrow_mut(rt.ac()), 4);
+    lagging_deltas.push(delta(&lagging.read(&mut rt.sc())));
+    (deltas, lagging_deltas)
+}
+
+#[test]
+fn history_limit_max_changes_resets_lagging_reader() {
+    assert_eq!(
+        lagging_reader_deltas(HistoryLimit::UNBOUNDED.with_max_changes(2)),
+        (
+            vec![Some(vec![0]), Some(vec![1]), Some(vec![2])],
+            vec![None, Some(vec![3])],
+        )
+    );
+}
+
+#[test]
+fn history_limit_max_bytes_resets_lagging_reader() {
+    assert_eq!(
+        lagging_reader_deltas(HistoryLimit::UNBOUNDED.with_max_bytes(2 * mem::size_of::<i32>())),
+        (
+            vec![Some(vec![0]), Some(vec![1]), Some(vec![2])],
+            vec![None, Some(vec![3])],
+        )
+    );
+}
+
+#[test]
+fn history_limit_keeps_reader_within_limit_incremental() {
+    assert_eq!(
+        lagging_reader_deltas(HistoryLimit::UNBOUNDED.with_max_changes(3)),
+        (
+            vec![Some(vec![0]), Some(vec![1]), Some(vec![2])],
+            vec![Some(vec![0, 1, 2]), Some(vec![3])],
+        )
+    );
+}
+
+struct SerdeModel(i32);
+
+impl ChangeFeedModel for SerdeModel {
+    type Change = ();
+}
+impl Serialize for SerdeModel {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        self.0.serialize(serializer)
+    }
+}
+impl<'de> Deserialize<'de> for SerdeModel {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        i32::deserialize(deserializer).map(SerdeModel)
+    }
+}
+
+#[test]
+fn state_serde() {
+    let mut rt = Runtime::new();
+    let state = ChangeFeedState::new(SerdeModel(1));
+    assert_eq!(serde_json::to_string(&state).unwrap(), "1");
+
+    let edit = state.borrow_mut(rt.ac());
+    assert!(serde_json::to_string(&state).is_err());
+    drop(edit);
+
+    let restored: ChangeFeedState<SerdeModel> = serde_json::from_str("2").unwrap();
+    let mut reader = restored.reader();
+    let value = reader.read(&mut rt.sc());
+    assert_eq!(value.current().0, 2);
+    assert!(matches!(value.delta(), ChangeFeedDelta::Initial));
+}
@@ -1,2 +1,4 @@
+mod aggregate;
+pub mod json_patch;
 pub mod slab_map;
 pub mod vec;
@@ -0,0 +1,152 @@
+use std::{
+    collections::BTreeMap,
+    ops::{AddAssign, SubAssign},
+};
+
+/// An aggregate that is updated by the values added to and removed from a collection.
+pub(crate) trait Aggregate<T>: 'static {
+    type Output: PartialEq + 'static;
+
+    fn add(&mut self, value: &T);
+    fn remove(&mut self, value: &T);
+    fn output(&mut self) -> Self::Output;
+
+    /// Removes all values, as if the aggregate had been newly created.
+    fn clear(&mut self);
+}
+
+pub(crate) struct Sum<T>(pub T);
+
+impl<T> Aggregate<T> for Sum<T>
+where
+    T: Clone + Default + AddAssign + SubAssign + PartialEq + 'static,
+{
+    type Output = T;
+
+    fn add(&mut self, value: &T) {
+        self.0 += value.clone();
+    }
+    fn remove(&mut self, value: &T) {
+        self.0 -= value.clone();
+    }
+    fn output(&mut self) -> T {
+        self.0.clone()
+    }
+    fn clear(&mut self) {
+        self.0 = T::default();
+    }
+}
+
+pub(crate) struct CountWhere<P, O> {
+    predicate: P,
+    count: usize,
+    output: fn(usize) -> O,
+}
+
+impl<P, O> CountWhere<P, O> {
+    pub fn new(predicate: P, output: fn(usize) -> O) -> Self {
+        Self {
+            predicate,
+            count: 0,
+            output,
+        }
+    }
+}
+
+impl<T, P, O> Aggregate<T> for CountWhere<P, O>
+where
+    P: Fn(&T) -> bool + 'static,
+    O: PartialEq + 'static,
+{
+    type Output = O;
+
+    fn add(&mut self, value: &T) {
+        if (self.predicate)(value) {
+            self.count += 1;
+        }
+    }
+    fn remove(&mut self, value: &T) {
+        if (self.predicate)(value) {
+            self.count -= 1;
+        }
+    }
+    fn output(&mut self) -> O {
+        (self.output)(self.count)
+    }
+    fn clear(&mut self) {
+        self.count = 0;
+    }
+}
+
+/// The values of a collection counted by value, used by [`Max`] and [`Min`].
+///
+/// Additions and removals take `O(log n)` time, and the memory is proportional to the number
+/// of distinct values in the collection.
+struct Values<T>(BTreeMap<T, usize>);
+
+impl<T: Ord + Clone> Values<T> {
+    fn add(&mut self, value: &T) {
+        *self.0.entry(value.clone()).or_default() += 1;
+    }
+    fn remove(&mut self, value: &T) {
+        let Some(count) = self.0.get_mut(value) else {
+            return;
+        };
+        *count -= 1;
+        if *count == 0 {
+            self.0.remove(value);
+        }
+    }
+}
+
+/// The largest value of a collection.
+pub(crate) struct Max<T>(Values<T>);
+
+impl<T> Max<T> {
+    pub fn new() -> Self {
+        Self(Values(BTreeMap::new()))
+    }
+}
+
+impl<T: Ord + Clone + 'static> Aggregate<T> for Max<T> {
+    type Output = Option<T>;
+
+    fn add(&mut self, value: &T) {
+        self.0.add(value);
+    }
+    fn remove(&mut self, value: &T) {
+        self.0.remove(value);
+    }
+    fn output(&mut self) -> Option<T> {
+        self.0.0.last_key_value().map(|(value, _)| value.clone())
+    }
+    fn clear(&mut self) {
+        self.0.0.clear();
+    }
+}
+
+/// The smallest value of a collection.
+pub(crate) struct Min<T>(Values<T>);
+
+impl<T> Min<T> {
+    pub fn new() -> Self {
+        Self(Values(BTreeMap::new()))
+    }
+}
+
+impl<T: Ord + Clone + 'static> Aggregate<T> for Min<T> {
+    type Output = Option<T>;
+
+    fn add(&mut self, value: &T) {
+        self.0.add(value);
+    }
+    fn remove(&mut self, value: &T) {
+        self.0.remove(value);
+    }
+    fn output(&mut self) -> Option<T> {
+        self.0.0.first_key_value().map(|(value, _)| value.clone())
+    }
+    fn clear(&mut self) {
+        self.0.0.clear();
+    }
+}
@@ -0,0 +1,179 @@
+//! JSON Patch ([RFC 6902](https://www.rfc-editor.org/rfc/rfc6902)) documents for the deltas of
+//! reactive collections.
+//!
+//! A [`StateVec`](super::vec::StateVec) is treated as a JSON array, and a
+//! [`StateSlabMap`](super::slab_map::StateSlabMap) as a JSON object whose member names are the keys
+//! of the map. Paths refer to items of the collection itself, so only paths with a single
+//! reference token such as `/3` are supported.
+
+use std::{fmt, marker::PhantomData};
+
+use parse_display::Display;
+use serde::{
+    Deserialize, Serialize,
+    de::{self, IgnoredAny, MapAccess, Visitor},
+    ser::SerializeMap,
+};
+
+/// An operation of a JSON Patch document.
+///
+/// Serialized as a JSON object such as `{"op":"add","path":"/0","value":1}`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum PatchOperation<V> {
+    Add { path: String, value: V },
+    Remove { path: String },
+    Replace { path: String, value: V },
+    Move { from: String, path: String },
+    Copy { from: String, path: String },
+    Test { path: String, value: V },
+}
+
+impl<V> PatchOperation<V> {
+    fn op(&self) -> &'static str {
+        match self {
+            Self::Add { .. } => "add",
+            Self::Remove { .. } => "remove",
+            Self::Replace { .. } => "replace",
+            Self::Move { .. } => "move",
+            Self::Copy { .. } => "copy",
+            Self::Test { .. } => "test",
+        }
+    }
+}
+
+const OPS: &[&str] = &["add", "remove", "replace", "move", "copy", "test"];
+
+impl<V: Serialize> Serialize for PatchOperation<V> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let mut map = serializer.serialize_map(None)?;
+        map.serialize_entry("op", self.op())?;
+        match self {
+            Self::Add { path, value }
+            | Self::Replace { path, value }
+            | Self::Test { path, value } => {
+                map.serialize_entry("path", path)?;
+                map.serialize_entry("value", value)?;
+            }
+            Self::Remove { path } => map.serialize_entry("path", path)?,
+            Self::Move { from, path } | Self::Copy { from, path } => {
+                map.serialize_entry("from", from)?;
+                map.serialize_entry("path", path)?;
+            }
+        }
+        map.end()
+    }
+}
+
+impl<'de, V: Deserialize<'de>> Deserialize<'de> for PatchOperation<V> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct PatchOperationVisitor<V>(PhantomData<fn(V)>);
+        impl<'de, V: Deserialize<'de>> Visitor<'de> for PatchOperationVisitor<V> {
+            type Value = PatchOperation<V>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("JSON Patch operation")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let mut op: Option<String> = None;
+                let mut path = None;
+                let mut from = None;
+                let mut value = None;
+                while let Some(key) = map.next_key::<String>()? {
+                    match key.as_str() {
+                        "op" => op = Some(map.next_value()?),
+                        "path" => path = Some(map.next_value()?),
+                        "from" => from = Some(map.next_value()?),
+                        "value" => value = Some(map.next_value()?),
+                        _ => {
+                            map.next_value::<IgnoredAny>()?;
+                        }
+                    }
+                }
+                let op = op.ok_or_else(|| de::Error::missing_field("op"))?;
+                let path = path.ok_or_else(|| de::Error::missing_field("path"))?;
+                let from = || from.ok_or_else(|| de::Error::missing_field("from"));
+                let value = || value.ok_or_else(|| de::Error::missing_field("value"));
+                Ok(match op.as_str() {
+                    "add" => PatchOperation::Add {
+                        path,
+                        value: value()?,
+                    },
+                    "remove" => PatchOperation::Remove { path },
+                    "replace" => PatchOperation::Replace {
+                        path,
+                        value: value()?,
+                    },
+                    "move" => PatchOperation::Move {
+                        from: from()?,
+                        path,
+                    },
+                    "copy" => PatchOperation::Copy {
+                        from: from()?,
+                        path,
+                    },
+                    "test" => PatchOperation::Test {
+                        path,
+                        value: value()?,
+                    },
+                    _ => return Err(de::Error::unknown_variant(&op, OPS)),
+                })
+            }
+        }
+        deserializer.deserialize_map(PatchOperationVisitor(PhantomData))
+    }
+}
+
+/// An error returned when a JSON Patch document cannot be applied to a collection.
+#[derive(Clone, Debug, Eq, PartialEq, Display)]
+pub enum JsonPatchError {
+    /// The path does not refer to an item of the collection.
+    #[display("invalid path `{0}`")]
+    InvalidPath(String),
+    /// No item exists at the path.
+    #[display("no item at `{0}`")]
+    NotFound(String),
+    /// The item at the path differs from the value of a `test` operation.
+    #[display("test failed at `{0}`")]
+    TestFailed(String),
+}
+
+impl std::error::Error for JsonPatchError {}
+
+/// A reference token of a path.
+pub(crate) enum PathToken {
+    Index(usize),
+    /// The `-` token, which refers to the position past the last array item.
+    End,
+}
+
+pub(crate) fn index_path(index: usize) -> String {
+    format!("/{index}")
+}
+
+pub(crate) fn parse_path(path: &str) -> Result<PathToken, JsonPatchError> {
+    let invalid = || JsonPatchError::InvalidPath(path.to_string());
+    let token = path.strip_prefix('/').ok_or_else(invalid)?;
+    if token == "-" {
+        return Ok(PathToken::End);
+    }
+    if token.is_empty()
+        || (token.len() > 1 && token.starts_with('0'))
+        || !token.bytes().all(|b| b.is_ascii_digit())
+    {
+        return Err(invalid());
+    }
+    token.parse().map(PathToken::Index).map_err(|_| invalid())
+}
+
+#[cfg(test)]
+mod tests;
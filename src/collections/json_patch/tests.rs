@@ -0,0 +1,281 @@
+use super::*;
+use crate::{
+    collections::{slab_map::StateSlabMap, vec::StateVec},
+    core::Runtime,
+};
+use pretty_assertions::assert_eq;
+use serde_json::json;
+
+#[test]
+fn patch_operation_serde() {
+    let patch = vec![
+        PatchOperation::Add {
+            path: "/0".to_string(),
+            value: 1,
+        },
+        PatchOperation::Remove {
+            path: "/1".to_string(),
+        },
+        PatchOperation::Move {
+            from: "/2".to_string(),
+            path: "/0".to_string(),
+        },
+        PatchOperation::Test {
+            path: "/0".to_string(),
+            value: 3,
+        },
+    ];
+    let value = serde_json::to_value(&patch).unwrap();
+    assert_eq!(
+        value,
+        json!([
+            { "op": "add", "path": "/0", "value": 1 },
+            { "op": "remove", "path": "/1" },
+            { "op": "move", "from": "/2", "path": "/0" },
+            { "op": "test", "path": "/0", "value": 3 },
+        ])
+    );
+    let deserialized: Vec<PatchOperation<i32>> = serde_json::from_value(value).unwrap();
+    assert_eq!(deserialized, patch);
+}
+
+#[test]
+fn patch_operation_deserialize_errors() {
+    let unknown = json!({ "op": "merge", "path": "/0" });
+    assert!(serde_json::from_value::<PatchOperation<i32>>(unknown).is_err());
+    let missing_value = json!({ "op": "add", "path": "/0" });
+    assert!(serde_json::from_value::<PatchOperation<i32>>(missing_value).is_err());
+}
+
+#[test]
+fn state_vec_json_patch_mirrors_source() {
+    let mut rt = Runtime::new();
+    let source = StateVec::from_iter([1, 2, 3, 4, 5]);
+    let mirror = StateVec::new();
+    let mut reader = source.reader();
+    let mut sync = |rt: &mut Runtime| {
+        let json = serde_json::to_string(&reader.read(&mut rt.sc()).to_json_patch()).unwrap();
+        let patch: Vec<PatchOperation<i32>> = serde_json::from_str(&json).unwrap();
+        mirror.borrow_mut(rt.ac()).apply_json_patch(patch).unwrap();
+        let expected: Vec<_> = source.borrow(&mut rt.sc()).iter().copied().collect();
+        assert_eq!(mirror.borrow(&mut rt.sc()), expected);
+    };
+    sync(&mut rt);
+
+    {
+        let mut items = source.borrow_mut(rt.ac());
+        items.insert(0, 0);
+        items.set(2, 20);
+        items.remove(3);
+        items.move_item(0, 2);
+        items.swap(0, 3);
+        items.swap(1, 2);
+    }
+    sync(&mut rt);
+
+    source.borrow_mut(rt.ac()).sort_by_key(|&x| -x);
+    sync(&mut rt);
+    source.borrow_mut(rt.ac()).reverse();
+    sync(&mut rt);
+    source.borrow_mut(rt.ac()).rotate_left(2);
+    sync(&mut rt);
+    source.borrow_mut(rt.ac()).rotate_right(2);
+    sync(&mut rt);
+}
+
+#[test]
+fn state_vec_apply_json_patch_errors() {
+    let mut rt = Runtime::new();
+    let vec = StateVec::from_iter([1, 2]);
+    let mut items = vec.borrow_mut(rt.ac());
+    let path = |path: &str| path.to_string();
+    assert_eq!(
+        items.apply_json_patch([PatchOperation::Remove { path: path("/2") }]),
+        Err(JsonPatchError::NotFound(path("/2")))
+    );
+    assert_eq!(
+        items.apply_json_patch([PatchOperation::Remove { path: path("/-") }]),
+        Err(JsonPatchError::NotFound(path("/-")))
+    );
+    assert_eq!(
+        items.apply_json_patch([PatchOperation::Remove { path: path("/01") }]),
+        Err(JsonPatchError::InvalidPath(path("/01")))
+    );
+    assert_eq!(
+        items.apply_json_patch([PatchOperation::Remove { path: path("/0/a") }]),
+        Err(JsonPatchError::InvalidPath(path("/0/a")))
+    );
+    assert_eq!(
+        items.apply_json_patch([PatchOperation::Test {
+            path: path("/0"),
+            value: 2,
+        }]),
+        Err(JsonPatchError::TestFailed(path("/0")))
+    );
+    items
+        .apply_json_patch([
+            PatchOperation::Add {
+                path: path("/-"),
+                value: 3,
+            },
+            PatchOperation::Copy {
+                from: path("/0"),
+                path: path("/1"),
+            },
+            PatchOperation::Move {
+                from: path("/0"),
+                path: path("/-"),
+            },
+            PatchOperation::Test {
+                path: path("/3"),
+                value: 1,
+            },
+        ])
+        .unwrap();
+    assert_eq!(items, [1, 2, 3, 1]);
+}
+
+#[test]
+fn state_vec_apply_json_patch_is_atomic() {
+    let mut rt = Runtime::new();
+    let vec = StateVec::from_iter([1, 2]);
+    let mut reader = vec.reader();
+    drop(reader.read(&mut rt.sc()));
+    let path = |path: &str| path.to_string();
+    assert_eq!(
+        vec.borrow_mut(rt.ac()).apply_json_patch([
+            PatchOperation::Add {
+                path: path("/0"),
+                value: 0,
+            },
+            PatchOperation::Move {
+                from: path("/2"),
+                path: path("/0"),
+            },
+            PatchOperation::Test {
+                path: path("/1"),
+                value: 1,
+            },
+        ]),
+        Err(JsonPatchError::TestFailed(path("/1")))
+    );
+    let items = reader.read(&mut rt.sc());
+    assert_eq!(items.delta().count(), 0);
+    assert_eq!(items, [1, 2]);
+}
+
+#[test]
+fn state_slab_map_json_patch_mirrors_source() {
+    let mut rt = Runtime::new();
+    let source = StateSlabMap::new();
+    let mirror = StateSlabMap::new();
+    let mut reader = source.reader();
+    let key0 = source.insert(10, rt.ac());
+    let key1 = source.insert(20, rt.ac());
+
+    let mut sync = |rt: &mut Runtime| {
+        let json = serde_json::to_string(&reader.read(&mut rt.sc()).to_json_patch()).unwrap();
+        let patch: Vec<PatchOperation<i32>> = serde_json::from_str(&json).unwrap();
+        mirror.apply_json_patch(patch, rt.ac()).unwrap();
+    };
+    sync(&mut rt);
+    source.remove(key0, rt.ac());
+    let key2 = source.insert(30, rt.ac());
+    sync(&mut rt);
+    source.set(key1, 21, rt.ac());
+    let mirror_key1 = mirror.member_key(key1).unwrap();
+    sync(&mut rt);
+
+    let mirror_items = |rt: &mut Runtime, names: &[usize]| {
+        let items = mirror.items(&mut rt.sc());
+        let values: Vec<_> = names
+            .iter()
+            .map(|&name| mirror.member_key(name).map(|key| items[key]))
+            .collect();
+        values
+    };
+    assert_eq!(mirror.member_key(key1), Some(mirror_key1));
+    assert_eq!(
+        mirror_items(&mut rt, &[key0, key1, key2]),
+        [None, Some(21), Some(30)]
+    );
+    assert_eq!(mirror.items(&mut rt.sc()).len(), 2);
+
+    let path = |key: usize| format!("/{key}");
+    let mirror_key2 = mirror.member_key(key2).unwrap();
+    mirror
+        .apply_json_patch(
+            [
+                PatchOperation::Replace {
+                    path: path(key1),
+                    value: 22,
+                },
+                PatchOperation::Move {
+                    from: path(key2),
+                    path: path(7),
+                },
+            ],
+            rt.ac(),
+        )
+        .unwrap();
+    assert_eq!(
+        mirror_items(&mut rt, &[key1, key2, 7]),
+        [Some(22), None, Some(30)]
+    );
+    assert_eq!(mirror.member_key(key1), Some(mirror_key1));
+    assert_eq!(mirror.member_key(7), Some(mirror_key2));
+    assert_eq!(
+        mirror.apply_json_patch([PatchOperation::Remove { path: path(key2) }], rt.ac()),
+        Err(JsonPatchError::NotFound(path(key2)))
+    );
+}
+
+#[test]
+fn state_slab_map_json_patch_addresses_unnamed_items_by_key() {
+    let mut rt = Runtime::new();
+    let map: StateSlabMap<i32> = serde_json::from_str(r#"{"3":30,"5":50}"#).unwrap();
+    let path = |key: usize| format!("/{key}");
+    map.apply_json_patch(
+        [
+            PatchOperation::Replace {
+                path: path(3),
+                value: 31,
+            },
+            PatchOperation::Remove { path: path(5) },
+        ],
+        rt.ac(),
+    )
+    .unwrap();
+    assert_eq!(serde_json::to_string(&map).unwrap(), r#"{"3":31}"#);
+}
+
+#[test]
+fn state_slab_map_apply_json_patch_is_atomic() {
+    let mut rt = Runtime::new();
+    let map: StateSlabMap<i32> = serde_json::from_str(r#"{"3":30,"5":50}"#).unwrap();
+    let mut reader = map.reader();
+    drop(reader.read(&mut rt.sc()));
+    let path = |key: usize| format!("/{key}");
+    assert_eq!(
+        map.apply_json_patch(
+            [
+                PatchOperation::Add {
+                    path: path(7),
+                    value: 70,
+                },
+                PatchOperation::Move {
+                    from: path(3),
+                    path: path(8),
+                },
+                PatchOperation::Remove { path: path(5) },
+                PatchOperation::Remove { path: path(3) },
+            ],
+            rt.ac(),
+        ),
+        Err(JsonPatchError::NotFound(path(3)))
+    );
+    assert_eq!(reader.read(&mut rt.sc()).delta().count(), 0);
+    assert_eq!(map.member_key(7), None);
+    assert_eq!(map.member_key(8), None);
+    assert_eq!(serde_json::to_string(&map).unwrap(), r#"{"3":30,"5":50}"#);
+}
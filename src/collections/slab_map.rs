@@ -1,24 +1,38 @@
 use std::{
     any::Any,
     cell::{Ref, RefCell},
+    collections::{HashMap, HashSet},
+    fmt,
+    marker::PhantomData,
     mem,
-    ops::Index,
+    ops::{AddAssign, Index, SubAssign},
     rc::Rc,
 };
 
 use derive_ex::derive_ex;
+use futures::Stream;
+use serde::{
+    Deserialize, Serialize,
+    de::{MapAccess, Visitor},
+};
 use slabmap::SlabMap;
 
 use crate::{
-    ActionContext, SignalContext,
+    ActionContext, Signal, SignalBuilder, SignalContext,
     building_blocks::change_feed::{
         ChangeFeedCursorReader, ChangeFeedDelta, ChangeFeedModel, ChangeFeedReader, ChangeFeedRef,
-        ChangeFeedRefMut, ChangeFeedState, ChangeFeedStorage,
+        ChangeFeedRefMut, ChangeFeedState, ChangeFeedStorage, Coalesced, HistoryLimit,
+    },
+    collections::{
+        aggregate::{Aggregate, CountWhere, Max, Min, Sum},
+        json_patch::{JsonPatchError, PatchOperation, PathToken, index_path, parse_path},
+        vec::{SignalVec, sorted::SortedIds},
     },
     core::{
-        BindKey, BindSink, BindSource, DirtyLevel, NotifyContext, ReactionContext, SinkBindings,
-        Slot, SourceBinder,
+        BindKey, BindSink, BindSource, DirtyLevel, NotifyContext, ReactionContext, Runtime,
+        SinkBindings, Slot, SourceBinder,
     },
+    stream::stream_from_merge,
 };
 
 const SLOT_ITEMS: Slot = Slot(usize::MAX);
@@ -56,6 +70,167 @@ impl<T: 'static> SignalSlabMap<T> {
     pub fn reader(&self) -> SignalSlabMapReader<T> {
         self.0.clone().reader()
     }
+
+    /// Creates a [`SignalVec`] that keeps the items of this map sorted by the key `f` returns.
+    ///
+    /// The sorted vector follows the deltas of this map, placing inserted and removed items by
+    /// binary search. Items with equal sort keys are ordered by their slab keys.
+    pub fn sorted_by_key<K: Ord>(&self, f: impl Fn(&T) -> K + 'static) -> SignalVec<T>
+    where
+        T: Clone,
+    {
+        let mut reader = self.reader();
+        let mut sorted = SortedIds::new();
+        let compare = move |a: &T, b: &T| f(a).cmp(&f(b));
+        SignalVec::from_scan(move |items, sc| {
+            let source = reader.read(sc);
+            if source.is_initial() {
+                sorted = SortedIds::new();
+                items.clear();
+            }
+            for change in source.delta() {
+                match change {
+                    SlabMapChange::Insert { key, new_value } => {
+                        sorted.insert(items, new_value, key, &compare)
+                    }
+                    SlabMapChange::Remove { key, old_value } => {
+                        sorted.remove(items, old_value, key, &compare)
+                    }
+                    SlabMapChange::Set {
+                        key,
+                        new_value,
+                        old_value,
+                    } => sorted.set(items, old_value, new_value, key, &compare),
+                }
+            }
+        })
+    }
+
+    /// Creates a signal with the number of items in this map.
+    pub fn len(&self) -> Signal<usize> {
+        let mut reader = self.reader();
+        SignalBuilder::from_scan_filter(0, move |len, sc| {
+            let new_len = reader.read(sc).len();
+            mem::replace(len, new_len) != new_len
+        })
+        .build()
+    }
+
+    /// Creates a signal with the sum of the items in this map.
+    ///
+    /// The sum is updated by the deltas of this map instead of being recomputed.
+    pub fn sum(&self) -> Signal<T>
+    where
+        T: Clone + Default + AddAssign + SubAssign + PartialEq,
+    {
+        self.aggregate(Sum(T::default()))
+    }
+
+    /// Creates a signal with the number of items for which `predicate` returns `true`.
+    pub fn count_where(&self, predicate: impl Fn(&T) -> bool + 'static) -> Signal<usize> {
+        self.aggregate(CountWhere::new(predicate, |count| count))
+    }
+
+    /// Creates a signal that is `true` if `predicate` returns `true` for any item.
+    pub fn any(&self, predicate: impl Fn(&T) -> bool + 'static) -> Signal<bool> {
+        self.aggregate(CountWhere::new(predicate, |count| count != 0))
+    }
+
+    /// Creates a signal that is `true` if `predicate` returns `true` for all items.
+    pub fn all(&self, predicate: impl Fn(&T) -> bool + 'static) -> Signal<bool> {
+        self.aggregate(CountWhere::new(
+            move |value: &T| !predicate(value),
+            |count| count == 0,
+        ))
+    }
+
+    /// Creates a signal with the smallest item, or `None` if this map is empty.
+    ///
+    /// The items are counted in an ordered map, so removing an item does not rescan the map.
+    pub fn min(&self) -> Signal<Option<T>>
+    where
+        T: Ord + Clone,
+    {
+        self.aggregate(Min::new())
+    }
+
+    /// Creates a signal with the largest item, or `None` if this map is empty.
+    ///
+    /// The items are counted in an ordered map, so removing an item does not rescan the map.
+    pub fn max(&self) -> Signal<Option<T>>
+    where
+        T: Ord + Clone,
+    {
+        self.aggregate(Max::new())
+    }
+
+    /// Creates a signal that folds the items of this map incrementally.
+    ///
+    /// `add` is called for each inserted item and `remove` for each removed item. A replaced item
+    /// is removed and then added.
+    pub fn fold_incremental<St: Clone + 'static>(
+        &self,
+        initial_state: St,
+        mut add: impl FnMut(&mut St, &T) + 'static,
+        mut remove: impl FnMut(&mut St, &T) + 'static,
+    ) -> Signal<St> {
+        let mut reader = self.reader();
+        SignalBuilder::from_scan(initial_state.clone(), move |st, sc| {
+            let source = reader.read(sc);
+            if source.is_initial() {
+                *st = initial_state.clone();
+            }
+            for change in source.delta() {
+                match change {
+                    SlabMapChange::Insert { new_value, .. } => add(st, new_value),
+                    SlabMapChange::Remove { old_value, .. } => remove(st, old_value),
+                    SlabMapChange::Set {
+                        new_value,
+                        old_value,
+                        ..
+                    } => {
+                        remove(st, old_value);
+                        add(st, new_value);
+                    }
+                }
+            }
+        })
+        .build()
+    }
+
+    fn aggregate<A: Aggregate<T>>(&self, mut aggregate: A) -> Signal<A::Output> {
+        let mut reader = self.reader();
+        let output = aggregate.output();
+        SignalBuilder::from_scan_filter((aggregate, output), move |(aggregate, output), sc| {
+            let source = reader.read(sc);
+            if source.is_initial() {
+                aggregate.clear();
+            }
+            for change in source.delta() {
+                match change {
+                    SlabMapChange::Insert { new_value, .. } => aggregate.add(new_value),
+                    SlabMapChange::Remove { old_value, .. } => aggregate.remove(old_value),
+                    SlabMapChange::Set {
+                        new_value,
+                        old_value,
+                        ..
+                    } => {
+                        aggregate.remove(old_value);
+                        aggregate.add(new_value);
+                    }
+                }
+            }
+            let new_output = aggregate.output();
+            if *output != new_output {
+                *output = new_output;
+                true
+            } else {
+                false
+            }
+        })
+        .map(|(_, output)| output)
+        .build()
+    }
 }
 
 trait DynSignalSlabMap<T> {
@@ -113,6 +288,30 @@ impl<T: 'static> SignalSlabMapReader<T> {
             }
         }
     }
+
+    /// Converts this reader into a [`Stream`] of owned delta batches.
+    ///
+    /// The first batch is an [`Initial`](OwnedSlabMapChange::Initial) snapshot of the items.
+    /// Batches that have not been consumed yet are combined into one.
+    pub fn into_delta_stream(
+        mut self,
+    ) -> impl Stream<Item = Vec<OwnedSlabMapChange<T>>> + Unpin + 'static
+    where
+        T: Clone,
+    {
+        stream_from_merge(
+            move |sc| self.read(sc).owned_delta(),
+            |pending, batch| match pending {
+                Some(mut pending)
+                    if !matches!(batch.first(), Some(OwnedSlabMapChange::Initial(_))) =>
+                {
+                    pending.extend(batch);
+                    Some(pending)
+                }
+                _ => (!batch.is_empty()).then_some(batch),
+            },
+        )
+    }
 }
 
 pub struct Items<'a, T: 'static> {
@@ -137,7 +336,15 @@ impl<'a, T: 'static> Items<'a, T> {
     }
 
     pub fn iter(&self) -> Iter<'_, T> {
-        Iter(self.value.current().0.items.iter())
+        self.value.current().0.iter()
+    }
+
+    /// Returns whether this is an initial read, whose delta inserts every current item.
+    ///
+    /// A reader that falls behind the [`HistoryLimit`] of the map gets an initial read again,
+    /// and must then discard what it built from earlier deltas.
+    pub fn is_initial(&self) -> bool {
+        matches!(self.value.delta(), ChangeFeedDelta::Initial)
     }
 
     /// Returns the delta needed to reproduce the current items from the reader's baseline.
@@ -152,7 +359,8 @@ impl<'a, T: 'static> Items<'a, T> {
                 .into_iter0(),
             ChangeFeedDelta::Incremental(changes) => changes
                 .map(|change| {
-                    let value = &self.value.current().0.items[change.key].value;
+                    let values = &self.value.current().0.values;
+                    let value = &values[change.value];
                     match change.action {
                         ChangeAction::Insert => SlabMapChange::Insert {
                             key: change.key,
@@ -162,11 +370,35 @@ impl<'a, T: 'static> Items<'a, T> {
                             key: change.key,
                             old_value: value,
                         },
+                        ChangeAction::Set { old_value } => SlabMapChange::Set {
+                            key: change.key,
+                            new_value: value,
+                            old_value: &values[old_value],
+                        },
                     }
                 })
                 .into_iter1(),
         }
     }
+
+    /// Returns the delta as JSON Patch operations on an object whose member names are the keys.
+    pub fn to_json_patch(&self) -> Vec<PatchOperation<&T>> {
+        self.delta().map(|change| change.to_json_patch()).collect()
+    }
+
+    fn owned_delta(&self) -> Vec<OwnedSlabMapChange<T>>
+    where
+        T: Clone,
+    {
+        match self.value.delta() {
+            ChangeFeedDelta::Initial => vec![OwnedSlabMapChange::Initial(
+                self.iter()
+                    .map(|(key, value)| (key, value.clone()))
+                    .collect(),
+            )],
+            ChangeFeedDelta::Incremental(_) => self.delta().map(OwnedSlabMapChange::from).collect(),
+        }
+    }
 }
 
 impl<T: 'static> Index<usize> for Items<'_, T> {
@@ -187,7 +419,8 @@ impl<'a, T: 'static> IntoIterator for &'a Items<'a, T> {
 }
 
 pub struct ItemsMut<T> {
-    items: SlabMap<Item<T>>,
+    items: SlabMap<Item>,
+    values: SlabMap<T>,
     len: usize,
     pending_changes: Vec<ChangeData>,
 }
@@ -196,11 +429,19 @@ impl<T> ItemsMut<T> {
     fn new() -> Self {
         Self {
             items: SlabMap::new(),
+            values: SlabMap::new(),
             len: 0,
             pending_changes: Vec::new(),
         }
     }
 
+    fn iter(&self) -> Iter<'_, T> {
+        Iter {
+            items: self.items.iter(),
+            values: &self.values,
+        }
+    }
+
     fn take_changes(&mut self) -> Vec<ChangeData> {
         mem::take(&mut self.pending_changes)
     }
@@ -208,18 +449,20 @@ impl<T> ItemsMut<T> {
     pub fn get(&self, key: usize) -> Option<&T> {
         let item = self.items.get(key)?;
         if item.is_exists {
-            Some(&item.value)
+            Some(&self.values[item.value])
         } else {
             None
         }
     }
 
     pub fn insert(&mut self, value: T) -> usize {
+        let value = self.values.insert(value);
         let key = self.items.insert(Item::new(value));
         self.len += 1;
         self.pending_changes.push(ChangeData {
             action: ChangeAction::Insert,
             key,
+            value,
         });
         key
     }
@@ -232,6 +475,19 @@ impl<T> ItemsMut<T> {
         self.pending_changes.push(ChangeData {
             action: ChangeAction::Remove,
             key,
+            value: item.value,
+        });
+    }
+
+    /// Replaces the item at `key`, keeping its key.
+    pub fn set(&mut self, key: usize, value: T) {
+        assert!(self.items[key].is_exists);
+        let value = self.values.insert(value);
+        let old_value = mem::replace(&mut self.items[key].value, value);
+        self.pending_changes.push(ChangeData {
+            action: ChangeAction::Set { old_value },
+            key,
+            value,
         });
     }
 }
@@ -250,8 +506,47 @@ impl<T: 'static> ChangeFeedModel for SlabMapModel<T> {
     type Change = ChangeData;
 
     fn release_change(&mut self, change: Self::Change) {
-        if matches!(change.action, ChangeAction::Remove) {
-            self.0.items.remove(change.key);
+        match change.action {
+            ChangeAction::Insert => {}
+            ChangeAction::Remove => {
+                self.0.items.remove(change.key);
+                self.0.values.remove(change.value);
+            }
+            ChangeAction::Set { old_value } => {
+                self.0.values.remove(old_value);
+            }
+        }
+    }
+
+    fn change_size(&self, change: &Self::Change) -> usize {
+        mem::size_of::<ChangeData>()
+            + match change.action {
+                ChangeAction::Insert => 0,
+                ChangeAction::Remove | ChangeAction::Set { .. } => mem::size_of::<T>(),
+            }
+    }
+
+    fn coalesce(&mut self, prev: &mut Self::Change, next: Self::Change) -> Coalesced<Self::Change> {
+        if prev.key != next.key {
+            return Coalesced::Separate(next);
+        }
+        match (prev.action, next.action) {
+            (ChangeAction::Insert, ChangeAction::Remove) => {
+                self.release_change(next);
+                Coalesced::Cancelled
+            }
+            (ChangeAction::Insert | ChangeAction::Set { .. }, ChangeAction::Set { old_value }) => {
+                self.0.values.remove(old_value);
+                prev.value = next.value;
+                Coalesced::Merged
+            }
+            (ChangeAction::Set { old_value }, ChangeAction::Remove) => {
+                self.0.values.remove(prev.value);
+                prev.action = ChangeAction::Remove;
+                prev.value = old_value;
+                Coalesced::Merged
+            }
+            _ => Coalesced::Separate(next),
         }
     }
 }
@@ -290,28 +585,31 @@ impl<T: 'static> Drop for PendingEdit<'_, '_, T> {
     }
 }
 
-pub struct Iter<'a, T>(slabmap::Iter<'a, Item<T>>);
+pub struct Iter<'a, T> {
+    items: slabmap::Iter<'a, Item>,
+    values: &'a SlabMap<T>,
+}
 
 impl<'a, T> Iterator for Iter<'a, T> {
     type Item = (usize, &'a T);
 
     fn next(&mut self) -> Option<Self::Item> {
-        for (key, value) in self.0.by_ref() {
-            if value.is_exists {
-                return Some((key, &value.value));
+        for (key, item) in self.items.by_ref() {
+            if item.is_exists {
+                return Some((key, &self.values[item.value]));
             }
         }
         None
     }
 }
 
-struct Item<T> {
-    value: T,
+struct Item {
+    value: usize,
     is_exists: bool,
 }
 
-impl<T> Item<T> {
-    fn new(value: T) -> Self {
+impl Item {
+    fn new(value: usize) -> Self {
         Self {
             value,
             is_exists: true,
@@ -320,20 +618,100 @@ impl<T> Item<T> {
 }
 
 #[derive(Clone, Copy, Debug, Eq, PartialEq)]
+#[non_exhaustive]
 pub enum SlabMapChange<'a, T> {
-    Insert { key: usize, new_value: &'a T },
-    Remove { key: usize, old_value: &'a T },
+    Insert {
+        key: usize,
+        new_value: &'a T,
+    },
+    Remove {
+        key: usize,
+        old_value: &'a T,
+    },
+    Set {
+        key: usize,
+        new_value: &'a T,
+        old_value: &'a T,
+    },
+}
+
+impl<'a, T> SlabMapChange<'a, T> {
+    /// Converts this change into a JSON Patch operation on an object whose member names are the
+    /// keys.
+    pub fn to_json_patch(&self) -> PatchOperation<&'a T> {
+        match *self {
+            SlabMapChange::Insert { key, new_value } => PatchOperation::Add {
+                path: index_path(key),
+                value: new_value,
+            },
+            SlabMapChange::Remove { key, .. } => PatchOperation::Remove {
+                path: index_path(key),
+            },
+            SlabMapChange::Set { key, new_value, .. } => PatchOperation::Replace {
+                path: index_path(key),
+                value: new_value,
+            },
+        }
+    }
+}
+
+/// An owned counterpart of [`SlabMapChange`], used to pass deltas outside of a [`SignalContext`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+#[non_exhaustive]
+pub enum OwnedSlabMapChange<T> {
+    /// Replaces all items with the given key-value pairs.
+    Initial(Vec<(usize, T)>),
+    Insert {
+        key: usize,
+        new_value: T,
+    },
+    Remove {
+        key: usize,
+        old_value: T,
+    },
+    Set {
+        key: usize,
+        new_value: T,
+        old_value: T,
+    },
+}
+
+impl<T: Clone> From<SlabMapChange<'_, T>> for OwnedSlabMapChange<T> {
+    fn from(change: SlabMapChange<'_, T>) -> Self {
+        match change {
+            SlabMapChange::Insert { key, new_value } => Self::Insert {
+                key,
+                new_value: new_value.clone(),
+            },
+            SlabMapChange::Remove { key, old_value } => Self::Remove {
+                key,
+                old_value: old_value.clone(),
+            },
+            SlabMapChange::Set {
+                key,
+                new_value,
+                old_value,
+            } => Self::Set {
+                key,
+                new_value: new_value.clone(),
+                old_value: old_value.clone(),
+            },
+        }
+    }
 }
 
 #[derive(Clone, Copy)]
 enum ChangeAction {
     Insert,
     Remove,
+    Set { old_value: usize },
 }
 
 struct ChangeData {
     action: ChangeAction,
     key: usize,
+    // The key in `ItemsMut::values` of the inserted, removed or new value.
+    value: usize,
 }
 
 #[derive_ex(Default, Clone(bound()))]
@@ -342,9 +720,13 @@ pub struct StateSlabMap<T: 'static>(Rc<RawStateSlabMap<T>>);
 
 impl<T: 'static> StateSlabMap<T> {
     pub fn new() -> Self {
+        Self::from_items(ItemsMut::new())
+    }
+    fn from_items(items: ItemsMut<T>) -> Self {
         Self(Rc::new(RawStateSlabMap {
-            state: ChangeFeedState::new(SlabMapModel(ItemsMut::new())),
+            state: ChangeFeedState::new(SlabMapModel(items)),
             item_sinks: RefCell::new(ItemSinkBindings::new()),
+            patch_keys: RefCell::new(PatchKeys::new()),
         }))
     }
 
@@ -371,6 +753,18 @@ impl<T: 'static> StateSlabMap<T> {
             let keys = record_pending(&mut edit);
             debug_assert_eq!(keys, [key]);
         }
+        self.0.patch_keys.borrow_mut().remove_key(key);
+        self.0.item_sinks.borrow_mut().notify(key, ac.nc());
+    }
+
+    /// Replaces the item at `key`, keeping its key.
+    pub fn set(&self, key: usize, value: T, ac: &mut ActionContext) {
+        {
+            let mut edit = self.0.state.borrow_mut(ac);
+            edit.current_mut().0.set(key, value);
+            let keys = record_pending(&mut edit);
+            debug_assert_eq!(keys, [key]);
+        }
         self.0.item_sinks.borrow_mut().notify(key, ac.nc());
     }
 
@@ -386,11 +780,283 @@ impl<T: 'static> StateSlabMap<T> {
     pub fn reader(&self) -> SignalSlabMapReader<T> {
         SignalSlabMapReader::from_state(self.0.state.reader())
     }
+
+    /// Sets the limit of the changes retained for readers of this map.
+    ///
+    /// A reader that falls behind the limit reads the items as [`Items::is_initial`] again.
+    pub fn set_history_limit(&self, limit: HistoryLimit) {
+        self.0.state.set_history_limit(limit);
+    }
+
+    /// Applies the operations of a JSON Patch document in order.
+    ///
+    /// Keys cannot be chosen when inserting into a slab map, so this map keeps the key of each
+    /// member added by a patch, and addresses any other item by its own key.
+    /// Replacing a member replaces the item in place and moving a member renames it, so the item
+    /// keeps its key in both cases.
+    ///
+    /// If an operation cannot be applied, returns its error and applies none of the operations.
+    pub fn apply_json_patch(
+        &self,
+        patch: impl IntoIterator<Item = PatchOperation<T>>,
+        ac: &mut ActionContext,
+    ) -> Result<(), JsonPatchError>
+    where
+        T: Clone + PartialEq,
+    {
+        let patch: Vec<_> = patch.into_iter().collect();
+        self.check_json_patch(&patch)?;
+        for operation in patch {
+            match operation {
+                PatchOperation::Add { path, value } => {
+                    self.patch_add(patch_key(&path)?, value, ac);
+                }
+                PatchOperation::Remove { path } => {
+                    let key = self.patch_find(&path)?;
+                    self.remove(key, ac);
+                }
+                PatchOperation::Replace { path, value } => {
+                    let key = self.patch_find(&path)?;
+                    self.set(key, value, ac);
+                }
+                PatchOperation::Move { from, path } => {
+                    let name = patch_key(&path)?;
+                    let key = self.patch_find(&from)?;
+                    if patch_key(&from)? != name {
+                        if let Some(old_key) = self.member_key(name) {
+                            self.remove(old_key, ac);
+                        }
+                        let mut patch_keys = self.0.patch_keys.borrow_mut();
+                        patch_keys.remove_key(key);
+                        patch_keys.insert(name, key);
+                    }
+                }
+                PatchOperation::Copy { from, path } => {
+                    let name = patch_key(&path)?;
+                    let value = self.patch_value(&from)?;
+                    self.patch_add(name, value, ac);
+                }
+                PatchOperation::Test { .. } => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies `patch` to references to the members, so that a failing operation is found before
+    /// any item changes.
+    fn check_json_patch(&self, patch: &[PatchOperation<T>]) -> Result<(), JsonPatchError>
+    where
+        T: PartialEq,
+    {
+        let model = self.0.state.current_ref_untracked();
+        let mut members = HashMap::<usize, Option<&T>>::new();
+        let current = |name| self.member_key(name).map(|key| &model.0[key]);
+        for operation in patch {
+            match operation {
+                PatchOperation::Add { path, value } => {
+                    members.insert(patch_key(path)?, Some(value));
+                }
+                PatchOperation::Remove { path } => {
+                    patch_member(&members, path, current)?;
+                    members.insert(patch_key(path)?, None);
+                }
+                PatchOperation::Replace { path, value } => {
+                    patch_member(&members, path, current)?;
+                    members.insert(patch_key(path)?, Some(value));
+                }
+                PatchOperation::Move { from, path } => {
+                    let name = patch_key(path)?;
+                    let value = patch_member(&members, from, current)?;
+                    members.insert(patch_key(from)?, None);
+                    members.insert(name, Some(value));
+                }
+                PatchOperation::Copy { from, path } => {
+                    let name = patch_key(path)?;
+                    let value = patch_member(&members, from, current)?;
+                    members.insert(name, Some(value));
+                }
+                PatchOperation::Test { path, value } => {
+                    if patch_member(&members, path, current)? != value {
+                        return Err(JsonPatchError::TestFailed(path.clone()));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns the key of the item that JSON Patch operations address as the member `name`.
+    ///
+    /// See [`apply_json_patch`](Self::apply_json_patch).
+    pub fn member_key(&self, name: usize) -> Option<usize> {
+        if let Some(key) = self.0.patch_keys.borrow().key(name) {
+            return Some(key);
+        }
+        let is_named = self.0.patch_keys.borrow().is_named(name);
+        let is_exists = self.0.state.current_ref_untracked().0.get(name).is_some();
+        (!is_named && is_exists).then_some(name)
+    }
+
+    fn patch_add(&self, name: usize, value: T, ac: &mut ActionContext) {
+        if let Some(key) = self.member_key(name) {
+            self.set(key, value, ac);
+        } else {
+            let key = self.insert(value, ac);
+            self.0.patch_keys.borrow_mut().insert(name, key);
+        }
+    }
+    fn patch_find(&self, path: &str) -> Result<usize, JsonPatchError> {
+        self.member_key(patch_key(path)?)
+            .ok_or_else(|| JsonPatchError::NotFound(path.to_string()))
+    }
+    fn patch_value(&self, path: &str) -> Result<T, JsonPatchError>
+    where
+        T: Clone,
+    {
+        let key = self.patch_find(path)?;
+        Ok(self.0.state.current_ref_untracked().0[key].clone())
+    }
+}
+
+/// Serializes the items as a map from keys to values.
+impl<T: Serialize> Serialize for StateSlabMap<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        let value = self
+            .0
+            .state
+            .try_borrow_contextless()
+            .map_err(serde::ser::Error::custom)?;
+        serializer.collect_map(value.current().0.iter())
+    }
+}
+
+/// Deserializes a map from keys to values, keeping the keys.
+impl<'de, T: Deserialize<'de> + 'static> Deserialize<'de> for StateSlabMap<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        struct StateSlabMapVisitor<T>(PhantomData<fn(T)>);
+        impl<'de, T: Deserialize<'de> + 'static> Visitor<'de> for StateSlabMapVisitor<T> {
+            type Value = StateSlabMap<T>;
+
+            fn expecting(&self, formatter: &mut fmt::Formatter) -> fmt::Result {
+                formatter.write_str("map with integer keys")
+            }
+
+            fn visit_map<A>(self, mut map: A) -> Result<Self::Value, A::Error>
+            where
+                A: MapAccess<'de>,
+            {
+                let mut entries = Vec::new();
+                let mut values = SlabMap::new();
+                let mut keys = HashSet::new();
+                while let Some((key, value)) = map.next_entry::<usize, T>()? {
+                    if !keys.insert(key) {
+                        return Err(serde::de::Error::custom(format!("duplicate key {key}")));
+                    }
+                    entries.push((key, Item::new(values.insert(value))));
+                }
+                let len = entries.len();
+                let items = SlabMap::from_iter_with_capacity(entries, len);
+                Ok(StateSlabMap::from_items(ItemsMut {
+                    items,
+                    values,
+                    len,
+                    pending_changes: Vec::new(),
+                }))
+            }
+        }
+        deserializer.deserialize_map(StateSlabMapVisitor(PhantomData))
+    }
+}
+
+/// Serializes the current items as a map from keys to values.
+///
+/// Items are read through [`Runtime::call`],
+/// so serialization fails unless the runtime is lent with [`Runtime::lend`].
+impl<T: Serialize + 'static> Serialize for SignalSlabMap<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        Runtime::try_call(|rt| serializer.collect_map(self.items(&mut rt.sc()).iter()))
+            .unwrap_or_else(|e| Err(serde::ser::Error::custom(e)))
+    }
+}
+impl<'de, T: Deserialize<'de> + 'static> Deserialize<'de> for SignalSlabMap<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        StateSlabMap::deserialize(deserializer).map(|map| map.to_signal_slab_map())
+    }
+}
+
+fn patch_member<'a, T>(
+    members: &HashMap<usize, Option<&'a T>>,
+    path: &str,
+    current: impl FnOnce(usize) -> Option<&'a T>,
+) -> Result<&'a T, JsonPatchError> {
+    let name = patch_key(path)?;
+    let value = match members.get(&name) {
+        Some(value) => *value,
+        None => current(name),
+    };
+    value.ok_or_else(|| JsonPatchError::NotFound(path.to_string()))
+}
+
+fn patch_key(path: &str) -> Result<usize, JsonPatchError> {
+    match parse_path(path)? {
+        PathToken::Index(key) => Ok(key),
+        PathToken::End => Err(JsonPatchError::InvalidPath(path.to_string())),
+    }
 }
 
 struct RawStateSlabMap<T: 'static> {
     state: ChangeFeedState<SlabMapModel<T>>,
     item_sinks: RefCell<ItemSinkBindings>,
+    patch_keys: RefCell<PatchKeys>,
+}
+
+/// The keys of the items added by JSON Patch operations, by member name.
+struct PatchKeys {
+    keys: HashMap<usize, usize>,
+    names: HashMap<usize, usize>,
+}
+
+impl PatchKeys {
+    fn new() -> Self {
+        Self {
+            keys: HashMap::new(),
+            names: HashMap::new(),
+        }
+    }
+
+    fn key(&self, name: usize) -> Option<usize> {
+        self.keys.get(&name).copied()
+    }
+
+    /// Returns whether the item at `key` was added by a patch and is addressed by its name.
+    fn is_named(&self, key: usize) -> bool {
+        self.names.contains_key(&key)
+    }
+
+    fn insert(&mut self, name: usize, key: usize) {
+        if let Some(old_key) = self.keys.insert(name, key) {
+            self.names.remove(&old_key);
+        }
+        self.names.insert(key, name);
+    }
+
+    fn remove_key(&mut self, key: usize) {
+        if let Some(name) = self.names.remove(&key) {
+            self.keys.remove(&name);
+        }
+    }
 }
 
 impl<T: 'static> RawStateSlabMap<T> {
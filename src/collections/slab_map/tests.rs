@@ -1,5 +1,6 @@
 use super::*;
-use crate::{State, core::Runtime, effect};
+use crate::{State, collections::vec::VecChange, core::Runtime, effect};
+use futures::{FutureExt, StreamExt};
 use pretty_assertions::assert_eq;
 use std::{cell::Cell, rc::Rc};
 
@@ -95,6 +96,38 @@ fn signal_slab_map_from_scan_updates() {
     }
 }
 
+#[test]
+fn signal_slab_map_from_scan_coalesces_insert_and_remove() {
+    let mut rt = Runtime::new();
+    let state = State::new(0);
+    let old = Rc::new(String::from("old"));
+    let map = SignalSlabMap::from_scan({
+        let state = state.clone();
+        let old = old.clone();
+        move |items, sc| {
+            let n = state.get(sc);
+            let key = items.insert(old.clone());
+            items.remove(key);
+            items.insert(Rc::new(n.to_string()));
+        }
+    });
+    let mut reader = map.reader();
+    drop(reader.read(&mut rt.sc()));
+
+    state.set(1, rt.ac());
+    let items = reader.read(&mut rt.sc());
+    let delta = items
+        .delta()
+        .map(|change| match change {
+            SlabMapChange::Insert { new_value, .. } => format!("+{new_value}"),
+            SlabMapChange::Remove { old_value, .. } => format!("-{old_value}"),
+            SlabMapChange::Set { new_value, .. } => format!("={new_value}"),
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(delta, ["+1"]);
+    assert_eq!(Rc::strong_count(&old), 2);
+}
+
 #[test]
 fn state_slab_map_reader_peek_and_clone_have_independent_cursors() {
     let mut rt = Runtime::new();
@@ -199,6 +232,78 @@ fn state_slab_map_retries_compaction_after_an_item_borrow() {
     assert_eq!(Rc::strong_count(&old), 1);
 }
 
+#[test]
+fn state_slab_map_set_keeps_key_and_releases_old_values() {
+    let mut rt = Runtime::new();
+    let map = StateSlabMap::new();
+    let old = Rc::new(String::from("old"));
+    let key = map.insert(old.clone(), rt.ac());
+    let mut reader = map.reader();
+    drop(reader.read(&mut rt.sc()));
+
+    let new = Rc::new(String::from("new"));
+    map.set(key, new.clone(), rt.ac());
+    assert_eq!(map.items(&mut rt.sc()).get(key), Some(&new));
+    assert_eq!(Rc::strong_count(&old), 2);
+    {
+        let items = reader.read(&mut rt.sc());
+        assert_eq!(
+            items.delta().collect::<Vec<_>>(),
+            [SlabMapChange::Set {
+                key,
+                new_value: &new,
+                old_value: &old,
+            }]
+        );
+    }
+    assert_eq!(Rc::strong_count(&old), 1);
+    drop(reader);
+
+    let sum = map.to_signal_slab_map().fold_incremental(
+        0,
+        |n, value: &Rc<String>| *n += value.len(),
+        |n, value| *n -= value.len(),
+    );
+    assert_eq!(sum.get(&mut rt.sc()), 3);
+    map.set(key, Rc::new(String::from("newer")), rt.ac());
+    map.set(key, Rc::new(String::from("newest")), rt.ac());
+    assert_eq!(sum.get(&mut rt.sc()), 6);
+    assert_eq!(Rc::strong_count(&new), 1);
+}
+
+#[test]
+fn signal_slab_map_from_scan_coalesces_sets() {
+    let mut rt = Runtime::new();
+    let state = State::new(0);
+    let map = SignalSlabMap::from_scan({
+        let state = state.clone();
+        let mut key = None;
+        move |items, sc| {
+            let n = state.get(sc);
+            match key {
+                None => key = Some(items.insert(n)),
+                Some(key) => {
+                    items.set(key, n * 10);
+                    items.set(key, n);
+                }
+            }
+        }
+    });
+    let mut reader = map.reader();
+    drop(reader.read(&mut rt.sc()));
+
+    state.set(1, rt.ac());
+    let items = reader.read(&mut rt.sc());
+    assert_eq!(
+        items.delta().collect::<Vec<_>>(),
+        [SlabMapChange::Set {
+            key: 0,
+            new_value: &1,
+            old_value: &0,
+        }]
+    );
+}
+
 #[test]
 fn state_slab_map_tracks_item_and_all_items_separately() {
     let mut rt = Runtime::new();
@@ -338,3 +443,162 @@ fn signal_slab_map_scan_readers_share_history_with_independent_cursors() {
         [SlabMapChange::Insert { key, new_value: &1 }]
     );
 }
+
+#[test]
+fn signal_slab_map_sorted_by_key() {
+    let mut rt = Runtime::new();
+    let map = StateSlabMap::new();
+    let key = map.insert(3, rt.ac());
+    map.insert(1, rt.ac());
+    let sorted = map.to_signal_slab_map().sorted_by_key(|&value| value);
+    let mut reader = sorted.reader();
+    assert_eq!(
+        reader
+            .read(&mut rt.sc())
+            .iter()
+            .copied()
+            .collect::<Vec<_>>(),
+        vec![1, 3]
+    );
+
+    map.insert(2, rt.ac());
+    assert_eq!(
+        reader.read(&mut rt.sc()).delta().collect::<Vec<_>>(),
+        vec![VecChange::Insert {
+            index: 1,
+            new_value: &2,
+        }]
+    );
+
+    map.remove(key, rt.ac());
+    assert_eq!(
+        reader.read(&mut rt.sc()).delta().collect::<Vec<_>>(),
+        vec![VecChange::Remove {
+            index: 2,
+            old_value: &3,
+        }]
+    );
+
+    map.set(1, 5, rt.ac());
+    assert_eq!(
+        reader
+            .read(&mut rt.sc())
+            .iter()
+            .copied()
+            .collect::<Vec<_>>(),
+        vec![2, 5]
+    );
+}
+
+#[test]
+fn signal_slab_map_aggregates() {
+    let mut rt = Runtime::new();
+    let map = StateSlabMap::new();
+    let s = map.to_signal_slab_map();
+    let len = s.len();
+    let sum = s.sum();
+    let min = s.min();
+    let max = s.max();
+    let any_even = s.any(|x| x % 2 == 0);
+    let count = s.fold_incremental(0, |n, _| *n += 1, |n, _| *n -= 1);
+
+    let key1 = map.insert(3, rt.ac());
+    let key2 = map.insert(5, rt.ac());
+    map.insert(4, rt.ac());
+    {
+        let sc = &mut rt.sc();
+        assert_eq!(
+            (len.get(sc), sum.get(sc), min.get(sc), max.get(sc)),
+            (3, 12, Some(3), Some(5))
+        );
+        assert!(any_even.get(sc));
+        assert_eq!(count.get(sc), 3);
+    }
+
+    map.remove(key1, rt.ac());
+    map.remove(key2, rt.ac());
+    {
+        let sc = &mut rt.sc();
+        assert_eq!(
+            (len.get(sc), sum.get(sc), min.get(sc), max.get(sc)),
+            (1, 4, Some(4), Some(4))
+        );
+        assert_eq!(count.get(sc), 1);
+    }
+}
+
+#[test]
+fn signal_slab_map_reader_into_delta_stream() {
+    let mut rt = Runtime::new();
+    let map = StateSlabMap::new();
+    let key0 = map.insert(10, rt.ac());
+    let mut stream = map.reader().into_delta_stream();
+    assert_eq!(stream.next().now_or_never(), None);
+
+    rt.flush();
+    assert_eq!(
+        stream.next().now_or_never(),
+        Some(Some(vec![OwnedSlabMapChange::Initial(vec![(key0, 10)])]))
+    );
+
+    let key1 = map.insert(20, rt.ac());
+    map.remove(key0, rt.ac());
+    rt.flush();
+    assert_eq!(
+        stream.next().now_or_never(),
+        Some(Some(vec![
+            OwnedSlabMapChange::Insert {
+                key: key1,
+                new_value: 20,
+            },
+            OwnedSlabMapChange::Remove {
+                key: key0,
+                old_value: 10,
+            },
+        ]))
+    );
+    rt.flush();
+    assert_eq!(stream.next().now_or_never(), None);
+}
+
+#[test]
+fn state_slab_map_serde_preserves_keys() {
+    let mut rt = Runtime::new();
+    let map = StateSlabMap::new();
+    let key0 = map.insert(10, rt.ac());
+    let key1 = map.insert(20, rt.ac());
+    let key2 = map.insert(30, rt.ac());
+    map.remove(key1, rt.ac());
+    let json = serde_json::to_string(&map).unwrap();
+    assert_eq!(json, format!(r#"{{"{key0}":10,"{key2}":30}}"#));
+
+    let restored: StateSlabMap<i32> = serde_json::from_str(&json).unwrap();
+    let items = restored.items(&mut rt.sc());
+    assert_eq!(items.iter().collect::<Vec<_>>(), [(key0, &10), (key2, &30)]);
+    assert_eq!(items.len(), 2);
+    drop(items);
+
+    let key3 = restored.insert(40, rt.ac());
+    assert!(key3 != key0 && key3 != key2);
+    assert!(serde_json::from_str::<StateSlabMap<i32>>(r#"{"1":1,"1":2}"#).is_err());
+}
+
+#[test]
+fn signal_slab_map_serde() {
+    let mut rt = Runtime::new();
+    let map = StateSlabMap::new();
+    let key = map.insert(10, rt.ac());
+    let signal = map.to_signal_slab_map();
+    let e = serde_json::to_string(&signal).unwrap_err();
+    assert!(e.to_string().contains("Runtime::lend"), "{e}");
+    {
+        let _lend = rt.lend();
+        assert_eq!(
+            serde_json::to_string(&signal).unwrap(),
+            format!(r#"{{"{key}":10}}"#)
+        );
+    }
+
+    let restored: SignalSlabMap<i32> = serde_json::from_str(r#"{"3":30}"#).unwrap();
+    assert_eq!(restored.items(&mut rt.sc()).get(3), Some(&30));
+}
@@ -1,24 +1,51 @@
 use std::{
+    cell::{Cell, RefCell},
     cmp::Ordering,
+    collections::{BTreeMap, HashMap, HashSet, btree_map::Entry},
     fmt::{self, Debug},
+    hash::Hash,
     marker::PhantomData,
-    ops::{Deref, DerefMut, Index, RangeBounds},
+    mem,
+    ops::{AddAssign, Deref, DerefMut, Index, Range, RangeBounds, SubAssign},
+    pin::pin,
     rc::Rc,
 };
 
 use derive_ex::{Ex, derive_ex};
+use futures::{Stream, StreamExt};
+use parse_display::Display;
 use serde::{Deserialize, Serialize};
 use slabmap::SlabMap;
 
 use crate::{
-    ActionContext, SignalContext,
+    ActionContext, AsyncActionContext, Signal, SignalBuilder, SignalContext, StateRef,
     building_blocks::change_feed::{
         ChangeFeedDelta, ChangeFeedModel, ChangeFeedReader, ChangeFeedRef, ChangeFeedRefMut,
-        ChangeFeedSignal, ChangeFeedState,
+        ChangeFeedSignal, ChangeFeedState, Coalesced, HistoryLimit,
     },
+    collections::{
+        aggregate::{Aggregate, CountWhere, Max, Min, Sum},
+        json_patch::{JsonPatchError, PatchOperation, PathToken, index_path, parse_path},
+    },
+    core::{BindSink, Runtime},
+    stream::stream_from_merge,
     utils::{IndexNewToOld, is_sorted, to_range},
 };
 
+mod diff;
+mod fenwick;
+mod filter;
+mod group_by;
+mod item_tracker;
+pub(crate) mod sorted;
+
+use self::{
+    filter::Filter,
+    group_by::GroupRouter,
+    item_tracker::{EDIT_SLOT, ItemTracker},
+    sorted::SortedIds,
+};
+
 #[derive(Ex)]
 #[derive_ex(Clone(bound()))]
 pub struct SignalVec<T: 'static>(RawSignalVec<T>);
@@ -28,20 +55,474 @@ impl<T: 'static> SignalVec<T> {
         f: impl FnMut(&mut ItemsMut<T>, &mut SignalContext<'_, '_>) + 'static,
     ) -> Self {
         let mut f = f;
-        Self(RawSignalVec::Changing(ChangeFeedSignal::from_scan(
-            VecModel::new(),
-            move |edit, sc| {
+        Self(RawSignalVec::Changing(ItemTracker::new(
+            ChangeFeedSignal::from_scan(VecModel::new(), move |edit, sc| {
                 let mut items = ItemsMut {
                     data: ItemsMutData(edit),
                 };
                 f(&mut items, sc);
-            },
+            }),
         )))
     }
 
+    /// Creates a `SignalVec` that follows the snapshots of `signal`.
+    ///
+    /// Each new snapshot is compared with the previous one by a Myers diff, and only the
+    /// differences are recorded as [`Insert`](VecChange::Insert), [`Remove`](VecChange::Remove)
+    /// and [`Set`](VecChange::Set). Use [`from_signal_diff_by_key`](Self::from_signal_diff_by_key)
+    /// to track item identity and record [`Move`](VecChange::Move).
+    pub fn from_signal_diff(signal: Signal<Vec<T>>) -> Self
+    where
+        T: Clone + PartialEq,
+    {
+        Self::from_scan(move |items, sc| diff::apply_diff(items, &signal.borrow(sc)))
+    }
+
+    /// Creates a `SignalVec` that follows the snapshots of `signal`, matching items by key.
+    ///
+    /// Items with a new key are inserted, items whose key disappeared are removed, and items that
+    /// changed position are moved with the fewest [`Move`](VecChange::Move) changes. An item that
+    /// kept its key but changed its value records [`Set`](VecChange::Set).
+    ///
+    /// # Panics
+    ///
+    /// Panics if two items in the same snapshot have the same key.
+    pub fn from_signal_diff_by_key<K: Eq + Hash>(
+        signal: Signal<Vec<T>>,
+        key: impl Fn(&T) -> K + 'static,
+    ) -> Self
+    where
+        T: Clone + PartialEq,
+    {
+        Self::from_scan(move |items, sc| diff::apply_diff_by_key(items, &signal.borrow(sc), &key))
+    }
+
+    /// Creates a `SignalVec` that keeps the items of this vector sorted by `compare`.
+    ///
+    /// The sorted vector follows the deltas of this vector. Inserted and removed items are placed
+    /// by binary search, and a [`Set`](VecChange::Set) that changes the order of an item records a
+    /// [`Move`](VecChange::Move) of that item only. Items that compare equal are kept in the order
+    /// in which they were added.
+    pub fn sorted_by(&self, compare: impl Fn(&T, &T) -> Ordering + 'static) -> SignalVec<T>
+    where
+        T: Clone,
+    {
+        let mut reader = self.reader();
+        let mut ids = Vec::new();
+        let mut next_id = 0;
+        let mut sorted = SortedIds::new();
+        Self::from_scan(move |items, sc| {
+            let source = reader.read(sc);
+            if source.is_initial() {
+                ids.clear();
+                sorted = SortedIds::new();
+                items.clear();
+            }
+            for change in source.delta() {
+                match change {
+                    VecChange::Insert { index, new_value } => {
+                        ids.insert(index, next_id);
+                        sorted.insert(items, new_value, next_id, &compare);
+                        next_id += 1;
+                    }
+                    VecChange::Remove { index, old_value } => {
+                        let id = ids.remove(index);
+                        sorted.remove(items, old_value, id, &compare);
+                    }
+                    VecChange::Set {
+                        index,
+                        new_value,
+                        old_value,
+                    } => sorted.set(items, old_value, new_value, ids[index], &compare),
+                    VecChange::Move {
+                        old_index,
+                        new_index,
+                    } => {
+                        let id = ids.remove(old_index);
+                        ids.insert(new_index, id);
+                    }
+                    VecChange::Swap { index: (i0, i1) } => ids.swap(i0, i1),
+                    VecChange::Reverse { .. } => ids.reverse(),
+                    VecChange::Rotate { mid, .. } => ids.rotate_left(mid),
+                    VecChange::Sort(new_to_old) => new_to_old.apply_to(&mut ids),
+                }
+            }
+        })
+    }
+
+    /// Creates a `SignalVec` that keeps the items of this vector sorted by the key `f` returns.
+    ///
+    /// See [`sorted_by`](Self::sorted_by) for how changes are applied.
+    pub fn sorted_by_key<K: Ord>(&self, f: impl Fn(&T) -> K + 'static) -> SignalVec<T>
+    where
+        T: Clone,
+    {
+        self.sorted_by(move |a, b| f(a).cmp(&f(b)))
+    }
+
+    /// Creates a `SignalVec` that contains the items of this vector within `range`.
+    ///
+    /// The parts of `range` beyond the end of this vector are ignored. Changes of this vector are
+    /// translated into changes within the window, and changing `range` records only the items
+    /// that enter or leave the window. Only the items in the window are tracked, so each change
+    /// takes time proportional to the size of the window rather than of this vector.
+    pub fn window(&self, range: Signal<Range<usize>>) -> SignalVec<T>
+    where
+        T: Clone,
+    {
+        let mut reader = self.reader();
+        let mut next_id = 0;
+        let mut window_ids = Vec::new();
+        // Index in the source of each item in the window, by identifier.
+        let mut positions = HashMap::<usize, usize>::new();
+        let mut changed_ids = HashSet::new();
+        Self::from_scan(move |items, sc| {
+            let source = reader.read(sc);
+            if source.is_initial() {
+                items.clear();
+                window_ids.clear();
+                positions.clear();
+            }
+            for change in source.delta() {
+                match change {
+                    VecChange::Insert { index, .. } => {
+                        for p in positions.values_mut() {
+                            if *p >= index {
+                                *p += 1;
+                            }
+                        }
+                    }
+                    VecChange::Remove { index, .. } => {
+                        positions.retain(|_, p| match (*p).cmp(&index) {
+                            Ordering::Less => true,
+                            Ordering::Equal => false,
+                            Ordering::Greater => {
+                                *p -= 1;
+                                true
+                            }
+                        });
+                    }
+                    VecChange::Set { index, .. } => {
+                        changed_ids.extend(
+                            positions
+                                .iter()
+                                .filter(|&(_, &p)| p == index)
+                                .map(|(&id, _)| id),
+                        );
+                    }
+                    VecChange::Move {
+                        old_index,
+                        new_index,
+                    } => {
+                        for p in positions.values_mut() {
+                            if *p == old_index {
+                                *p = new_index;
+                            } else if old_index < *p && *p <= new_index {
+                                *p -= 1;
+                            } else if new_index <= *p && *p < old_index {
+                                *p += 1;
+                            }
+                        }
+                    }
+                    VecChange::Swap { index: (i0, i1) } => {
+                        for p in positions.values_mut() {
+                            if *p == i0 {
+                                *p = i1;
+                            } else if *p == i1 {
+                                *p = i0;
+                            }
+                        }
+                    }
+                    VecChange::Reverse { len } => {
+                        for p in positions.values_mut() {
+                            *p = len - 1 - *p;
+                        }
+                    }
+                    VecChange::Rotate { len, mid } => {
+                        for p in positions.values_mut() {
+                            *p = (*p + len - mid) % len;
+                        }
+                    }
+                    VecChange::Sort(new_to_old) => {
+                        let old_to_new = new_to_old.build_old_to_new();
+                        for p in positions.values_mut() {
+                            *p = old_to_new[*p];
+                        }
+                    }
+                }
+            }
+            let range = range.borrow(sc);
+            let end = range.end.min(source.len());
+            let start = range.start.min(end);
+            let mut new_ids = vec![None; end - start];
+            for (&id, &p) in &positions {
+                if (start..end).contains(&p) {
+                    new_ids[p - start] = Some(id);
+                }
+            }
+            let new_ids: Vec<_> = new_ids
+                .into_iter()
+                .map(|id| {
+                    id.unwrap_or_else(|| {
+                        next_id += 1;
+                        next_id - 1
+                    })
+                })
+                .collect();
+            diff::apply_diff_by_id(
+                items,
+                &mut window_ids,
+                &new_ids,
+                |index| source[start + index].clone(),
+                |index, _| changed_ids.contains(&new_ids[index]),
+            );
+            positions.clear();
+            positions.extend(new_ids.iter().enumerate().map(|(i, &id)| (id, start + i)));
+            changed_ids.clear();
+        })
+    }
+
+    /// Splits this vector into the items for which `predicate` returns `true` and the rest.
+    ///
+    /// Both vectors follow the deltas of this vector and keep the relative order of the items.
+    pub fn partition(
+        &self,
+        predicate: impl Fn(&T) -> bool + 'static,
+    ) -> (SignalVec<T>, SignalVec<T>)
+    where
+        T: Clone,
+    {
+        let predicate = Rc::new(predicate);
+        let matched = self.filter({
+            let predicate = predicate.clone();
+            move |value| predicate(value)
+        });
+        let unmatched = self.filter(move |value| !predicate(value));
+        (matched, unmatched)
+    }
+
+    /// Creates a signal with a `SignalVec` for each key `key_fn` returns for the items of this
+    /// vector.
+    ///
+    /// Each group follows the deltas of this vector and keeps the relative order of its items.
+    /// A delta is translated only into changes of the groups it affects. The map changes only
+    /// when a key appears or disappears; a group stays the same `SignalVec` while it has items.
+    pub fn group_by<K: Ord + Clone + 'static>(
+        &self,
+        key_fn: impl Fn(&T) -> K + 'static,
+    ) -> Signal<BTreeMap<K, SignalVec<T>>>
+    where
+        T: Clone,
+    {
+        let router = Rc::new(RefCell::new(GroupRouter::new()));
+        let mut reader = self.reader();
+        let routed = SignalBuilder::from_scan((), {
+            let router = router.clone();
+            move |_, sc| router.borrow_mut().apply(&reader.read(sc), &key_fn)
+        })
+        .build();
+        SignalBuilder::from_scan_filter(
+            (BTreeMap::new(), BTreeMap::new()),
+            move |(ids, groups), sc| {
+                routed.get(sc);
+                let mut r = router.borrow_mut();
+                if !r.take_keys_changed() {
+                    return false;
+                }
+                ids.retain(|key, id| r.ids().get(key) == Some(id));
+                groups.retain(|key, _| ids.contains_key(key));
+                for (key, &id) in r.ids() {
+                    if let Entry::Vacant(entry) = ids.entry(key.clone()) {
+                        entry.insert(id);
+                        let router = router.clone();
+                        let routed = routed.clone();
+                        let group = Self::from_scan(move |items, sc| {
+                            routed.get(sc);
+                            match router.borrow_mut().take_changes(id) {
+                                Some(changes) => items.apply_changes(changes).unwrap(),
+                                None => items.clear(),
+                            }
+                        });
+                        groups.insert(key.clone(), group);
+                    }
+                }
+                true
+            },
+        )
+        .map(|(_, groups)| groups)
+        .build()
+    }
+
+    fn filter(&self, predicate: impl Fn(&T) -> bool + 'static) -> SignalVec<T>
+    where
+        T: Clone,
+    {
+        let mut reader = self.reader();
+        let mut filter = Filter::new();
+        Self::from_scan(move |items, sc| {
+            let source = reader.read(sc);
+            if source.is_initial() {
+                filter = Filter::new();
+                items.clear();
+            }
+            for change in source.delta() {
+                filter.apply(items, change, &predicate);
+            }
+        })
+    }
+
+    /// Creates a signal with the number of items in this vector.
+    pub fn len(&self) -> Signal<usize> {
+        let mut reader = self.reader();
+        SignalBuilder::from_scan_filter(0, move |len, sc| {
+            let new_len = reader.read(sc).len();
+            mem::replace(len, new_len) != new_len
+        })
+        .build()
+    }
+
+    /// Creates a signal with the sum of the items in this vector.
+    ///
+    /// The sum is updated by the deltas of this vector instead of being recomputed.
+    pub fn sum(&self) -> Signal<T>
+    where
+        T: Clone + Default + AddAssign + SubAssign + PartialEq,
+    {
+        self.aggregate(Sum(T::default()))
+    }
+
+    /// Creates a signal with the number of items for which `predicate` returns `true`.
+    pub fn count_where(&self, predicate: impl Fn(&T) -> bool + 'static) -> Signal<usize> {
+        self.aggregate(CountWhere::new(predicate, |count| count))
+    }
+
+    /// Creates a signal that is `true` if `predicate` returns `true` for any item.
+    pub fn any(&self, predicate: impl Fn(&T) -> bool + 'static) -> Signal<bool> {
+        self.aggregate(CountWhere::new(predicate, |count| count != 0))
+    }
+
+    /// Creates a signal that is `true` if `predicate` returns `true` for all items.
+    pub fn all(&self, predicate: impl Fn(&T) -> bool + 'static) -> Signal<bool> {
+        self.aggregate(CountWhere::new(
+            move |value: &T| !predicate(value),
+            |count| count == 0,
+        ))
+    }
+
+    /// Creates a signal with the smallest item, or `None` if this vector is empty.
+    ///
+    /// The items are counted in an ordered map, so removing an item does not rescan the vector.
+    pub fn min(&self) -> Signal<Option<T>>
+    where
+        T: Ord + Clone,
+    {
+        self.aggregate(Min::new())
+    }
+
+    /// Creates a signal with the largest item, or `None` if this vector is empty.
+    ///
+    /// The items are counted in an ordered map, so removing an item does not rescan the vector.
+    pub fn max(&self) -> Signal<Option<T>>
+    where
+        T: Ord + Clone,
+    {
+        self.aggregate(Max::new())
+    }
+
+    /// Creates a signal that folds the items of this vector incrementally.
+    ///
+    /// `add` is called for each inserted item and `remove` for each removed item. A replaced item
+    /// is removed and then added. Moving items does not call either function, so the result must
+    /// not depend on the order of the items.
+    pub fn fold_incremental<St: Clone + 'static>(
+        &self,
+        initial_state: St,
+        mut add: impl FnMut(&mut St, &T) + 'static,
+        mut remove: impl FnMut(&mut St, &T) + 'static,
+    ) -> Signal<St> {
+        let mut reader = self.reader();
+        SignalBuilder::from_scan(initial_state.clone(), move |st, sc| {
+            let source = reader.read(sc);
+            if source.is_initial() {
+                *st = initial_state.clone();
+            }
+            for change in source.delta() {
+                match change {
+                    VecChange::Insert { new_value, .. } => add(st, new_value),
+                    VecChange::Remove { old_value, .. } => remove(st, old_value),
+                    VecChange::Set {
+                        new_value,
+                        old_value,
+                        ..
+                    } => {
+                        remove(st, old_value);
+                        add(st, new_value);
+                    }
+                    VecChange::Move { .. }
+                    | VecChange::Swap { .. }
+                    | VecChange::Reverse { .. }
+                    | VecChange::Rotate { .. }
+                    | VecChange::Sort(_) => {}
+                }
+            }
+        })
+        .build()
+    }
+
+    fn aggregate<A: Aggregate<T>>(&self, mut aggregate: A) -> Signal<A::Output> {
+        let mut reader = self.reader();
+        let output = aggregate.output();
+        SignalBuilder::from_scan_filter((aggregate, output), move |(aggregate, output), sc| {
+            let source = reader.read(sc);
+            if source.is_initial() {
+                aggregate.clear();
+            }
+            for change in source.delta() {
+                match change {
+                    VecChange::Insert { new_value, .. } => aggregate.add(new_value),
+                    VecChange::Remove { old_value, .. } => aggregate.remove(old_value),
+                    VecChange::Set {
+                        new_value,
+                        old_value,
+                        ..
+                    } => {
+                        aggregate.remove(old_value);
+                        aggregate.add(new_value);
+                    }
+                    VecChange::Move { .. }
+                    | VecChange::Swap { .. }
+                    | VecChange::Reverse { .. }
+                    | VecChange::Rotate { .. }
+                    | VecChange::Sort(_) => {}
+                }
+            }
+            let new_output = aggregate.output();
+            if *output != new_output {
+                *output = new_output;
+                true
+            } else {
+                false
+            }
+        })
+        .map(|(_, output)| output)
+        .build()
+    }
+
+    /// Sets the limit of the changes retained for readers of this vector.
+    ///
+    /// A reader that falls behind the limit reads the items as [`Items::is_initial`] again.
+    /// Vectors that never change retain no changes, so this has no effect on them.
+    pub fn set_history_limit(&self, limit: HistoryLimit) {
+        if let RawSignalVec::Changing(tracker) = &self.0 {
+            tracker.source().set_history_limit(limit);
+        }
+    }
+
     pub fn reader(&self) -> SignalVecReader<T> {
         SignalVecReader(match &self.0 {
-            RawSignalVec::Changing(signal) => RawSignalVecReader::Changing(signal.reader()),
+            RawSignalVec::Changing(tracker) => {
+                RawSignalVecReader::Changing(tracker.source().reader())
+            }
             RawSignalVec::Vec(vec) => RawSignalVecReader::Vec {
                 vec: vec.clone(),
                 has_read: false,
@@ -55,12 +536,86 @@ impl<T: 'static> SignalVec<T> {
 
     pub fn borrow<'a, 'r: 'a>(&'a self, sc: &mut SignalContext<'r, '_>) -> Items<'a, T> {
         match &self.0 {
-            RawSignalVec::Changing(signal) => Items::from_ref(signal.borrow(sc)),
+            RawSignalVec::Changing(tracker) => Items::from_ref(tracker.source().borrow(sc)),
             RawSignalVec::Vec(vec) => Items::from_slice_items(vec),
             RawSignalVec::Slice(slice) => Items::from_slice_items(slice),
         }
     }
+
+    /// Borrows the item at `index` and registers a dependency on that position only.
+    ///
+    /// The dependency becomes dirty when the item at `index` is replaced, moved or removed, or
+    /// when an insertion or removal before `index` shifts it.
+    pub fn item<'a, 'r: 'a>(
+        &'a self,
+        index: usize,
+        sc: &mut SignalContext<'r, '_>,
+    ) -> Option<StateRef<'a, T>> {
+        match &self.0 {
+            RawSignalVec::Changing(tracker) => tracker.item(index, sc),
+            RawSignalVec::Vec(vec) => vec.get(index).map(StateRef::from),
+            RawSignalVec::Slice(slice) => slice.get(index).map(StateRef::from),
+        }
+    }
+
+    /// Borrows the item identified by `handle` and registers a dependency on that item only.
+    ///
+    /// The dependency becomes dirty when the item is replaced, moved or removed. Returns `None`
+    /// if the item has been removed.
+    pub fn item_by_handle<'a, 'r: 'a>(
+        &'a self,
+        handle: ItemHandle,
+        sc: &mut SignalContext<'r, '_>,
+    ) -> Option<StateRef<'a, T>> {
+        match &self.0 {
+            RawSignalVec::Changing(tracker) => tracker.item_by_handle(handle, sc),
+            RawSignalVec::Vec(vec) => vec.get(handle.0).map(StateRef::from),
+            RawSignalVec::Slice(slice) => slice.get(handle.0).map(StateRef::from),
+        }
+    }
+
+    /// Returns the current index of the item identified by `handle` and registers a dependency
+    /// on that index.
+    ///
+    /// The dependency becomes dirty only when the index of the item changes or the item is
+    /// removed.
+    pub fn index_of(&self, handle: ItemHandle, sc: &mut SignalContext<'_, '_>) -> Option<usize> {
+        match &self.0 {
+            RawSignalVec::Changing(tracker) => tracker.index_of(handle, sc),
+            RawSignalVec::Vec(vec) => (handle.0 < vec.len()).then_some(handle.0),
+            RawSignalVec::Slice(slice) => (handle.0 < slice.len()).then_some(handle.0),
+        }
+    }
+}
+
+/// Serializes the current items as a sequence.
+///
+/// Items of a changing vector are read through [`Runtime::call`],
+/// so serialization fails unless the runtime is lent with [`Runtime::lend`].
+impl<T: Serialize> Serialize for SignalVec<T> {
+    fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
+    where
+        S: serde::Serializer,
+    {
+        match &self.0 {
+            RawSignalVec::Changing(_) => {
+                Runtime::try_call(|rt| serializer.collect_seq(self.borrow(&mut rt.sc()).iter()))
+                    .unwrap_or_else(|e| Err(serde::ser::Error::custom(e)))
+            }
+            RawSignalVec::Vec(vec) => serializer.collect_seq(vec.iter()),
+            RawSignalVec::Slice(slice) => serializer.collect_seq(slice.iter()),
+        }
+    }
 }
+impl<'de, T: Deserialize<'de> + 'static> Deserialize<'de> for SignalVec<T> {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        Vec::deserialize(deserializer).map(Self::from)
+    }
+}
+
 impl<T> From<Vec<T>> for SignalVec<T> {
     fn from(value: Vec<T>) -> Self {
         Rc::new(value).into()
@@ -85,7 +640,7 @@ impl<const N: usize, T> From<&'static [T; N]> for SignalVec<T> {
 
 #[derive_ex(Clone)]
 enum RawSignalVec<T: 'static> {
-    Changing(ChangeFeedSignal<VecModel<T>>),
+    Changing(Rc<ItemTracker<T>>),
     Vec(Rc<Vec<T>>),
     Slice(&'static [T]),
 }
@@ -138,6 +693,29 @@ impl<T: 'static> SignalVecReader<T> {
             RawSignalVecReader::Slice { slice, has_read } => Items::from_slice(slice, !*has_read),
         }
     }
+
+    /// Converts this reader into a [`Stream`] of owned delta batches.
+    ///
+    /// The first batch is an [`Initial`](OwnedVecChange::Initial) snapshot of the items.
+    /// Batches that have not been consumed yet are combined into one,
+    /// so no change is lost however slowly the stream is polled.
+    pub fn into_delta_stream(
+        mut self,
+    ) -> impl Stream<Item = Vec<OwnedVecChange<T>>> + Unpin + 'static
+    where
+        T: Clone,
+    {
+        stream_from_merge(
+            move |sc| self.read(sc).owned_delta(),
+            |pending, batch| match pending {
+                Some(mut pending) if !matches!(batch.first(), Some(OwnedVecChange::Initial(_))) => {
+                    pending.extend(batch);
+                    Some(pending)
+                }
+                _ => (!batch.is_empty()).then_some(batch),
+            },
+        )
+    }
 }
 
 pub struct Items<'a, T: 'static> {
@@ -175,6 +753,39 @@ impl<'a, T: 'static> Items<'a, T> {
     pub fn get(&self, index: usize) -> Option<&T> {
         self.items.get(index)
     }
+    /// Returns the handle of the item at `index`.
+    pub fn handle(&self, index: usize) -> Option<ItemHandle> {
+        match &self.items {
+            RawItems::ChangeFeed(value) => value.current().handle(index),
+            RawItems::Slice(slice) => (index < slice.len()).then_some(ItemHandle(index)),
+        }
+    }
+    /// Returns the item identified by `handle`, or `None` if it has been removed.
+    pub fn get_by_handle(&self, handle: ItemHandle) -> Option<&T> {
+        match &self.items {
+            RawItems::ChangeFeed(value) => value.current().get_by_handle(handle),
+            RawItems::Slice(slice) => slice.get(handle.0),
+        }
+    }
+    /// Returns the current index of the item identified by `handle`.
+    pub fn index_of(&self, handle: ItemHandle) -> Option<usize> {
+        match &self.items {
+            RawItems::ChangeFeed(value) => value.current().index_of(handle),
+            RawItems::Slice(slice) => (handle.0 < slice.len()).then_some(handle.0),
+        }
+    }
+
+    /// Returns whether this is an initial read, whose delta inserts every current item.
+    ///
+    /// A reader that falls behind the [`HistoryLimit`] of the vector gets an initial read again,
+    /// and must then discard what it built from earlier deltas.
+    pub fn is_initial(&self) -> bool {
+        match &self.items {
+            RawItems::ChangeFeed(value) => matches!(value.delta(), ChangeFeedDelta::Initial),
+            RawItems::Slice(_) => self.immutable_initial,
+        }
+    }
+
     /// Returns the delta needed to reproduce the current items from the reader's baseline.
     ///
     /// An initial read returns an [`Insert`](VecChange::Insert) for every current item.
@@ -192,6 +803,35 @@ impl<'a, T: 'static> Items<'a, T> {
         }
     }
 
+    fn owned_delta(&self) -> Vec<OwnedVecChange<T>>
+    where
+        T: Clone,
+    {
+        match &self.items {
+            RawItems::ChangeFeed(value) => match value.delta() {
+                ChangeFeedDelta::Initial => {
+                    vec![OwnedVecChange::Initial(self.iter().cloned().collect())]
+                }
+                ChangeFeedDelta::Incremental(changes) => changes
+                    .map(|change| change.to_signal_vec_change(&value.current().values).into())
+                    .collect(),
+            },
+            RawItems::Slice(slice) if self.immutable_initial => {
+                vec![OwnedVecChange::Initial(slice.to_vec())]
+            }
+            RawItems::Slice(_) => Vec::new(),
+        }
+    }
+
+    /// Returns the delta as JSON Patch operations.
+    ///
+    /// See [`VecChange::to_json_patch`].
+    pub fn to_json_patch(&self) -> Vec<PatchOperation<&T>> {
+        self.delta()
+            .flat_map(|change| change.to_json_patch())
+            .collect()
+    }
+
     fn initial_changes(&self) -> impl Iterator<Item = VecChange<'_, T>> + '_ {
         self.iter()
             .enumerate()
@@ -301,17 +941,41 @@ impl<T: 'static> ItemsMut<'_, T> {
         let len = self.len();
         self.insert(len, value);
     }
+    /// Inserts an item and returns a handle that keeps referring to it.
+    pub fn insert_with_handle(&mut self, index: usize, value: T) -> ItemHandle {
+        self.insert(index, value);
+        self.data.handle(index).unwrap()
+    }
+    /// Appends an item and returns a handle that keeps referring to it.
+    pub fn push_with_handle(&mut self, value: T) -> ItemHandle {
+        let len = self.len();
+        self.insert_with_handle(len, value)
+    }
     pub fn remove(&mut self, index: usize) {
-        let old_value = self.data.items.remove(index);
+        let old_value = self.data.remove_raw(index);
         self.data.record(ChangeData::Remove { index, old_value });
     }
     pub fn get(&self, index: usize) -> Option<&T> {
         self.data.get(index)
     }
+    /// Returns the handle of the item at `index`.
+    pub fn handle(&self, index: usize) -> Option<ItemHandle> {
+        self.data.handle(index)
+    }
+    /// Returns the item identified by `handle`, or `None` if it has been removed.
+    pub fn get_by_handle(&self, handle: ItemHandle) -> Option<&T> {
+        self.data.get_by_handle(handle)
+    }
+    /// Returns the current index of the item identified by `handle`.
+    pub fn index_of(&self, handle: ItemHandle) -> Option<usize> {
+        self.data.index_of(handle)
+    }
     pub fn set(&mut self, index: usize, value: T) {
         let old_value = self.data.items[index];
-        let new_value = self.data.values.insert(value);
+        let id = self.data.ids[old_value];
+        let new_value = self.data.insert_value(value, id);
         self.data.items[index] = new_value;
+        self.data.update_position(index);
         self.data.record(ChangeData::Set {
             index,
             old_value,
@@ -323,6 +987,8 @@ impl<T: 'static> ItemsMut<'_, T> {
             return;
         }
         self.data.items.swap(index0, index1);
+        self.data.update_position(index0);
+        self.data.update_position(index1);
         self.data.record(ChangeData::Swap {
             index: (index0, index1),
         });
@@ -339,6 +1005,7 @@ impl<T: 'static> ItemsMut<'_, T> {
             Ordering::Greater => self.data.items[new_index..=old_index].rotate_right(1),
             Ordering::Equal => return,
         }
+        self.data.invalidate_positions(old_index.min(new_index));
         self.data.record(ChangeData::Move {
             old_index,
             new_index,
@@ -373,24 +1040,386 @@ impl<T: 'static> ItemsMut<'_, T> {
     pub fn sort_unstable_by_key<K: Ord>(&mut self, mut key: impl FnMut(&T) -> K) {
         self.sort_unstable_by(|a, b| key(a).cmp(&key(b)))
     }
+    fn permute(&mut self, new_to_old: Vec<usize>) {
+        if !is_sorted(&new_to_old) {
+            IndexNewToOld::new(&new_to_old).apply_to(&mut self.data.items);
+            self.data.invalidate_positions(0);
+            self.data.record(ChangeData::Sort { new_to_old });
+        }
+    }
+    /// Keeps only the items for which `f` returns `true`.
+    ///
+    /// Records one `Remove` per removed item and runs in linear time.
+    pub fn retain(&mut self, mut f: impl FnMut(&T) -> bool) {
+        let keep: Vec<bool> = self.iter().map(&mut f).collect();
+        self.retain_mask(&keep);
+    }
+
+    /// Removes consecutive items for which `same_bucket` returns `true`.
+    ///
+    /// Unlike [`Vec::dedup_by`], `same_bucket` cannot modify the items.
+    /// It is called with the current item and the last item kept before it.
+    pub fn dedup_by(&mut self, mut same_bucket: impl FnMut(&T, &T) -> bool) {
+        let mut keep = Vec::with_capacity(self.len());
+        let mut last = None;
+        for value in self.iter() {
+            let is_kept = last.is_none_or(|last| !same_bucket(value, last));
+            if is_kept {
+                last = Some(value);
+            }
+            keep.push(is_kept);
+        }
+        self.retain_mask(&keep);
+    }
+    pub fn dedup_by_key<K: PartialEq>(&mut self, mut key: impl FnMut(&T) -> K) {
+        self.dedup_by(|a, b| key(a) == key(b))
+    }
+    pub fn dedup(&mut self)
+    where
+        T: PartialEq,
+    {
+        self.dedup_by(|a, b| a == b)
+    }
+    fn retain_mask(&mut self, keep: &[bool]) {
+        let VecModel {
+            items, ids, keys, ..
+        } = &mut *self.data;
+        let mut index = 0;
+        let mut changes = Vec::new();
+        items.retain(|&key| {
+            let is_kept = keep[index + changes.len()];
+            if is_kept {
+                index += 1;
+            } else {
+                keys.remove(&ids[key]);
+                changes.push(ChangeData::Remove {
+                    index,
+                    old_value: key,
+                });
+            }
+            is_kept
+        });
+        if let Some(ChangeData::Remove { index, .. }) = changes.first() {
+            self.data.invalidate_positions(*index);
+        }
+        for change in changes {
+            self.data.record(change);
+        }
+    }
+
+    /// Replaces the items in `range` with the items of `replace_with`.
+    ///
+    /// Items replaced one for one are recorded as `Set` and keep their handles, like [`Self::set`].
+    /// The rest of the range is recorded as `Remove`, and the rest of `replace_with` as `Insert`.
+    pub fn splice(
+        &mut self,
+        range: impl RangeBounds<usize>,
+        replace_with: impl IntoIterator<Item = T>,
+    ) {
+        let range = to_range(range, self.len());
+        let mut values = replace_with.into_iter();
+        let mut index = range.start;
+        while index < range.end {
+            let Some(value) = values.next() else {
+                self.drain(index..range.end);
+                return;
+            };
+            self.set(index, value);
+            index += 1;
+        }
+        let new_values: Vec<usize> = values
+            .map(|value| self.data.insert_new_value(value))
+            .collect();
+        self.data
+            .items
+            .splice(index..index, new_values.iter().copied());
+        self.data.invalidate_positions(index);
+        for (offset, new_value) in new_values.into_iter().enumerate() {
+            self.data.record(ChangeData::Insert {
+                index: index + offset,
+                new_value,
+            });
+        }
+    }
+    pub fn extend_from_slice(&mut self, values: &[T])
+    where
+        T: Clone,
+    {
+        let len = self.len();
+        self.splice(len..len, values.iter().cloned());
+    }
+    pub fn truncate(&mut self, len: usize) {
+        if len < self.len() {
+            self.drain(len..);
+        }
+    }
+    pub fn resize_with(&mut self, new_len: usize, mut f: impl FnMut() -> T) {
+        let len = self.len();
+        if new_len <= len {
+            self.truncate(new_len);
+        } else {
+            self.splice(len..len, (len..new_len).map(|_| f()));
+        }
+    }
+
+    /// Reverses the order of the items.
+    ///
+    /// Recorded as a single `Reverse`.
+    pub fn reverse(&mut self) {
+        let len = self.len();
+        if len > 1 {
+            self.data.items.reverse();
+            self.data.invalidate_positions(0);
+            self.data.record(ChangeData::Reverse { len });
+        }
+    }
+
+    /// Rotates the items so that the item at `mid` becomes the first item.
+    ///
+    /// Recorded as a single `Move` when only one item wraps around, and as a single `Rotate`
+    /// otherwise.
+    pub fn rotate_left(&mut self, mid: usize) {
+        let len = self.len();
+        assert!(mid <= len, "mid out of bounds");
+        if mid == 0 || mid == len {
+            return;
+        }
+        if mid == 1 {
+            self.move_item(0, len - 1);
+        } else if mid == len - 1 {
+            self.move_item(len - 1, 0);
+        } else {
+            self.data.items.rotate_left(mid);
+            self.data.invalidate_positions(0);
+            self.data.record(ChangeData::Rotate { len, mid });
+        }
+    }
+
+    /// Rotates the items so that the last `k` items become the first items.
+    ///
+    /// See [`Self::rotate_left`].
+    pub fn rotate_right(&mut self, k: usize) {
+        let len = self.len();
+        assert!(k <= len, "k out of bounds");
+        self.rotate_left(len - k);
+    }
+
     pub fn drain(&mut self, range: impl RangeBounds<usize>) {
         let range = to_range(range, self.len());
         for index in (range.start..range.end).rev() {
             let old_value = self.data.items[index];
             self.data.record(ChangeData::Remove { index, old_value });
         }
-        self.data.items.drain(range);
+        let model = &mut *self.data;
+        model.invalidate_positions(range.start);
+        for key in model.items.drain(range) {
+            model.keys.remove(&model.ids[key]);
+        }
     }
 
     pub fn clear(&mut self) {
         self.drain(..);
     }
 
+    /// Applies an owned change, such as one produced by
+    /// [`SignalVecReader::into_delta_stream`].
+    ///
+    /// The `old_value` of `Remove` and `Set` is not compared with the current item.
+    /// An `Initial` change replaces all items with [`splice`](Self::splice).
+    ///
+    /// Returns an error without modifying the items if the change does not fit the current items.
+    pub fn apply_change(&mut self, change: OwnedVecChange<T>) -> Result<(), ApplyVecChangeError> {
+        let len = self.len();
+        let check = |index: usize, len: usize| {
+            if index < len {
+                Ok(())
+            } else {
+                Err(ApplyVecChangeError::IndexOutOfBounds { index, len })
+            }
+        };
+        let check_len = |expected: usize, len: usize| {
+            if expected == len {
+                Ok(())
+            } else {
+                Err(ApplyVecChangeError::LengthMismatch { expected, len })
+            }
+        };
+        match change {
+            OwnedVecChange::Initial(values) => self.splice(.., values),
+            OwnedVecChange::Insert { index, new_value } => {
+                check(index, len + 1)?;
+                self.insert(index, new_value);
+            }
+            OwnedVecChange::Remove { index, .. } => {
+                check(index, len)?;
+                self.remove(index);
+            }
+            OwnedVecChange::Set {
+                index, new_value, ..
+            } => {
+                check(index, len)?;
+                self.set(index, new_value);
+            }
+            OwnedVecChange::Move {
+                old_index,
+                new_index,
+            } => {
+                check(old_index, len)?;
+                check(new_index, len)?;
+                self.move_item(old_index, new_index);
+            }
+            OwnedVecChange::Swap { index: (i0, i1) } => {
+                check(i0, len)?;
+                check(i1, len)?;
+                self.swap(i0, i1);
+            }
+            OwnedVecChange::Reverse { len: expected } => {
+                check_len(expected, len)?;
+                self.reverse();
+            }
+            OwnedVecChange::Rotate { len: expected, mid } => {
+                check_len(expected, len)?;
+                if mid > len {
+                    return Err(ApplyVecChangeError::IndexOutOfBounds { index: mid, len });
+                }
+                self.rotate_left(mid);
+            }
+            OwnedVecChange::Sort(new_to_old) => {
+                let mut used = vec![false; len];
+                if new_to_old.len() != len
+                    || !new_to_old
+                        .iter()
+                        .all(|&old| old < len && !mem::replace(&mut used[old], true))
+                {
+                    return Err(ApplyVecChangeError::InvalidPermutation { len });
+                }
+                self.permute(new_to_old);
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies the operations of a JSON Patch document in order.
+    ///
+    /// If an operation cannot be applied, returns its error and applies none of the operations.
+    pub fn apply_json_patch(
+        &mut self,
+        patch: impl IntoIterator<Item = PatchOperation<T>>,
+    ) -> Result<(), JsonPatchError>
+    where
+        T: Clone + PartialEq,
+    {
+        let patch: Vec<_> = patch.into_iter().collect();
+        self.check_json_patch(&patch)?;
+        for operation in patch {
+            match operation {
+                PatchOperation::Add { path, value } => {
+                    let index = patch_index(self.len(), &path, 1)?;
+                    self.insert(index, value);
+                }
+                PatchOperation::Remove { path } => {
+                    let index = patch_index(self.len(), &path, 0)?;
+                    self.remove(index);
+                }
+                PatchOperation::Replace { path, value } => {
+                    let index = patch_index(self.len(), &path, 0)?;
+                    self.set(index, value);
+                }
+                PatchOperation::Move { from, path } => {
+                    let old_index = patch_index(self.len(), &from, 0)?;
+                    let new_index = patch_move_index(self.len(), &path)?;
+                    self.move_item(old_index, new_index);
+                }
+                PatchOperation::Copy { from, path } => {
+                    let value = self[patch_index(self.len(), &from, 0)?].clone();
+                    let index = patch_index(self.len(), &path, 1)?;
+                    self.insert(index, value);
+                }
+                PatchOperation::Test { .. } => {}
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies `patch` to references to the items, so that a failing operation is found before
+    /// any item changes.
+    fn check_json_patch(&self, patch: &[PatchOperation<T>]) -> Result<(), JsonPatchError>
+    where
+        T: PartialEq,
+    {
+        let mut items: Vec<&T> = self.iter().collect();
+        for operation in patch {
+            match operation {
+                PatchOperation::Add { path, value } => {
+                    let index = patch_index(items.len(), path, 1)?;
+                    items.insert(index, value);
+                }
+                PatchOperation::Remove { path } => {
+                    let index = patch_index(items.len(), path, 0)?;
+                    items.remove(index);
+                }
+                PatchOperation::Replace { path, value } => {
+                    let index = patch_index(items.len(), path, 0)?;
+                    items[index] = value;
+                }
+                PatchOperation::Move { from, path } => {
+                    let old_index = patch_index(items.len(), from, 0)?;
+                    let new_index = patch_move_index(items.len(), path)?;
+                    let item = items.remove(old_index);
+                    items.insert(new_index, item);
+                }
+                PatchOperation::Copy { from, path } => {
+                    let value = items[patch_index(items.len(), from, 0)?];
+                    let index = patch_index(items.len(), path, 1)?;
+                    items.insert(index, value);
+                }
+                PatchOperation::Test { path, value } => {
+                    if items[patch_index(items.len(), path, 0)?] != value {
+                        return Err(JsonPatchError::TestFailed(path.clone()));
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+
+    /// Applies owned changes in order.
+    ///
+    /// Stops at the first change that does not fit the current items and returns its error.
+    /// The changes before it remain applied.
+    pub fn apply_changes(
+        &mut self,
+        changes: impl IntoIterator<Item = OwnedVecChange<T>>,
+    ) -> Result<(), ApplyVecChangeError> {
+        for change in changes {
+            self.apply_change(change)?;
+        }
+        Ok(())
+    }
+
     pub fn iter(&self) -> Iter<'_, T> {
         Iter::new(IterSource::Model(&self.data))
     }
 }
 
+fn patch_index(len: usize, path: &str, extra: usize) -> Result<usize, JsonPatchError> {
+    let index = match parse_path(path)? {
+        PathToken::Index(index) => index,
+        PathToken::End => len,
+    };
+    if index < len + extra {
+        Ok(index)
+    } else {
+        Err(JsonPatchError::NotFound(path.to_string()))
+    }
+}
+fn patch_move_index(len: usize, path: &str) -> Result<usize, JsonPatchError> {
+    match parse_path(path)? {
+        PathToken::End => Ok(len - 1),
+        PathToken::Index(_) => patch_index(len, path, 0),
+    }
+}
+
 impl<T> Index<usize> for ItemsMut<'_, T> {
     type Output = T;
 
@@ -408,9 +1437,8 @@ impl<'a, T> IntoIterator for &'a ItemsMut<'_, T> {
 }
 impl<T> Extend<T> for ItemsMut<'_, T> {
     fn extend<I: IntoIterator<Item = T>>(&mut self, iter: I) {
-        for value in iter {
-            self.push(value)
-        }
+        let len = self.len();
+        self.splice(len..len, iter);
     }
 }
 
@@ -513,6 +1541,13 @@ impl<'a, T: 'static> IterSource<'a, T> {
     }
 }
 
+/// Identifies an item of a vector regardless of its position.
+///
+/// A handle stays valid while the item is replaced, moved or sorted, and refers to no item once
+/// the item is removed. Handles of a vector that never changes are the indexes of its items.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct ItemHandle(usize);
+
 #[derive(Debug, Eq, PartialEq)]
 #[derive_ex(Clone, Copy, bound())]
 pub enum VecChange<'a, T: ?Sized> {
@@ -536,9 +1571,168 @@ pub enum VecChange<'a, T: ?Sized> {
     Swap {
         index: (usize, usize),
     },
+    /// Reverses the order of all `len` items.
+    Reverse {
+        len: usize,
+    },
+    /// Rotates all `len` items so that the item at `mid` becomes the first item.
+    Rotate {
+        len: usize,
+        mid: usize,
+    },
     Sort(&'a IndexNewToOld),
 }
 
+impl<'a, T> VecChange<'a, T> {
+    /// Converts this change into JSON Patch operations on an array.
+    ///
+    /// `Swap` becomes up to two `move` operations, `Reverse` and `Rotate` become one `move` per
+    /// item that changes its place, and `Sort` becomes one `move` per displaced item.
+    pub fn to_json_patch(&self) -> Vec<PatchOperation<&'a T>> {
+        let move_op = |old_index, new_index| PatchOperation::Move {
+            from: index_path(old_index),
+            path: index_path(new_index),
+        };
+        match *self {
+            VecChange::Insert { index, new_value } => vec![PatchOperation::Add {
+                path: index_path(index),
+                value: new_value,
+            }],
+            VecChange::Remove { index, .. } => vec![PatchOperation::Remove {
+                path: index_path(index),
+            }],
+            VecChange::Set {
+                index, new_value, ..
+            } => vec![PatchOperation::Replace {
+                path: index_path(index),
+                value: new_value,
+            }],
+            VecChange::Move {
+                old_index,
+                new_index,
+            } => vec![move_op(old_index, new_index)],
+            VecChange::Swap { index: (i0, i1) } => {
+                let (i0, i1) = (i0.min(i1), i0.max(i1));
+                let mut operations = vec![move_op(i1, i0)];
+                if i0 + 1 != i1 {
+                    operations.push(move_op(i0 + 1, i1));
+                }
+                operations
+            }
+            VecChange::Reverse { len } => (0..len.saturating_sub(1))
+                .map(|index| move_op(len - 1, index))
+                .collect(),
+            VecChange::Rotate { len, mid } => {
+                if mid <= len - mid {
+                    (0..mid).map(|_| move_op(0, len - 1)).collect()
+                } else {
+                    (mid..len).map(|_| move_op(len - 1, 0)).collect()
+                }
+            }
+            VecChange::Sort(new_to_old) => {
+                let mut order: Vec<usize> = (0..new_to_old.as_slice().len()).collect();
+                let mut operations = Vec::new();
+                for (new_index, &old) in new_to_old.as_slice().iter().enumerate() {
+                    let index =
+                        new_index + order[new_index..].iter().position(|&o| o == old).unwrap();
+                    if index != new_index {
+                        order.remove(index);
+                        order.insert(new_index, old);
+                        operations.push(move_op(index, new_index));
+                    }
+                }
+                operations
+            }
+        }
+    }
+}
+
+/// An owned counterpart of [`VecChange`], used to pass deltas outside of a [`SignalContext`].
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum OwnedVecChange<T> {
+    /// Replaces all items.
+    Initial(Vec<T>),
+    Insert {
+        index: usize,
+        new_value: T,
+    },
+    Remove {
+        index: usize,
+        old_value: T,
+    },
+    Set {
+        index: usize,
+        new_value: T,
+        old_value: T,
+    },
+    Move {
+        old_index: usize,
+        new_index: usize,
+    },
+    Swap {
+        index: (usize, usize),
+    },
+    /// Reverses the order of all `len` items.
+    Reverse {
+        len: usize,
+    },
+    /// Rotates all `len` items so that the item at `mid` becomes the first item.
+    Rotate {
+        len: usize,
+        mid: usize,
+    },
+    /// Reorders the items so that the item at index `i` comes from index `new_to_old[i]`.
+    Sort(Vec<usize>),
+}
+
+/// An error returned when an [`OwnedVecChange`] does not fit the items it is applied to.
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Display)]
+pub enum ApplyVecChangeError {
+    #[display("index {index} is out of bounds for length {len}")]
+    IndexOutOfBounds { index: usize, len: usize },
+    #[display("sort order is not a permutation of length {len}")]
+    InvalidPermutation { len: usize },
+    #[display("change for length {expected} does not fit length {len}")]
+    LengthMismatch { expected: usize, len: usize },
+}
+
+impl std::error::Error for ApplyVecChangeError {}
+
+impl<T: Clone> From<VecChange<'_, T>> for OwnedVecChange<T> {
+    fn from(change: VecChange<'_, T>) -> Self {
+        match change {
+            VecChange::Insert { index, new_value } => Self::Insert {
+                index,
+                new_value: new_value.clone(),
+            },
+            VecChange::Remove { index, old_value } => Self::Remove {
+                index,
+                old_value: old_value.clone(),
+            },
+            VecChange::Set {
+                index,
+                new_value,
+                old_value,
+            } => Self::Set {
+                index,
+                new_value: new_value.clone(),
+                old_value: old_value.clone(),
+            },
+            VecChange::Move {
+                old_index,
+                new_index,
+            } => Self::Move {
+                old_index,
+                new_index,
+            },
+            VecChange::Swap { index } => Self::Swap { index },
+            VecChange::Reverse { len } => Self::Reverse { len },
+            VecChange::Rotate { len, mid } => Self::Rotate { len, mid },
+            VecChange::Sort(new_to_old) => Self::Sort(new_to_old.as_slice().to_vec()),
+        }
+    }
+}
+
 #[derive(Debug)]
 enum ChangeData {
     Insert {
@@ -561,6 +1755,13 @@ enum ChangeData {
     Swap {
         index: (usize, usize),
     },
+    Reverse {
+        len: usize,
+    },
+    Rotate {
+        len: usize,
+        mid: usize,
+    },
     Sort {
         new_to_old: Vec<usize>,
     },
@@ -594,6 +1795,8 @@ impl ChangeData {
                 new_index,
             },
             &ChangeData::Swap { index } => VecChange::Swap { index },
+            &ChangeData::Reverse { len } => VecChange::Reverse { len },
+            &ChangeData::Rotate { len, mid } => VecChange::Rotate { len, mid },
             ChangeData::Sort { new_to_old } => VecChange::Sort(IndexNewToOld::new(new_to_old)),
         }
     }
@@ -602,18 +1805,33 @@ impl ChangeData {
 #[derive(Ex)]
 #[derive_ex(Clone(bound()), Default)]
 #[default(Self::new())]
-pub struct StateVec<T: 'static>(ChangeFeedState<VecModel<T>>);
+pub struct StateVec<T: 'static>(ChangeFeedState<VecModel<T>>, Rc<ItemTracker<T>>);
 
 impl<T> StateVec<T> {
     pub fn new() -> Self {
-        Self(ChangeFeedState::new(VecModel::new()))
+        Self::from_model(VecModel::new())
+    }
+    fn from_model(model: VecModel<T>) -> Self {
+        let state = ChangeFeedState::new(model);
+        let tracker = ItemTracker::new(state.to_signal());
+        let sink: Rc<dyn BindSink> = tracker.clone();
+        state.set_edit_sink(Rc::downgrade(&sink), EDIT_SLOT);
+        Self(state, tracker)
     }
     pub fn to_signal_vec(&self) -> SignalVec<T> {
-        SignalVec(RawSignalVec::Changing(self.0.to_signal()))
+        SignalVec(RawSignalVec::Changing(self.1.clone()))
     }
     pub fn reader(&self) -> SignalVecReader<T> {
         SignalVecReader(RawSignalVecReader::Changing(self.0.reader()))
     }
+
+    /// Sets the limit of the changes retained for readers of this vector.
+    ///
+    /// A reader that falls behind the limit reads the items as [`Items::is_initial`] again.
+    pub fn set_history_limit(&self, limit: HistoryLimit) {
+        self.0.set_history_limit(limit);
+    }
+
     pub fn borrow<'a, 'r: 'a>(&'a self, sc: &mut SignalContext<'r, '_>) -> Items<'a, T> {
         Items::from_ref(self.0.borrow(sc))
     }
@@ -627,6 +1845,57 @@ impl<T> StateVec<T> {
             data: ItemsMutData(self.0.borrow_mut_loose(ac)),
         }
     }
+
+    /// Borrows the item at `index` and registers a dependency on that position only.
+    ///
+    /// See [`SignalVec::item`].
+    pub fn item<'a, 'r: 'a>(
+        &'a self,
+        index: usize,
+        sc: &mut SignalContext<'r, '_>,
+    ) -> Option<StateRef<'a, T>> {
+        self.1.item(index, sc)
+    }
+
+    /// Borrows the item identified by `handle` and registers a dependency on that item only.
+    ///
+    /// See [`SignalVec::item_by_handle`].
+    pub fn item_by_handle<'a, 'r: 'a>(
+        &'a self,
+        handle: ItemHandle,
+        sc: &mut SignalContext<'r, '_>,
+    ) -> Option<StateRef<'a, T>> {
+        self.1.item_by_handle(handle, sc)
+    }
+
+    /// Returns the current index of the item identified by `handle` and registers a dependency
+    /// on that index.
+    ///
+    /// See [`SignalVec::index_of`].
+    pub fn index_of(&self, handle: ItemHandle, sc: &mut SignalContext<'_, '_>) -> Option<usize> {
+        self.1.index_of(handle, sc)
+    }
+
+    /// Applies each batch of `changes` in its own action step until the stream ends.
+    ///
+    /// This can be used to mirror a collection read with [`SignalVecReader::into_delta_stream`]
+    /// elsewhere. Stops at the first change that does not fit the items and returns its error.
+    ///
+    /// See [`ItemsMut::apply_changes`].
+    pub async fn apply_stream<I>(
+        &self,
+        changes: impl Stream<Item = I>,
+        ac: &mut AsyncActionContext,
+    ) -> Result<(), ApplyVecChangeError>
+    where
+        I: IntoIterator<Item = OwnedVecChange<T>>,
+    {
+        let mut changes = pin!(changes);
+        while let Some(batch) = changes.next().await {
+            ac.call(|ac| self.borrow_mut(ac).apply_changes(batch))?;
+        }
+        Ok(())
+    }
 }
 impl<T: Serialize> Serialize for StateVec<T> {
     fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
@@ -661,7 +1930,7 @@ impl<'de, T: Deserialize<'de> + 'static> Deserialize<'de> for StateVec<T> {
                 while let Some(value) = seq.next_element()? {
                     data.push_raw(value)
                 }
-                Ok(StateVec(ChangeFeedState::new(data)))
+                Ok(StateVec::from_model(data))
             }
         }
         deserializer.deserialize_seq(StateVecVisitor(PhantomData))
@@ -675,19 +1944,32 @@ impl<A> FromIterator<A> for StateVec<A> {
         for i in iter {
             data.push_raw(i);
         }
-        Self(ChangeFeedState::new(data))
+        Self::from_model(data)
     }
 }
 
 struct VecModel<T> {
     items: Vec<usize>,
     values: SlabMap<T>,
+    ids: Vec<usize>,
+    keys: HashMap<usize, usize>,
+    next_id: usize,
+    // The index of each item by key, valid for the first `positions_len` items.
+    //
+    // Edits only shorten the valid prefix, and `index_of` extends it as far as it needs to.
+    positions: RefCell<Vec<usize>>,
+    positions_len: Cell<usize>,
 }
 impl<T: 'static> VecModel<T> {
     fn new() -> Self {
         Self {
             items: Vec::new(),
             values: SlabMap::new(),
+            ids: Vec::new(),
+            keys: HashMap::new(),
+            next_id: 0,
+            positions: RefCell::new(Vec::new()),
+            positions_len: Cell::new(0),
         }
     }
     fn len(&self) -> usize {
@@ -698,8 +1980,29 @@ impl<T: 'static> VecModel<T> {
         self.values.reserve(additional);
     }
     fn insert_raw(&mut self, index: usize, value: T) -> usize {
-        let key = self.values.insert(value);
+        let key = self.insert_new_value(value);
         self.items.insert(index, key);
+        self.invalidate_positions(index);
+        key
+    }
+    fn insert_new_value(&mut self, value: T) -> usize {
+        let id = self.next_id;
+        self.next_id += 1;
+        self.insert_value(value, id)
+    }
+    fn insert_value(&mut self, value: T, id: usize) -> usize {
+        let key = self.values.insert(value);
+        if self.ids.len() <= key {
+            self.ids.resize(key + 1, 0);
+        }
+        self.ids[key] = id;
+        self.keys.insert(id, key);
+        key
+    }
+    fn remove_raw(&mut self, index: usize) -> usize {
+        let key = self.items.remove(index);
+        self.keys.remove(&self.ids[key]);
+        self.invalidate_positions(index);
         key
     }
     fn push_raw(&mut self, value: T) {
@@ -709,6 +2012,52 @@ impl<T: 'static> VecModel<T> {
     fn get(&self, index: usize) -> Option<&T> {
         Some(&self.values[*self.items.get(index)?])
     }
+    fn handle(&self, index: usize) -> Option<ItemHandle> {
+        Some(ItemHandle(self.ids[*self.items.get(index)?]))
+    }
+    fn get_by_handle(&self, handle: ItemHandle) -> Option<&T> {
+        Some(&self.values[*self.keys.get(&handle.0)?])
+    }
+    fn index_of(&self, handle: ItemHandle) -> Option<usize> {
+        let key = *self.keys.get(&handle.0)?;
+        let mut positions = self.positions.borrow_mut();
+        let len = self.positions_len.get();
+        if let Some(&index) = positions.get(key)
+            && index < len
+            && self.items[index] == key
+        {
+            return Some(index);
+        }
+        if positions.len() < self.ids.len() {
+            positions.resize(self.ids.len(), 0);
+        }
+        for (index, &k) in self.items.iter().enumerate().skip(len) {
+            positions[k] = index;
+            if k == key {
+                self.positions_len.set(index + 1);
+                return Some(index);
+            }
+        }
+        unreachable!()
+    }
+
+    /// Discards the known indexes of the items at `index` and after.
+    fn invalidate_positions(&mut self, index: usize) {
+        let len = self.positions_len.get_mut();
+        *len = (*len).min(index);
+    }
+
+    /// Records the index of the item at `index` after it was replaced.
+    fn update_position(&mut self, index: usize) {
+        if index < self.positions_len.get() {
+            let key = self.items[index];
+            let positions = self.positions.get_mut();
+            if positions.len() <= key {
+                positions.resize(key + 1, 0);
+            }
+            positions[key] = index;
+        }
+    }
     fn sort_as(
         &mut self,
         mut compare: impl FnMut(&T, &T) -> Ordering,
@@ -727,6 +2076,7 @@ impl<T: 'static> VecModel<T> {
             return None;
         }
         IndexNewToOld::new(&new_to_old).apply_to(&mut self.items);
+        self.invalidate_positions(0);
         Some(ChangeData::Sort { new_to_old })
     }
     fn iter(&self) -> Iter<'_, T> {
@@ -745,9 +2095,100 @@ impl<T: 'static> ChangeFeedModel for VecModel<T> {
             ChangeData::Insert { .. }
             | ChangeData::Move { .. }
             | ChangeData::Swap { .. }
+            | ChangeData::Reverse { .. }
+            | ChangeData::Rotate { .. }
             | ChangeData::Sort { .. } => {}
         }
     }
+
+    fn change_size(&self, change: &Self::Change) -> usize {
+        mem::size_of::<ChangeData>()
+            + match change {
+                ChangeData::Remove { .. } | ChangeData::Set { .. } => mem::size_of::<T>(),
+                ChangeData::Sort { new_to_old } => mem::size_of_val(new_to_old.as_slice()),
+                ChangeData::Insert { .. }
+                | ChangeData::Move { .. }
+                | ChangeData::Swap { .. }
+                | ChangeData::Reverse { .. }
+                | ChangeData::Rotate { .. } => 0,
+            }
+    }
+
+    fn coalesce(&mut self, prev: &mut Self::Change, next: Self::Change) -> Coalesced<Self::Change> {
+        match (&mut *prev, next) {
+            (
+                &mut ChangeData::Insert { index, .. },
+                ChangeData::Remove {
+                    index: next_index,
+                    old_value,
+                },
+            ) if index == next_index => {
+                self.values.remove(old_value);
+                Coalesced::Cancelled
+            }
+            (
+                ChangeData::Insert { index, new_value }
+                | ChangeData::Set {
+                    index, new_value, ..
+                },
+                ChangeData::Set {
+                    index: next_index,
+                    old_value,
+                    new_value: next_value,
+                },
+            ) if *index == next_index => {
+                self.values.remove(old_value);
+                *new_value = next_value;
+                Coalesced::Merged
+            }
+            (
+                &mut ChangeData::Set {
+                    index, old_value, ..
+                },
+                ChangeData::Remove {
+                    index: next_index,
+                    old_value: next_value,
+                },
+            ) if index == next_index => {
+                self.values.remove(next_value);
+                *prev = ChangeData::Remove { index, old_value };
+                Coalesced::Merged
+            }
+            (
+                ChangeData::Insert { index, .. },
+                ChangeData::Move {
+                    old_index,
+                    new_index,
+                },
+            ) if *index == old_index => {
+                *index = new_index;
+                Coalesced::Merged
+            }
+            (
+                ChangeData::Move {
+                    old_index,
+                    new_index,
+                },
+                ChangeData::Move {
+                    old_index: next_old_index,
+                    new_index: next_new_index,
+                },
+            ) if *new_index == next_old_index => {
+                if *old_index == next_new_index {
+                    Coalesced::Cancelled
+                } else {
+                    *new_index = next_new_index;
+                    Coalesced::Merged
+                }
+            }
+            (&mut ChangeData::Swap { index: (i0, i1) }, ChangeData::Swap { index })
+                if index == (i0, i1) || index == (i1, i0) =>
+            {
+                Coalesced::Cancelled
+            }
+            (_, next) => Coalesced::Separate(next),
+        }
+    }
 }
 
 #[cfg(test)]
@@ -0,0 +1,270 @@
+use std::{collections::HashMap, hash::Hash, ops::Range};
+
+use super::{ItemsMut, fenwick::Fenwick};
+
+struct Hunk {
+    old: Range<usize>,
+    new: Range<usize>,
+}
+
+/// Updates `items` to `new` with the changes found by a Myers diff.
+///
+/// Matching runs are kept. Each differing run records `Set` for the overlapping part and
+/// `Remove` or `Insert` for the rest.
+pub(super) fn apply_diff<T: Clone + PartialEq>(items: &mut ItemsMut<T>, new: &[T]) {
+    let hunks = diff_hunks(items.len(), new.len(), |i, j| items[i] == new[j]);
+    for hunk in hunks {
+        let index = hunk.new.start;
+        let common = hunk.old.len().min(hunk.new.len());
+        for i in index..index + common {
+            if items[i] != new[i] {
+                items.set(i, new[i].clone());
+            }
+        }
+        if hunk.old.len() > common {
+            items.drain(index + common..index + hunk.old.len());
+        }
+        for (i, value) in new
+            .iter()
+            .enumerate()
+            .take(hunk.new.end)
+            .skip(index + common)
+        {
+            items.insert(i, value.clone());
+        }
+    }
+}
+
+/// Updates `items` to `new` by matching items with the same key.
+///
+/// Items whose key disappeared are removed, new keys are inserted, and the fewest kept items
+/// necessary are moved. Kept items whose value changed record `Set`.
+pub(super) fn apply_diff_by_key<T: Clone + PartialEq, K: Eq + Hash>(
+    items: &mut ItemsMut<T>,
+    new: &[T],
+    key: impl Fn(&T) -> K,
+) {
+    let mut new_indexes = HashMap::with_capacity(new.len());
+    for (index, value) in new.iter().enumerate() {
+        if new_indexes.insert(key(value), index).is_some() {
+            panic!("duplicate key in `SignalVec::from_signal_diff_by_key`");
+        }
+    }
+    let mut next_id = new.len();
+    let mut ids = items
+        .iter()
+        .map(|value| {
+            new_indexes.get(&key(value)).copied().unwrap_or_else(|| {
+                next_id += 1;
+                next_id - 1
+            })
+        })
+        .collect();
+    let new_ids: Vec<_> = (0..new.len()).collect();
+    apply_diff_by_id(
+        items,
+        &mut ids,
+        &new_ids,
+        |index| new[index].clone(),
+        |index, value| value != &new[index],
+    );
+}
+
+/// Updates `items`, whose identifiers are `ids`, to the items identified by `new_ids`.
+///
+/// `new_value` returns the value of the item at an index of `new_ids`, and `needs_set` tells
+/// whether a kept item must be replaced with that value.
+pub(super) fn apply_diff_by_id<T>(
+    items: &mut ItemsMut<T>,
+    ids: &mut Vec<usize>,
+    new_ids: &[usize],
+    new_value: impl Fn(usize) -> T,
+    needs_set: impl Fn(usize, &T) -> bool,
+) {
+    let new_indexes: HashMap<_, _> = new_ids
+        .iter()
+        .enumerate()
+        .map(|(index, &id)| (id, index))
+        .collect();
+    let old_to_new: Vec<_> = ids.iter().map(|id| new_indexes.get(id).copied()).collect();
+    for index in (0..old_to_new.len()).rev() {
+        if old_to_new[index].is_none() {
+            items.remove(index);
+        }
+    }
+
+    let cur: Vec<usize> = old_to_new.into_iter().flatten().collect();
+    let mut is_kept = vec![false; new_ids.len()];
+    for &i in &cur {
+        is_kept[i] = true;
+    }
+    let mut is_stable = vec![false; new_ids.len()];
+    for i in longest_increasing_subsequence(&cur) {
+        is_stable[cur[i]] = true;
+    }
+
+    // Every item is placed right before the previously placed item, so the positions an item
+    // takes can be laid out in advance: between two stable items come the unstable items kept
+    // there, followed by the final positions of the items placed there.
+    let mut old_slots = vec![usize::MAX; new_ids.len()];
+    let mut new_slots = Vec::with_capacity(new_ids.len());
+    let mut slot = 0;
+    let mut rank = 0;
+    let mut skip_unstable = |slot: &mut usize, rank: &mut usize| {
+        while *rank < cur.len() && !is_stable[cur[*rank]] {
+            old_slots[cur[*rank]] = *slot;
+            *slot += 1;
+            *rank += 1;
+        }
+    };
+    skip_unstable(&mut slot, &mut rank);
+    for &is_stable in &is_stable {
+        new_slots.push(slot);
+        slot += 1;
+        if is_stable {
+            rank += 1;
+            skip_unstable(&mut slot, &mut rank);
+        }
+    }
+    let mut occupied = Fenwick::from_counts((0..slot).map(|_| 0));
+    for i in 0..new_ids.len() {
+        if is_stable[i] {
+            occupied.increment(new_slots[i]);
+        } else if is_kept[i] {
+            occupied.increment(old_slots[i]);
+        }
+    }
+
+    for i in (0..new_ids.len()).rev() {
+        if !is_kept[i] {
+            items.insert(occupied.prefix_sum(new_slots[i]), new_value(i));
+            occupied.increment(new_slots[i]);
+            continue;
+        }
+        if !is_stable[i] {
+            occupied.decrement(old_slots[i]);
+            let old_index = occupied.prefix_sum(old_slots[i]);
+            let new_index = occupied.prefix_sum(new_slots[i]);
+            items.move_item(old_index, new_index);
+            occupied.increment(new_slots[i]);
+        }
+        let index = occupied.prefix_sum(new_slots[i]);
+        if needs_set(i, &items[index]) {
+            items.set(index, new_value(i));
+        }
+    }
+    ids.clear();
+    ids.extend_from_slice(new_ids);
+}
+
+fn diff_hunks(old_len: usize, new_len: usize, eq: impl Fn(usize, usize) -> bool) -> Vec<Hunk> {
+    let mut start = 0;
+    while start < old_len && start < new_len && eq(start, start) {
+        start += 1;
+    }
+    let mut old_end = old_len;
+    let mut new_end = new_len;
+    while start < old_end && start < new_end && eq(old_end - 1, new_end - 1) {
+        old_end -= 1;
+        new_end -= 1;
+    }
+    let n = old_end - start;
+    let m = new_end - start;
+    let matches = myers(n, m, |i, j| eq(start + i, start + j));
+
+    let mut hunks = Vec::new();
+    let (mut x0, mut y0) = (0, 0);
+    for (x, y) in matches.into_iter().chain([(n, m)]) {
+        if x0 < x || y0 < y {
+            hunks.push(Hunk {
+                old: start + x0..start + x,
+                new: start + y0..start + y,
+            });
+        }
+        (x0, y0) = (x + 1, y + 1);
+    }
+    hunks
+}
+
+/// Returns the matched `(old, new)` index pairs of a shortest edit script, in ascending order.
+fn myers(n: usize, m: usize, eq: impl Fn(usize, usize) -> bool) -> Vec<(usize, usize)> {
+    let max = (n + m) as isize;
+    if max == 0 {
+        return Vec::new();
+    }
+    let offset = max + 1;
+    let at = |k: isize| (k + offset) as usize;
+    let mut v = vec![0isize; 2 * max as usize + 3];
+    let mut trace = Vec::new();
+    'search: for d in 0..=max {
+        trace.push(v[at(-d)..=at(d)].to_vec());
+        for k in (-d..=d).step_by(2) {
+            let mut x = if k == -d || (k != d && v[at(k - 1)] < v[at(k + 1)]) {
+                v[at(k + 1)]
+            } else {
+                v[at(k - 1)] + 1
+            };
+            let mut y = x - k;
+            while x < n as isize && y < m as isize && eq(x as usize, y as usize) {
+                x += 1;
+                y += 1;
+            }
+            v[at(k)] = x;
+            if x >= n as isize && y >= m as isize {
+                break 'search;
+            }
+        }
+    }
+
+    let mut matches = Vec::new();
+    let (mut x, mut y) = (n as isize, m as isize);
+    for d in (0..trace.len() as isize).rev() {
+        let v = &trace[d as usize];
+        let get = |k: isize| v[(k + d) as usize];
+        let k = x - y;
+        let (prev_x, prev_y, mid_x, mid_y) = if d == 0 {
+            (0, 0, 0, 0)
+        } else if k == -d || (k != d && get(k - 1) < get(k + 1)) {
+            let prev_x = get(k + 1);
+            let prev_y = prev_x - (k + 1);
+            (prev_x, prev_y, prev_x, prev_y + 1)
+        } else {
+            let prev_x = get(k - 1);
+            let prev_y = prev_x - (k - 1);
+            (prev_x, prev_y, prev_x + 1, prev_y)
+        };
+        while x > mid_x && y > mid_y {
+            x -= 1;
+            y -= 1;
+            matches.push((x as usize, y as usize));
+        }
+        (x, y) = (prev_x, prev_y);
+    }
+    matches.reverse();
+    matches
+}
+
+/// Returns the positions of a longest strictly increasing subsequence of `values`.
+fn longest_increasing_subsequence(values: &[usize]) -> Vec<usize> {
+    let mut tails: Vec<usize> = Vec::new();
+    let mut prev = vec![usize::MAX; values.len()];
+    for (i, &value) in values.iter().enumerate() {
+        let pos = tails.partition_point(|&t| values[t] < value);
+        if pos > 0 {
+            prev[i] = tails[pos - 1];
+        }
+        if pos == tails.len() {
+            tails.push(i);
+        } else {
+            tails[pos] = i;
+        }
+    }
+    let mut result = Vec::with_capacity(tails.len());
+    let mut i = tails.last().copied().unwrap_or(usize::MAX);
+    while i != usize::MAX {
+        result.push(i);
+        i = prev[i];
+    }
+    result.reverse();
+    result
+}
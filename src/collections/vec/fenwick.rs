@@ -0,0 +1,55 @@
+/// A Fenwick tree of counts that answers prefix sums in `O(log n)`.
+pub(super) struct Fenwick(Vec<usize>);
+
+impl Fenwick {
+    /// Creates a tree with the count of each index in `counts`.
+    pub fn from_counts(counts: impl IntoIterator<Item = usize>) -> Self {
+        let mut tree: Vec<usize> = counts.into_iter().collect();
+        for index in 0..tree.len() {
+            let parent = index | (index + 1);
+            if parent < tree.len() {
+                tree[parent] += tree[index];
+            }
+        }
+        Self(tree)
+    }
+
+    pub fn increment(&mut self, mut index: usize) {
+        while index < self.0.len() {
+            self.0[index] += 1;
+            index |= index + 1;
+        }
+    }
+
+    pub fn decrement(&mut self, mut index: usize) {
+        while index < self.0.len() {
+            self.0[index] -= 1;
+            index |= index + 1;
+        }
+    }
+
+    /// Returns the sum of the counts of indexes less than `end`.
+    pub fn prefix_sum(&self, mut end: usize) -> usize {
+        let mut sum = 0;
+        while end > 0 {
+            sum += self.0[end - 1];
+            end &= end - 1;
+        }
+        sum
+    }
+
+    /// Returns the index at which the prefix sum first exceeds `sum`, or the length of the tree
+    /// if there is no such index.
+    pub fn find(&self, mut sum: usize) -> usize {
+        let mut index = 0;
+        let mut step = self.0.len().next_power_of_two();
+        while step > 0 {
+            if index + step <= self.0.len() && self.0[index + step - 1] <= sum {
+                index += step;
+                sum -= self.0[index - 1];
+            }
+            step /= 2;
+        }
+        index
+    }
+}
@@ -0,0 +1,252 @@
+use std::collections::BTreeMap;
+
+use super::{ItemsMut, VecChange, fenwick::Fenwick};
+
+const CHUNK_LEN: usize = 64;
+
+/// The keys of the items of a source vector, indexed to find the position of an item among the
+/// items with the same key.
+///
+/// Keys are stored in chunks, and the number of keys in each chunk is kept in Fenwick trees, so
+/// finding a position takes `O(log n + CHUNK_LEN)`.
+pub(super) struct KeyedIndex<K> {
+    chunks: Vec<Vec<K>>,
+    lens: Fenwick,
+    counts: BTreeMap<K, Fenwick>,
+}
+
+impl<K: Ord + Clone> KeyedIndex<K> {
+    pub fn new() -> Self {
+        Self::from_keys(Vec::new())
+    }
+    pub fn from_keys(keys: Vec<K>) -> Self {
+        let mut this = Self {
+            chunks: keys.chunks(CHUNK_LEN).map(|chunk| chunk.to_vec()).collect(),
+            lens: Fenwick::from_counts([]),
+            counts: BTreeMap::new(),
+        };
+        this.rebuild();
+        this
+    }
+    fn rebuild(&mut self) {
+        self.lens = Fenwick::from_counts(self.chunks.iter().map(Vec::len));
+        let mut counts = BTreeMap::<K, Vec<usize>>::new();
+        for (index, chunk) in self.chunks.iter().enumerate() {
+            for key in chunk {
+                if let Some(counts) = counts.get_mut(key) {
+                    counts[index] += 1;
+                } else {
+                    let mut c = vec![0; self.chunks.len()];
+                    c[index] = 1;
+                    counts.insert(key.clone(), c);
+                }
+            }
+        }
+        self.counts = counts
+            .into_iter()
+            .map(|(key, counts)| (key, Fenwick::from_counts(counts)))
+            .collect();
+    }
+
+    pub fn len(&self) -> usize {
+        self.lens.prefix_sum(self.chunks.len())
+    }
+    pub fn keys(&self) -> impl Iterator<Item = &K> {
+        self.chunks.iter().flatten()
+    }
+
+    /// Returns the number of items with `key`.
+    pub fn count(&self, key: &K) -> usize {
+        self.counts
+            .get(key)
+            .map_or(0, |counts| counts.prefix_sum(self.chunks.len()))
+    }
+
+    /// Returns the chunk containing the item at `index` and the offset of the item in it.
+    fn locate(&self, index: usize) -> (usize, usize) {
+        let chunk = self.lens.find(index);
+        (chunk, index - self.lens.prefix_sum(chunk))
+    }
+
+    pub fn get(&self, index: usize) -> &K {
+        let (chunk, offset) = self.locate(index);
+        &self.chunks[chunk][offset]
+    }
+
+    /// Returns the number of items with `key` before `index`.
+    pub fn position(&self, index: usize, key: &K) -> usize {
+        if index == self.len() {
+            return self.count(key);
+        }
+        let (chunk, offset) = self.locate(index);
+        let before = self
+            .counts
+            .get(key)
+            .map_or(0, |counts| counts.prefix_sum(chunk));
+        before
+            + self.chunks[chunk][..offset]
+                .iter()
+                .filter(|k| *k == key)
+                .count()
+    }
+
+    pub fn insert(&mut self, index: usize, key: K) {
+        if self.chunks.is_empty() {
+            self.chunks.push(Vec::new());
+            self.rebuild();
+        }
+        let (chunk, offset) = if index == self.len() {
+            let chunk = self.chunks.len() - 1;
+            (chunk, self.chunks[chunk].len())
+        } else {
+            self.locate(index)
+        };
+        self.increment(chunk, &key);
+        self.chunks[chunk].insert(offset, key);
+        if self.chunks[chunk].len() > CHUNK_LEN * 2 {
+            let tail = self.chunks[chunk].split_off(CHUNK_LEN);
+            self.chunks.insert(chunk + 1, tail);
+            self.rebuild();
+        }
+    }
+
+    pub fn remove(&mut self, index: usize) -> K {
+        let (chunk, offset) = self.locate(index);
+        let key = self.chunks[chunk].remove(offset);
+        self.decrement(chunk, &key);
+        if self.chunks[chunk].is_empty() {
+            self.chunks.remove(chunk);
+            self.rebuild();
+        }
+        key
+    }
+
+    /// Replaces the key of the item at `index` and returns the old key.
+    pub fn set(&mut self, index: usize, key: K) -> K {
+        let (chunk, offset) = self.locate(index);
+        self.increment(chunk, &key);
+        let old = std::mem::replace(&mut self.chunks[chunk][offset], key);
+        self.decrement(chunk, &old);
+        old
+    }
+
+    fn increment(&mut self, chunk: usize, key: &K) {
+        self.lens.increment(chunk);
+        let len = self.chunks.len();
+        self.counts
+            .entry(key.clone())
+            .or_insert_with(|| Fenwick::from_counts(vec![0; len]))
+            .increment(chunk);
+    }
+    fn decrement(&mut self, chunk: usize, key: &K) {
+        self.lens.decrement(chunk);
+        let counts = self.counts.get_mut(key).unwrap();
+        counts.decrement(chunk);
+        if counts.prefix_sum(self.chunks.len()) == 0 {
+            self.counts.remove(key);
+        }
+    }
+}
+
+/// Tracks which items of a source vector are included in a filtered vector.
+pub(super) struct Filter(KeyedIndex<bool>);
+
+impl Filter {
+    pub fn new() -> Self {
+        Self(KeyedIndex::new())
+    }
+
+    /// Returns the index in the filtered vector of the source item at `index`.
+    fn position(&self, index: usize) -> usize {
+        self.0.position(index, &true)
+    }
+
+    pub fn apply<T: Clone>(
+        &mut self,
+        items: &mut ItemsMut<T>,
+        change: VecChange<T>,
+        predicate: impl Fn(&T) -> bool,
+    ) {
+        match change {
+            VecChange::Insert { index, new_value } => {
+                let included = predicate(new_value);
+                self.0.insert(index, included);
+                if included {
+                    items.insert(self.position(index), new_value.clone());
+                }
+            }
+            VecChange::Remove { index, .. } => {
+                if self.0.remove(index) {
+                    items.remove(self.position(index));
+                }
+            }
+            VecChange::Set {
+                index, new_value, ..
+            } => {
+                let included = predicate(new_value);
+                let position = self.position(index);
+                match (self.0.set(index, included), included) {
+                    (true, true) => items.set(position, new_value.clone()),
+                    (true, false) => items.remove(position),
+                    (false, true) => items.insert(position, new_value.clone()),
+                    (false, false) => {}
+                }
+            }
+            VecChange::Move {
+                old_index,
+                new_index,
+            } => {
+                let old_position = self.position(old_index);
+                let included = self.0.remove(old_index);
+                self.0.insert(new_index, included);
+                if included {
+                    items.move_item(old_position, self.position(new_index));
+                }
+            }
+            VecChange::Swap { index: (i0, i1) } => {
+                let (i0, i1) = (i0.min(i1), i0.max(i1));
+                let (p0, p1) = (self.position(i0), self.position(i1));
+                let included1 = *self.0.get(i1);
+                let included0 = self.0.set(i0, included1);
+                self.0.set(i1, included0);
+                match (included0, included1) {
+                    (true, true) => items.swap(p0, p1),
+                    (true, false) => items.move_item(p0, p1 - 1),
+                    (false, true) => items.move_item(p1, p0),
+                    (false, false) => {}
+                }
+            }
+            VecChange::Reverse { .. } => {
+                let mut included: Vec<bool> = self.0.keys().copied().collect();
+                included.reverse();
+                self.0 = KeyedIndex::from_keys(included);
+                items.reverse();
+            }
+            VecChange::Rotate { mid, .. } => {
+                let position = self.position(mid);
+                let mut included: Vec<bool> = self.0.keys().copied().collect();
+                included.rotate_left(mid);
+                self.0 = KeyedIndex::from_keys(included);
+                items.rotate_left(position);
+            }
+            VecChange::Sort(new_to_old) => {
+                let mut included: Vec<bool> = self.0.keys().copied().collect();
+                let mut positions = Vec::with_capacity(included.len());
+                let mut count = 0;
+                for &included in &included {
+                    positions.push(count);
+                    count += usize::from(included);
+                }
+                let new_to_old_positions: Vec<_> = new_to_old
+                    .as_slice()
+                    .iter()
+                    .filter(|&&old| included[old])
+                    .map(|&old| positions[old])
+                    .collect();
+                new_to_old.apply_to(&mut included);
+                self.0 = KeyedIndex::from_keys(included);
+                items.permute(new_to_old_positions);
+            }
+        }
+    }
+}
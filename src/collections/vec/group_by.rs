@@ -0,0 +1,242 @@
+use std::{
+    collections::{BTreeMap, HashMap},
+    mem,
+};
+
+use super::{Items, OwnedVecChange, VecChange, filter::KeyedIndex};
+
+/// Distributes the deltas of a source vector to the groups of its items.
+///
+/// Each delta is translated into changes of the groups it affects only, and the changes are queued
+/// until the group reads them.
+pub(super) struct GroupRouter<K, T> {
+    keys: KeyedIndex<K>,
+    ids: BTreeMap<K, usize>,
+    queues: HashMap<usize, Vec<OwnedVecChange<T>>>,
+    next_id: usize,
+    is_keys_changed: bool,
+}
+
+impl<K: Ord + Clone, T: Clone> GroupRouter<K, T> {
+    pub fn new() -> Self {
+        Self {
+            keys: KeyedIndex::new(),
+            ids: BTreeMap::new(),
+            queues: HashMap::new(),
+            next_id: 0,
+            is_keys_changed: false,
+        }
+    }
+
+    /// Returns the identifier of the group of each key.
+    pub fn ids(&self) -> &BTreeMap<K, usize> {
+        &self.ids
+    }
+
+    /// Returns whether a group was added or removed since the last call.
+    pub fn take_keys_changed(&mut self) -> bool {
+        mem::take(&mut self.is_keys_changed)
+    }
+
+    /// Takes the changes queued for the group `id`, or returns `None` if the group was removed.
+    pub fn take_changes(&mut self, id: usize) -> Option<Vec<OwnedVecChange<T>>> {
+        self.queues.get_mut(&id).map(mem::take)
+    }
+
+    pub fn apply(&mut self, source: &Items<T>, key_fn: &impl Fn(&T) -> K) {
+        let is_initial = source.is_initial();
+        if is_initial {
+            self.keys = KeyedIndex::new();
+            for queue in self.queues.values_mut() {
+                *queue = vec![OwnedVecChange::Initial(Vec::new())];
+            }
+        }
+        for change in source.delta() {
+            self.apply_change(change, key_fn);
+        }
+        if is_initial {
+            let keys: Vec<_> = self.ids.keys().cloned().collect();
+            for key in keys {
+                self.remove_if_empty(&key);
+            }
+        }
+    }
+
+    fn apply_change(&mut self, change: VecChange<T>, key_fn: &impl Fn(&T) -> K) {
+        match change {
+            VecChange::Insert { index, new_value } => {
+                let key = key_fn(new_value);
+                self.keys.insert(index, key.clone());
+                let index = self.keys.position(index, &key);
+                self.push(
+                    &key,
+                    OwnedVecChange::Insert {
+                        index,
+                        new_value: new_value.clone(),
+                    },
+                );
+            }
+            VecChange::Remove { index, old_value } => {
+                let key = self.keys.remove(index);
+                let index = self.keys.position(index, &key);
+                self.push(
+                    &key,
+                    OwnedVecChange::Remove {
+                        index,
+                        old_value: old_value.clone(),
+                    },
+                );
+                self.remove_if_empty(&key);
+            }
+            VecChange::Set {
+                index,
+                new_value,
+                old_value,
+            } => {
+                let key = key_fn(new_value);
+                let old_key = self.keys.get(index).clone();
+                if key == old_key {
+                    let index = self.keys.position(index, &key);
+                    self.push(
+                        &key,
+                        OwnedVecChange::Set {
+                            index,
+                            new_value: new_value.clone(),
+                            old_value: old_value.clone(),
+                        },
+                    );
+                    return;
+                }
+                let old_position = self.keys.position(index, &old_key);
+                self.keys.set(index, key.clone());
+                self.push(
+                    &old_key,
+                    OwnedVecChange::Remove {
+                        index: old_position,
+                        old_value: old_value.clone(),
+                    },
+                );
+                self.remove_if_empty(&old_key);
+                let new_position = self.keys.position(index, &key);
+                self.push(
+                    &key,
+                    OwnedVecChange::Insert {
+                        index: new_position,
+                        new_value: new_value.clone(),
+                    },
+                );
+            }
+            VecChange::Move {
+                old_index,
+                new_index,
+            } => {
+                let key = self.keys.get(old_index).clone();
+                let old_position = self.keys.position(old_index, &key);
+                self.keys.remove(old_index);
+                self.keys.insert(new_index, key.clone());
+                let new_position = self.keys.position(new_index, &key);
+                self.push_move(&key, old_position, new_position);
+            }
+            VecChange::Swap { index: (i0, i1) } => {
+                let (i0, i1) = (i0.min(i1), i0.max(i1));
+                let k0 = self.keys.get(i0).clone();
+                let k1 = self.keys.get(i1).clone();
+                let p0 = self.keys.position(i0, &k0);
+                let p1 = self.keys.position(i1, &k1);
+                if k0 == k1 {
+                    self.push(&k0, OwnedVecChange::Swap { index: (p0, p1) });
+                    return;
+                }
+                self.keys.set(i0, k1.clone());
+                self.keys.set(i1, k0.clone());
+                let new_p0 = self.keys.position(i1, &k0);
+                let new_p1 = self.keys.position(i0, &k1);
+                self.push_move(&k0, p0, new_p0);
+                self.push_move(&k1, p1, new_p1);
+            }
+            VecChange::Reverse { .. } => {
+                let mut keys: Vec<K> = self.keys.keys().cloned().collect();
+                for key in self.ids.keys().cloned().collect::<Vec<_>>() {
+                    let len = self.keys.count(&key);
+                    if len > 1 {
+                        self.push(&key, OwnedVecChange::Reverse { len });
+                    }
+                }
+                keys.reverse();
+                self.keys = KeyedIndex::from_keys(keys);
+            }
+            VecChange::Rotate { mid, .. } => {
+                let mut keys: Vec<K> = self.keys.keys().cloned().collect();
+                for key in self.ids.keys().cloned().collect::<Vec<_>>() {
+                    let len = self.keys.count(&key);
+                    let mid = self.keys.position(mid, &key);
+                    if mid != 0 && mid != len {
+                        self.push(&key, OwnedVecChange::Rotate { len, mid });
+                    }
+                }
+                keys.rotate_left(mid);
+                self.keys = KeyedIndex::from_keys(keys);
+            }
+            VecChange::Sort(new_to_old) => {
+                let keys: Vec<K> = self.keys.keys().cloned().collect();
+                let mut counts = BTreeMap::<&K, usize>::new();
+                let positions: Vec<usize> = keys
+                    .iter()
+                    .map(|key| {
+                        let count = counts.entry(key).or_default();
+                        *count += 1;
+                        *count - 1
+                    })
+                    .collect();
+                let mut orders = BTreeMap::<&K, Vec<usize>>::new();
+                for &old in new_to_old.as_slice() {
+                    orders.entry(&keys[old]).or_default().push(positions[old]);
+                }
+                for (key, order) in orders {
+                    if order.iter().enumerate().any(|(new, &old)| new != old) {
+                        self.push(key, OwnedVecChange::Sort(order));
+                    }
+                }
+                let sorted = new_to_old
+                    .as_slice()
+                    .iter()
+                    .map(|&old| keys[old].clone())
+                    .collect();
+                self.keys = KeyedIndex::from_keys(sorted);
+            }
+        }
+    }
+
+    fn push(&mut self, key: &K, change: OwnedVecChange<T>) {
+        let id = match self.ids.get(key) {
+            Some(&id) => id,
+            None => {
+                let id = self.next_id;
+                self.next_id += 1;
+                self.ids.insert(key.clone(), id);
+                self.is_keys_changed = true;
+                id
+            }
+        };
+        self.queues.entry(id).or_default().push(change);
+    }
+    fn push_move(&mut self, key: &K, old_index: usize, new_index: usize) {
+        if old_index != new_index {
+            self.push(
+                key,
+                OwnedVecChange::Move {
+                    old_index,
+                    new_index,
+                },
+            );
+        }
+    }
+    fn remove_if_empty(&mut self, key: &K) {
+        if self.keys.count(key) == 0
+            && let Some(id) = self.ids.remove(key)
+        {
+            self.queues.remove(&id);
+            self.is_keys_changed = true;
+        }
+    }
+}
@@ -0,0 +1,623 @@
+use std::{
+    cell::{Ref, RefCell},
+    collections::{BTreeMap, BTreeSet, HashMap, HashSet},
+    mem,
+    ops::RangeBounds,
+    rc::Rc,
+};
+
+use crate::{
+    SignalContext, StateRef,
+    building_blocks::change_feed::{
+        ChangeFeedDelta, ChangeFeedReader, ChangeFeedRef, ChangeFeedSignal,
+    },
+    core::{
+        BindKey, BindSink, BindSource, DirtyLevel, NotifyContext, ReactionContext, SinkBindings,
+        Slot, SourceBinder,
+    },
+    utils::IndexNewToOld,
+};
+
+use super::{ChangeData, ItemHandle, VecModel};
+
+const SOURCE_SLOT: Slot = Slot(0);
+
+/// The slot at which the tracker is notified of every direct edit of a source state.
+pub(super) const EDIT_SLOT: Slot = Slot(1);
+
+/// Tracks reads of individual items of a changing vector.
+///
+/// When a source state is edited directly, the tracker reads the delta right away and notifies
+/// only the reads of the items it touched. A derived source is only known to have changed after
+/// it is evaluated, so its changes mark all item reads as maybe dirty, and the delta is read when
+/// a read is checked.
+pub(super) struct ItemTracker<T: 'static> {
+    source: ChangeFeedSignal<VecModel<T>>,
+    data: RefCell<TrackerData<T>>,
+    sinks: RefCell<ItemSinks>,
+}
+
+struct TrackerData<T: 'static> {
+    source_binder: SourceBinder,
+    reader: Option<ChangeFeedReader<VecModel<T>>>,
+    ids: Vec<usize>,
+}
+
+impl<T: 'static> ItemTracker<T> {
+    pub fn new(source: ChangeFeedSignal<VecModel<T>>) -> Rc<Self> {
+        Rc::new_cyclic(|this| Self {
+            source,
+            data: RefCell::new(TrackerData {
+                source_binder: SourceBinder::new(this, SOURCE_SLOT),
+                reader: None,
+                ids: Vec::new(),
+            }),
+            sinks: RefCell::new(ItemSinks::new()),
+        })
+    }
+
+    pub fn source(&self) -> &ChangeFeedSignal<VecModel<T>> {
+        &self.source
+    }
+
+    pub fn item<'a, 'r: 'a>(
+        self: &'a Rc<Self>,
+        index: usize,
+        sc: &mut SignalContext<'r, '_>,
+    ) -> Option<StateRef<'a, T>> {
+        self.watch(ItemSlot::Index(index), sc);
+        Ref::filter_map(self.source.current_ref_untracked(), |model| {
+            model.get(index)
+        })
+        .ok()
+        .map(StateRef::from)
+    }
+
+    pub fn item_by_handle<'a, 'r: 'a>(
+        self: &'a Rc<Self>,
+        handle: ItemHandle,
+        sc: &mut SignalContext<'r, '_>,
+    ) -> Option<StateRef<'a, T>> {
+        self.watch(ItemSlot::Handle(handle.0), sc);
+        Ref::filter_map(self.source.current_ref_untracked(), |model| {
+            model.get_by_handle(handle)
+        })
+        .ok()
+        .map(StateRef::from)
+    }
+
+    pub fn index_of(
+        self: &Rc<Self>,
+        handle: ItemHandle,
+        sc: &mut SignalContext<'_, '_>,
+    ) -> Option<usize> {
+        self.watch(ItemSlot::Position(handle.0), sc);
+        let index = self.source.current_ref_untracked().index_of(handle);
+        self.sinks.borrow_mut().set_position(handle.0, index);
+        index
+    }
+
+    fn update(self: &Rc<Self>, rc: &mut ReactionContext<'_, '_>) {
+        if rc.borrow(&self.data).source_binder.is_clean() {
+            return;
+        }
+        let TrackerData {
+            source_binder,
+            reader,
+            ids,
+        } = &mut *self.data.borrow_mut();
+        let mut changed = ChangedItems::new();
+        if source_binder.check(rc) {
+            source_binder.update(
+                |sc| {
+                    let reader = reader.get_or_insert_with(|| self.source.reader());
+                    self.sinks
+                        .borrow_mut()
+                        .read_changes(&reader.read(sc), ids, &mut changed);
+                },
+                rc,
+            );
+        }
+        self.sinks.borrow_mut().update(&changed, rc);
+    }
+
+    /// Reads the changes of a directly edited source and notifies the reads they touched.
+    fn read_edits(&self, nc: &mut NotifyContext) {
+        let mut changed = ChangedItems::new();
+        {
+            let TrackerData { reader, ids, .. } = &mut *self.data.borrow_mut();
+            let Some(reader) = reader else {
+                return;
+            };
+            self.sinks
+                .borrow_mut()
+                .read_changes(&reader.read_untracked(), ids, &mut changed);
+        }
+        self.sinks.borrow_mut().notify_changed(&changed, nc);
+    }
+
+    fn watch(self: &Rc<Self>, slot: ItemSlot, sc: &mut SignalContext<'_, '_>) {
+        self.update(sc.rc());
+        self.sinks.borrow_mut().bind(self.clone(), slot, sc);
+    }
+}
+
+impl<T: 'static> BindSource for ItemTracker<T> {
+    fn check(self: Rc<Self>, slot: Slot, key: BindKey, rc: &mut ReactionContext<'_, '_>) -> bool {
+        self.update(rc);
+        self.sinks
+            .borrow()
+            .is_dirty(ItemSlot::from_slot(slot), key, rc)
+    }
+
+    fn unbind(self: Rc<Self>, slot: Slot, key: BindKey, rc: &mut ReactionContext<'_, '_>) {
+        let is_empty = {
+            let mut sinks = self.sinks.borrow_mut();
+            sinks.unbind(ItemSlot::from_slot(slot), key, rc);
+            sinks.is_empty()
+        };
+        if is_empty {
+            let data = &mut *self.data.borrow_mut();
+            data.source_binder.clear(rc);
+            data.reader = None;
+            data.ids.clear();
+        }
+    }
+
+    fn rebind(self: Rc<Self>, slot: Slot, key: BindKey, sc: &mut SignalContext<'_, '_>) {
+        self.sinks
+            .borrow_mut()
+            .rebind(self.clone(), ItemSlot::from_slot(slot), key, sc);
+    }
+}
+
+impl<T: 'static> BindSink for ItemTracker<T> {
+    fn notify(self: Rc<Self>, slot: Slot, level: DirtyLevel, nc: &mut NotifyContext) {
+        if slot == EDIT_SLOT {
+            self.read_edits(nc);
+            return;
+        }
+        if self.data.borrow_mut().source_binder.on_notify(slot, level) {
+            if level == DirtyLevel::Dirty {
+                self.read_edits(nc);
+            } else {
+                self.sinks.borrow_mut().notify_all(nc);
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum ItemSlot {
+    Index(usize),
+    Handle(usize),
+    Position(usize),
+}
+
+impl ItemSlot {
+    fn from_slot(slot: Slot) -> Self {
+        match slot.0 % 3 {
+            0 => Self::Index(slot.0 / 3),
+            1 => Self::Handle(slot.0 / 3),
+            _ => Self::Position(slot.0 / 3),
+        }
+    }
+
+    /// Returns `None` if the slot number overflows.
+    ///
+    /// Such an index or handle can never refer to an item, so its read needs no dependency.
+    fn to_slot(self) -> Option<Slot> {
+        let (n, offset) = match self {
+            Self::Index(index) => (index, 0),
+            Self::Handle(id) => (id, 1),
+            Self::Position(id) => (id, 2),
+        };
+        n.checked_mul(3)?.checked_add(offset).map(Slot)
+    }
+}
+
+struct PositionSinks {
+    sinks: SinkBindings,
+    index: Option<usize>,
+}
+
+struct ItemSinks {
+    indexes: BTreeMap<usize, SinkBindings>,
+    handles: HashMap<usize, SinkBindings>,
+    positions: HashMap<usize, PositionSinks>,
+    // Identifiers of the items in `positions` with a known index, by index.
+    position_ids: BTreeMap<usize, usize>,
+    // Whether all reads have been marked as maybe dirty and wait for `update`.
+    is_maybe_dirty: bool,
+}
+
+impl ItemSinks {
+    fn new() -> Self {
+        Self {
+            indexes: BTreeMap::new(),
+            handles: HashMap::new(),
+            positions: HashMap::new(),
+            position_ids: BTreeMap::new(),
+            is_maybe_dirty: false,
+        }
+    }
+
+    fn is_empty(&self) -> bool {
+        self.indexes.is_empty() && self.handles.is_empty() && self.positions.is_empty()
+    }
+
+    fn get(&self, slot: ItemSlot) -> Option<&SinkBindings> {
+        match slot {
+            ItemSlot::Index(index) => self.indexes.get(&index),
+            ItemSlot::Handle(id) => self.handles.get(&id),
+            ItemSlot::Position(id) => self.positions.get(&id).map(|p| &p.sinks),
+        }
+    }
+
+    fn get_mut(&mut self, slot: ItemSlot) -> Option<&mut SinkBindings> {
+        match slot {
+            ItemSlot::Index(index) => self.indexes.get_mut(&index),
+            ItemSlot::Handle(id) => self.handles.get_mut(&id),
+            ItemSlot::Position(id) => self.positions.get_mut(&id).map(|p| &mut p.sinks),
+        }
+    }
+
+    fn bind(&mut self, this: Rc<dyn BindSource>, slot: ItemSlot, sc: &mut SignalContext<'_, '_>) {
+        let Some(this_slot) = slot.to_slot() else {
+            return;
+        };
+        let sinks = match slot {
+            ItemSlot::Index(index) => self.indexes.entry(index).or_default(),
+            ItemSlot::Handle(id) => self.handles.entry(id).or_default(),
+            ItemSlot::Position(id) => {
+                &mut self
+                    .positions
+                    .entry(id)
+                    .or_insert_with(|| PositionSinks {
+                        sinks: SinkBindings::new(),
+                        index: None,
+                    })
+                    .sinks
+            }
+        };
+        sinks.bind(this, this_slot, sc);
+    }
+
+    fn unbind(&mut self, slot: ItemSlot, key: BindKey, rc: &mut ReactionContext<'_, '_>) {
+        let Some(sinks) = self.get_mut(slot) else {
+            return;
+        };
+        sinks.unbind(key, rc);
+        if sinks.is_empty() {
+            match slot {
+                ItemSlot::Index(index) => {
+                    self.indexes.remove(&index);
+                }
+                ItemSlot::Handle(id) => {
+                    self.handles.remove(&id);
+                }
+                ItemSlot::Position(id) => {
+                    if let Some(index) = self.positions.remove(&id).and_then(|p| p.index) {
+                        self.position_ids.remove(&index);
+                    }
+                }
+            }
+        }
+    }
+
+    fn rebind(
+        &mut self,
+        this: Rc<dyn BindSource>,
+        slot: ItemSlot,
+        key: BindKey,
+        sc: &mut SignalContext<'_, '_>,
+    ) {
+        let Some(this_slot) = slot.to_slot() else {
+            return;
+        };
+        if let Some(sinks) = self.get_mut(slot) {
+            sinks.rebind(this, this_slot, key, sc);
+        }
+    }
+
+    fn is_dirty(&self, slot: ItemSlot, key: BindKey, rc: &mut ReactionContext<'_, '_>) -> bool {
+        self.get(slot).is_none_or(|sinks| sinks.is_dirty(key, rc))
+    }
+
+    fn read_changes<T: 'static>(
+        &mut self,
+        value: &ChangeFeedRef<VecModel<T>>,
+        ids: &mut Vec<usize>,
+        changed: &mut ChangedItems,
+    ) {
+        let model = value.current();
+        match value.delta() {
+            ChangeFeedDelta::Initial => {
+                let new_ids = model.items.iter().map(|&key| model.ids[key]).collect();
+                changed.reset(mem::replace(ids, new_ids), ids);
+                self.reset_positions(ids, changed);
+            }
+            ChangeFeedDelta::Incremental(changes) => {
+                for change in changes {
+                    changed.apply(change, ids, model);
+                    self.apply_positions(change, changed);
+                }
+            }
+        }
+    }
+
+    fn reset_positions(&mut self, ids: &[usize], changed: &mut ChangedItems) {
+        if self.positions.is_empty() {
+            return;
+        }
+        let indexes: HashMap<_, _> = ids
+            .iter()
+            .enumerate()
+            .map(|(index, &id)| (id, index))
+            .collect();
+        self.position_ids.clear();
+        for (&id, position) in &mut self.positions {
+            let index = indexes.get(&id).copied();
+            if index != position.index {
+                position.index = index;
+                changed.positions.insert(id);
+            }
+            if let Some(index) = index {
+                self.position_ids.insert(index, id);
+            }
+        }
+    }
+
+    fn apply_positions(&mut self, change: &ChangeData, changed: &mut ChangedItems) {
+        match change {
+            &ChangeData::Insert { index, .. } => {
+                self.shift_positions(index.., |i| i + 1, changed);
+            }
+            &ChangeData::Remove { index, .. } => {
+                if let Some(id) = self.position_ids.remove(&index) {
+                    self.set_index(id, None, changed);
+                }
+                self.shift_positions(index + 1.., |i| i - 1, changed);
+            }
+            ChangeData::Set { .. } => {}
+            &ChangeData::Move {
+                old_index,
+                new_index,
+            } => {
+                let id = self.position_ids.remove(&old_index);
+                if old_index < new_index {
+                    self.shift_positions(old_index + 1..=new_index, |i| i - 1, changed);
+                } else {
+                    self.shift_positions(new_index..old_index, |i| i + 1, changed);
+                }
+                if let Some(id) = id {
+                    self.position_ids.insert(new_index, id);
+                    self.set_index(id, Some(new_index), changed);
+                }
+            }
+            &ChangeData::Swap { index: (i0, i1) } => {
+                let id0 = self.position_ids.remove(&i0);
+                let id1 = self.position_ids.remove(&i1);
+                for (index, id) in [(i1, id0), (i0, id1)] {
+                    if let Some(id) = id {
+                        self.position_ids.insert(index, id);
+                        self.set_index(id, Some(index), changed);
+                    }
+                }
+            }
+            &ChangeData::Reverse { len } => {
+                self.map_positions(|index| len - 1 - index, changed);
+            }
+            &ChangeData::Rotate { len, mid } => {
+                self.map_positions(|index| (index + len - mid) % len, changed);
+            }
+            ChangeData::Sort { new_to_old } => {
+                if self.position_ids.is_empty() {
+                    return;
+                }
+                let old_to_new = IndexNewToOld::new(new_to_old).build_old_to_new();
+                self.map_positions(|index| old_to_new[index], changed);
+            }
+        }
+    }
+
+    /// Moves the items with a known index in `range` to the index returned by `f`.
+    fn shift_positions(
+        &mut self,
+        range: impl RangeBounds<usize>,
+        f: impl Fn(usize) -> usize,
+        changed: &mut ChangedItems,
+    ) {
+        let moved: Vec<_> = self
+            .position_ids
+            .range(range)
+            .map(|(&index, &id)| (index, id))
+            .collect();
+        for &(index, _) in &moved {
+            self.position_ids.remove(&index);
+        }
+        for (index, id) in moved {
+            let index = f(index);
+            self.position_ids.insert(index, id);
+            self.set_index(id, Some(index), changed);
+        }
+    }
+
+    /// Moves every item with a known index to the index returned by `f`.
+    fn map_positions(&mut self, f: impl Fn(usize) -> usize, changed: &mut ChangedItems) {
+        for (index, id) in mem::take(&mut self.position_ids) {
+            let index = f(index);
+            self.position_ids.insert(index, id);
+            self.set_index(id, Some(index), changed);
+        }
+    }
+
+    fn set_index(&mut self, id: usize, index: Option<usize>, changed: &mut ChangedItems) {
+        if let Some(position) = self.positions.get_mut(&id)
+            && position.index != index
+        {
+            position.index = index;
+            changed.positions.insert(id);
+        }
+    }
+
+    /// Resolves the reads marked as maybe dirty by [`notify_all`](Self::notify_all).
+    fn update(&mut self, changed: &ChangedItems, rc: &mut ReactionContext<'_, '_>) {
+        if !mem::take(&mut self.is_maybe_dirty) {
+            return;
+        }
+        for (&index, sinks) in &mut self.indexes {
+            sinks.update(changed.contains_index(index), rc);
+        }
+        for (id, sinks) in &mut self.handles {
+            sinks.update(changed.handles.contains(id), rc);
+        }
+        for (id, position) in &mut self.positions {
+            position.sinks.update(changed.positions.contains(id), rc);
+        }
+    }
+
+    /// Notifies the reads touched by `changed`.
+    fn notify_changed(&mut self, changed: &ChangedItems, nc: &mut NotifyContext) {
+        let level = DirtyLevel::Dirty;
+        for index in changed.indexes.range(..changed.shifted_from) {
+            if let Some(sinks) = self.indexes.get_mut(index) {
+                sinks.notify(level, nc);
+            }
+        }
+        for sinks in self
+            .indexes
+            .range_mut(changed.shifted_from..)
+            .map(|(_, s)| s)
+        {
+            sinks.notify(level, nc);
+        }
+        for id in &changed.handles {
+            if let Some(sinks) = self.handles.get_mut(id) {
+                sinks.notify(level, nc);
+            }
+        }
+        for id in &changed.positions {
+            if let Some(position) = self.positions.get_mut(id) {
+                position.sinks.notify(level, nc);
+            }
+        }
+    }
+
+    fn set_position(&mut self, id: usize, index: Option<usize>) {
+        if let Some(position) = self.positions.get_mut(&id) {
+            if let Some(old) = mem::replace(&mut position.index, index) {
+                self.position_ids.remove(&old);
+            }
+            if let Some(index) = index {
+                self.position_ids.insert(index, id);
+            }
+        }
+    }
+
+    fn notify_all(&mut self, nc: &mut NotifyContext) {
+        let level = DirtyLevel::MaybeDirty;
+        self.is_maybe_dirty = true;
+        for sinks in self.indexes.values_mut() {
+            sinks.notify(level, nc);
+        }
+        for sinks in self.handles.values_mut() {
+            sinks.notify(level, nc);
+        }
+        for position in self.positions.values_mut() {
+            position.sinks.notify(level, nc);
+        }
+    }
+}
+
+/// Indexes and item identifiers changed by a delta.
+struct ChangedItems {
+    shifted_from: usize,
+    indexes: BTreeSet<usize>,
+    handles: HashSet<usize>,
+    positions: HashSet<usize>,
+}
+
+impl ChangedItems {
+    fn new() -> Self {
+        Self {
+            shifted_from: usize::MAX,
+            indexes: BTreeSet::new(),
+            handles: HashSet::new(),
+            positions: HashSet::new(),
+        }
+    }
+
+    fn contains_index(&self, index: usize) -> bool {
+        index >= self.shifted_from || self.indexes.contains(&index)
+    }
+
+    fn mark(&mut self, index: usize) {
+        self.indexes.insert(index);
+    }
+
+    fn shift(&mut self, index: usize) {
+        self.shifted_from = self.shifted_from.min(index);
+    }
+
+    /// Marks every item as changed, for a delta that replaces all items.
+    fn reset(&mut self, old_ids: Vec<usize>, new_ids: &[usize]) {
+        self.shift(0);
+        self.handles.extend(old_ids);
+        self.handles.extend(new_ids);
+    }
+
+    fn apply<T>(&mut self, change: &ChangeData, ids: &mut Vec<usize>, model: &VecModel<T>) {
+        match change {
+            &ChangeData::Insert { index, new_value } => {
+                ids.insert(index, model.ids[new_value]);
+                self.shift(index);
+            }
+            &ChangeData::Remove { index, .. } => {
+                self.handles.insert(ids.remove(index));
+                self.shift(index);
+            }
+            &ChangeData::Set { index, .. } => {
+                self.handles.insert(ids[index]);
+                self.mark(index);
+            }
+            &ChangeData::Move {
+                old_index,
+                new_index,
+            } => {
+                let id = ids.remove(old_index);
+                ids.insert(new_index, id);
+                self.handles.insert(id);
+                for index in old_index.min(new_index)..=old_index.max(new_index) {
+                    self.mark(index);
+                }
+            }
+            &ChangeData::Swap { index: (i0, i1) } => {
+                self.handles.insert(ids[i0]);
+                self.handles.insert(ids[i1]);
+                ids.swap(i0, i1);
+                self.mark(i0);
+                self.mark(i1);
+            }
+            ChangeData::Reverse { .. } => {
+                self.handles.extend(ids.iter().copied());
+                self.shift(0);
+                ids.reverse();
+            }
+            &ChangeData::Rotate { mid, .. } => {
+                self.handles.extend(ids.iter().copied());
+                self.shift(0);
+                ids.rotate_left(mid);
+            }
+            ChangeData::Sort { new_to_old } => {
+                for (new_index, &old_index) in new_to_old.iter().enumerate() {
+                    if new_index != old_index {
+                        self.handles.insert(ids[old_index]);
+                        self.mark(new_index);
+                    }
+                }
+                IndexNewToOld::new(new_to_old).apply_to(ids);
+            }
+        }
+    }
+}
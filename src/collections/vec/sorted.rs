@@ -0,0 +1,114 @@
+use std::cmp::Ordering;
+
+use super::ItemsMut;
+
+/// Identifiers of sorted items, kept in the same order as the items of an [`ItemsMut`].
+///
+/// Items that compare equal are ordered by identifier, so each item has exactly one position
+/// that can be found by binary search.
+pub(crate) struct SortedIds(Vec<usize>);
+
+impl SortedIds {
+    pub fn new() -> Self {
+        Self(Vec::new())
+    }
+
+    fn cmp_at<T>(
+        &self,
+        items: &ItemsMut<T>,
+        index: usize,
+        value: &T,
+        id: usize,
+        cmp: &impl Fn(&T, &T) -> Ordering,
+    ) -> Ordering {
+        cmp(&items[index], value).then(self.0[index].cmp(&id))
+    }
+
+    fn position<T>(
+        &self,
+        items: &ItemsMut<T>,
+        value: &T,
+        id: usize,
+        cmp: &impl Fn(&T, &T) -> Ordering,
+    ) -> usize {
+        self.position_without(items, value, id, usize::MAX, cmp)
+    }
+
+    /// Returns the insertion position of `value` as if the item at `skip` were removed.
+    fn position_without<T>(
+        &self,
+        items: &ItemsMut<T>,
+        value: &T,
+        id: usize,
+        skip: usize,
+        cmp: &impl Fn(&T, &T) -> Ordering,
+    ) -> usize {
+        let mut start = 0;
+        let mut end = self.0.len() - usize::from(skip < self.0.len());
+        while start < end {
+            let mid = start + (end - start) / 2;
+            let index = if mid < skip { mid } else { mid + 1 };
+            if self.cmp_at(items, index, value, id, cmp).is_lt() {
+                start = mid + 1;
+            } else {
+                end = mid;
+            }
+        }
+        start
+    }
+
+    fn find<T>(
+        &self,
+        items: &ItemsMut<T>,
+        value: &T,
+        id: usize,
+        cmp: &impl Fn(&T, &T) -> Ordering,
+    ) -> usize {
+        let index = self.position(items, value, id, cmp);
+        assert_eq!(self.0.get(index), Some(&id), "inconsistent comparison");
+        index
+    }
+
+    pub fn insert<T: Clone>(
+        &mut self,
+        items: &mut ItemsMut<T>,
+        value: &T,
+        id: usize,
+        cmp: &impl Fn(&T, &T) -> Ordering,
+    ) {
+        let index = self.position(items, value, id, cmp);
+        items.insert(index, value.clone());
+        self.0.insert(index, id);
+    }
+
+    pub fn remove<T>(
+        &mut self,
+        items: &mut ItemsMut<T>,
+        value: &T,
+        id: usize,
+        cmp: &impl Fn(&T, &T) -> Ordering,
+    ) {
+        let index = self.find(items, value, id, cmp);
+        items.remove(index);
+        self.0.remove(index);
+    }
+
+    /// Replaces the value of an item and moves it if its sort position changed.
+    pub fn set<T: Clone>(
+        &mut self,
+        items: &mut ItemsMut<T>,
+        old_value: &T,
+        new_value: &T,
+        id: usize,
+        cmp: &impl Fn(&T, &T) -> Ordering,
+    ) {
+        let old_index = self.find(items, old_value, id, cmp);
+        items.set(old_index, new_value.clone());
+        let new_index = self.position_without(items, new_value, id, old_index, cmp);
+        if new_index != old_index {
+            items.move_item(old_index, new_index);
+            let id = self.0.remove(old_index);
+            self.0.insert(new_index, id);
+        }
+    }
+}
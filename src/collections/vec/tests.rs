@@ -1,7 +1,17 @@
-use std::{cell::Cell, rc::Rc};
+use std::{
+    cell::Cell,
+    collections::{BTreeMap, BTreeSet},
+    ops::Range,
+    rc::Rc,
+};
 
 use super::*;
-use crate::{State, core::Runtime, effect};
+use crate::{
+    State, Subscription,
+    core::{Runtime, spawn_action_async},
+    effect,
+};
+use futures::{FutureExt, StreamExt};
 use pretty_assertions::assert_eq;
 
 #[test]
@@ -263,18 +273,11 @@ fn signal_vec_from_scan_peek_retains_delta() {
         let items = reader.peek(&mut rt.sc());
         assert_eq!(
             items.delta().collect::<Vec<_>>(),
-            vec![
-                VecChange::Set {
-                    index: 0,
-                    old_value: &1,
-                    new_value: &2,
-                },
-                VecChange::Set {
-                    index: 0,
-                    old_value: &2,
-                    new_value: &3,
-                },
-            ]
+            vec![VecChange::Set {
+                index: 0,
+                old_value: &1,
+                new_value: &3,
+            }]
         );
         assert_eq!(items, [3]);
     }
@@ -283,18 +286,11 @@ fn signal_vec_from_scan_peek_retains_delta() {
         let items = reader.read(&mut rt.sc());
         assert_eq!(
             items.delta().collect::<Vec<_>>(),
-            vec![
-                VecChange::Set {
-                    index: 0,
-                    old_value: &1,
-                    new_value: &2,
-                },
-                VecChange::Set {
-                    index: 0,
-                    old_value: &2,
-                    new_value: &3,
-                },
-            ]
+            vec![VecChange::Set {
+                index: 0,
+                old_value: &1,
+                new_value: &3,
+            }]
         );
     }
 }
@@ -755,3 +751,1585 @@ fn state_vec_serialization_reports_borrow_conflict() {
 
     assert!(serde_json::to_string(&vec).is_err());
 }
+
+#[test]
+fn signal_vec_serde() {
+    let vec = SignalVec::from(vec![1, 2]);
+    assert_eq!(serde_json::to_string(&vec).unwrap(), "[1,2]");
+
+    let mut rt = Runtime::new();
+    let state: StateVec<_> = [1, 2].into_iter().collect();
+    let vec = state.to_signal_vec().sorted_by(|a, b| b.cmp(a));
+    let e = serde_json::to_string(&vec).unwrap_err();
+    assert!(e.to_string().contains("Runtime::lend"), "{e}");
+    {
+        let _lend = rt.lend();
+        assert_eq!(serde_json::to_string(&vec).unwrap(), "[2,1]");
+    }
+
+    let restored: SignalVec<i32> = serde_json::from_str("[3,4]").unwrap();
+    assert_eq!(restored.borrow(&mut rt.sc()), [3, 4]);
+}
+
+fn apply_delta<T: Clone>(mirror: &mut Vec<T>, items: &Items<T>) {
+    for change in items.delta() {
+        match change {
+            VecChange::Insert { index, new_value } => mirror.insert(index, new_value.clone()),
+            VecChange::Remove { index, .. } => {
+                mirror.remove(index);
+            }
+            VecChange::Set {
+                index, new_value, ..
+            } => mirror[index] = new_value.clone(),
+            VecChange::Move {
+                old_index,
+                new_index,
+            } => {
+                let value = mirror.remove(old_index);
+                mirror.insert(new_index, value);
+            }
+            VecChange::Swap { index: (i0, i1) } => mirror.swap(i0, i1),
+            VecChange::Reverse { .. } => mirror.reverse(),
+            VecChange::Rotate { mid, .. } => mirror.rotate_left(mid),
+            VecChange::Sort(new_to_old) => new_to_old.apply_to(mirror),
+        }
+    }
+}
+
+#[test]
+fn items_mut_coalesces_changes_within_an_edit() {
+    let mut rt = Runtime::new();
+    let vec = StateVec::new();
+    vec.borrow_mut(rt.ac()).extend([1, 2, 3]);
+    let mut reader = vec.reader();
+    let mut mirror = Vec::new();
+    apply_delta(&mut mirror, &reader.read(&mut rt.sc()));
+
+    {
+        let mut items = vec.borrow_mut(rt.ac());
+        items.set(0, 10);
+        items.set(0, 11);
+        items.set(0, 12);
+        items.push(4);
+        items.set(3, 5);
+        items.insert(1, 6);
+        items.remove(1);
+        items.move_item(1, 2);
+        items.move_item(2, 0);
+    }
+    let items = reader.read(&mut rt.sc());
+    assert_eq!(
+        items.delta().collect::<Vec<_>>(),
+        vec![
+            VecChange::Set {
+                index: 0,
+                old_value: &1,
+                new_value: &12,
+            },
+            VecChange::Insert {
+                index: 3,
+                new_value: &5,
+            },
+            VecChange::Move {
+                old_index: 1,
+                new_index: 0,
+            },
+        ]
+    );
+    apply_delta(&mut mirror, &items);
+    assert_eq!(mirror, vec![2, 12, 3, 5]);
+    assert_eq!(items.iter().copied().collect::<Vec<_>>(), mirror);
+}
+
+#[test]
+fn items_mut_coalesces_changes_across_unread_edits() {
+    let mut rt = Runtime::new();
+    let vec = StateVec::new();
+    vec.borrow_mut(rt.ac()).extend([1, 2, 3]);
+    let mut reader = vec.reader();
+    let mut mirror = Vec::new();
+    apply_delta(&mut mirror, &reader.read(&mut rt.sc()));
+
+    vec.borrow_mut(rt.ac()).set(0, 10);
+    vec.borrow_mut(rt.ac()).set(0, 11);
+    vec.borrow_mut(rt.ac()).push(4);
+    vec.borrow_mut(rt.ac()).remove(3);
+    vec.borrow_mut(rt.ac()).set(0, 12);
+    let items = reader.read(&mut rt.sc());
+    assert_eq!(
+        items.delta().collect::<Vec<_>>(),
+        vec![VecChange::Set {
+            index: 0,
+            old_value: &1,
+            new_value: &12,
+        }]
+    );
+    apply_delta(&mut mirror, &items);
+    assert_eq!(mirror, vec![12, 2, 3]);
+    drop(items);
+
+    vec.borrow_mut(rt.ac()).set(1, 20);
+    let mut late_reader = vec.reader();
+    let mut late_mirror = Vec::new();
+    apply_delta(&mut late_mirror, &late_reader.read(&mut rt.sc()));
+    vec.borrow_mut(rt.ac()).set(1, 21);
+    let items = reader.read(&mut rt.sc());
+    assert_eq!(
+        items.delta().collect::<Vec<_>>(),
+        vec![
+            VecChange::Set {
+                index: 1,
+                old_value: &2,
+                new_value: &20,
+            },
+            VecChange::Set {
+                index: 1,
+                old_value: &20,
+                new_value: &21,
+            },
+        ]
+    );
+    drop(items);
+    apply_delta(&mut late_mirror, &late_reader.read(&mut rt.sc()));
+    assert_eq!(late_mirror, vec![12, 21, 3]);
+}
+
+#[test]
+fn items_mut_edit_that_cancels_an_unread_edit_notifies() {
+    let mut rt = Runtime::new();
+    let vec = StateVec::new();
+    vec.borrow_mut(rt.ac()).extend([1, 2]);
+    let mut reader = vec.reader();
+    drop(reader.read(&mut rt.sc()));
+    let len = Signal::new({
+        let vec = vec.clone();
+        move |sc| vec.borrow(sc).len()
+    });
+    assert_eq!(len.get(&mut rt.sc()), 2);
+
+    vec.borrow_mut(rt.ac()).push(3);
+    assert_eq!(len.get(&mut rt.sc()), 3);
+    vec.borrow_mut(rt.ac()).remove(2);
+    assert_eq!(len.get(&mut rt.sc()), 2);
+    assert_eq!(reader.read(&mut rt.sc()).delta().count(), 0);
+}
+
+#[test]
+fn items_mut_edit_that_cancels_out_does_not_notify() {
+    let mut rt = Runtime::new();
+    let vec = StateVec::new();
+    vec.borrow_mut(rt.ac()).extend([1, 2]);
+    let count = Rc::new(Cell::new(0));
+    let len = Signal::new({
+        let vec = vec.clone();
+        let count = count.clone();
+        move |sc| {
+            count.set(count.get() + 1);
+            vec.borrow(sc).len()
+        }
+    });
+    assert_eq!(len.get(&mut rt.sc()), 2);
+
+    {
+        let mut items = vec.borrow_mut(rt.ac());
+        items.push(3);
+        items.remove(2);
+        items.swap(0, 1);
+        items.swap(1, 0);
+    }
+    assert_eq!(len.get(&mut rt.sc()), 2);
+    assert_eq!(count.get(), 1);
+}
+
+#[test]
+fn signal_vec_from_signal_diff_records_minimal_changes() {
+    let mut rt = Runtime::new();
+    let source = State::new(vec![1, 2, 3, 4]);
+    let vec = SignalVec::from_signal_diff(source.to_signal());
+    let mut reader = vec.reader();
+    drop(reader.read(&mut rt.sc()));
+
+    source.set(vec![1, 5, 3, 4, 6], rt.ac());
+    assert_eq!(
+        reader.read(&mut rt.sc()).delta().collect::<Vec<_>>(),
+        vec![
+            VecChange::Set {
+                index: 1,
+                new_value: &5,
+                old_value: &2,
+            },
+            VecChange::Insert {
+                index: 4,
+                new_value: &6,
+            },
+        ]
+    );
+
+    source.set(vec![3, 4, 6], rt.ac());
+    assert_eq!(
+        reader.read(&mut rt.sc()).delta().collect::<Vec<_>>(),
+        vec![
+            VecChange::Remove {
+                index: 1,
+                old_value: &5,
+            },
+            VecChange::Remove {
+                index: 0,
+                old_value: &1,
+            },
+        ]
+    );
+
+    source.set(vec![3, 4, 6], rt.ac());
+    assert_eq!(reader.read(&mut rt.sc()).delta().collect::<Vec<_>>(), []);
+}
+
+#[test]
+fn signal_vec_from_signal_diff_reproduces_snapshots() {
+    let mut rt = Runtime::new();
+    let source = State::new(vec![]);
+    let vec = SignalVec::from_signal_diff(source.to_signal());
+    let mut reader = vec.reader();
+    let mut mirror = Vec::new();
+
+    let snapshots = [
+        vec!['a', 'b', 'c', 'a', 'b', 'b', 'a'],
+        vec!['c', 'b', 'a', 'b', 'a', 'c'],
+        vec![],
+        vec!['x', 'y'],
+        vec!['y', 'x', 'y', 'z'],
+        vec!['z', 'a', 'y'],
+    ];
+    for snapshot in snapshots {
+        source.set(snapshot.clone(), rt.ac());
+        apply_delta(&mut mirror, &reader.read(&mut rt.sc()));
+        assert_eq!(mirror, snapshot);
+        assert_eq!(vec.borrow(&mut rt.sc()), snapshot);
+    }
+}
+
+#[test]
+fn signal_vec_from_signal_diff_by_key_records_moves() {
+    let mut rt = Runtime::new();
+    let source = State::new(vec![(1, 'a'), (2, 'b'), (3, 'c'), (4, 'd')]);
+    let vec = SignalVec::from_signal_diff_by_key(source.to_signal(), |&(id, _)| id);
+    let mut reader = vec.reader();
+    drop(reader.read(&mut rt.sc()));
+
+    source.set(vec![(2, 'b'), (3, 'c'), (4, 'd'), (1, 'a')], rt.ac());
+    assert_eq!(
+        reader.read(&mut rt.sc()).delta().collect::<Vec<_>>(),
+        vec![VecChange::Move {
+            old_index: 0,
+            new_index: 3,
+        }]
+    );
+
+    source.set(vec![(5, 'e'), (3, 'x'), (4, 'd'), (1, 'a')], rt.ac());
+    assert_eq!(
+        reader.read(&mut rt.sc()).delta().collect::<Vec<_>>(),
+        vec![
+            VecChange::Remove {
+                index: 0,
+                old_value: &(2, 'b'),
+            },
+            VecChange::Set {
+                index: 0,
+                new_value: &(3, 'x'),
+                old_value: &(3, 'c'),
+            },
+            VecChange::Insert {
+                index: 0,
+                new_value: &(5, 'e'),
+            },
+        ]
+    );
+}
+
+#[test]
+fn signal_vec_from_signal_diff_by_key_reproduces_snapshots() {
+    let mut rt = Runtime::new();
+    let source = State::new(vec![]);
+    let vec = SignalVec::from_signal_diff_by_key(source.to_signal(), |&(id, _)| id);
+    let mut reader = vec.reader();
+    let mut mirror = Vec::new();
+
+    let snapshots = [
+        vec![(1, 0), (2, 0), (3, 0), (4, 0), (5, 0)],
+        vec![(5, 0), (4, 0), (3, 1), (2, 0), (1, 0)],
+        vec![(6, 0), (3, 1), (1, 2), (7, 0), (5, 0)],
+        vec![(1, 2), (5, 0)],
+        vec![(8, 0), (5, 0), (9, 0), (1, 2), (6, 0)],
+        vec![],
+    ];
+    for snapshot in snapshots {
+        source.set(snapshot.clone(), rt.ac());
+        apply_delta(&mut mirror, &reader.read(&mut rt.sc()));
+        assert_eq!(mirror, snapshot);
+    }
+}
+
+#[test]
+fn signal_vec_from_signal_diff_by_key_reproduces_shuffles() {
+    let mut rt = Runtime::new();
+    let source = State::new(vec![]);
+    let vec = SignalVec::from_signal_diff_by_key(source.to_signal(), |&(id, _)| id);
+    let mut reader = vec.reader();
+    let mut mirror = Vec::new();
+
+    let mut seed = 1u32;
+    let mut next = move |n: u32| {
+        seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+        (seed >> 16) % n
+    };
+    for round in 0..200 {
+        let mut snapshot: Vec<_> = (0..12).filter(|_| next(4) != 0).map(|id| (id, 0)).collect();
+        for i in (1..snapshot.len()).rev() {
+            if next(3) != 0 {
+                snapshot.swap(i, next(i as u32 + 1) as usize);
+            }
+        }
+        for item in &mut snapshot {
+            item.1 = next(2) * round;
+        }
+        source.set(snapshot.clone(), rt.ac());
+        apply_delta(&mut mirror, &reader.read(&mut rt.sc()));
+        assert_eq!(mirror, snapshot);
+    }
+}
+
+#[test]
+fn signal_vec_sorted_by_records_minimal_changes() {
+    let mut rt = Runtime::new();
+    let vec = StateVec::new();
+    vec.borrow_mut(rt.ac()).extend([3, 1, 2]);
+    let sorted = vec.to_signal_vec().sorted_by(|a, b| a.cmp(b));
+    let mut reader = sorted.reader();
+    assert_eq!(
+        reader
+            .read(&mut rt.sc())
+            .iter()
+            .copied()
+            .collect::<Vec<_>>(),
+        vec![1, 2, 3]
+    );
+
+    vec.borrow_mut(rt.ac()).push(0);
+    assert_eq!(
+        reader.read(&mut rt.sc()).delta().collect::<Vec<_>>(),
+        vec![VecChange::Insert {
+            index: 0,
+            new_value: &0,
+        }]
+    );
+
+    vec.borrow_mut(rt.ac()).set(1, 5);
+    assert_eq!(
+        reader.read(&mut rt.sc()).delta().collect::<Vec<_>>(),
+        vec![
+            VecChange::Set {
+                index: 1,
+                new_value: &5,
+                old_value: &1,
+            },
+            VecChange::Move {
+                old_index: 1,
+                new_index: 3,
+            },
+        ]
+    );
+
+    vec.borrow_mut(rt.ac()).sort();
+    assert_eq!(reader.read(&mut rt.sc()).delta().count(), 0);
+
+    vec.borrow_mut(rt.ac()).remove(0);
+    assert_eq!(
+        reader.read(&mut rt.sc()).delta().collect::<Vec<_>>(),
+        vec![VecChange::Remove {
+            index: 0,
+            old_value: &0,
+        }]
+    );
+}
+
+#[test]
+fn signal_vec_sorted_by_key_keeps_equal_items_in_insertion_order() {
+    let mut rt = Runtime::new();
+    let vec = StateVec::new();
+    let sorted = vec.to_signal_vec().sorted_by_key(|&(key, _)| key);
+    let mut reader = sorted.reader();
+    let mut mirror = Vec::new();
+
+    vec.borrow_mut(rt.ac())
+        .extend([(2, 'a'), (1, 'b'), (2, 'c'), (1, 'd')]);
+    apply_delta(&mut mirror, &reader.read(&mut rt.sc()));
+    assert_eq!(mirror, vec![(1, 'b'), (1, 'd'), (2, 'a'), (2, 'c')]);
+
+    {
+        let mut items = vec.borrow_mut(rt.ac());
+        items.swap(0, 3);
+        items.move_item(1, 2);
+        items.set(0, (0, 'd'));
+        items.remove(3);
+    }
+    apply_delta(&mut mirror, &reader.read(&mut rt.sc()));
+    assert_eq!(mirror, vec![(0, 'd'), (1, 'b'), (2, 'c')]);
+}
+
+#[test]
+fn state_vec_history_limit_resets_lagging_adaptors() {
+    let mut rt = Runtime::new();
+    let vec = StateVec::new();
+    vec.set_history_limit(HistoryLimit::UNBOUNDED.with_max_changes(2));
+    vec.borrow_mut(rt.ac()).extend([3, 1, 2]);
+    let s = vec.to_signal_vec();
+    let sorted = s.sorted_by(|a, b| a.cmp(b));
+    let (even, _) = s.partition(|x| x % 2 == 0);
+    let groups = s.group_by(|x| x % 3);
+    let sum = s.sum();
+    let max = s.max();
+    let count = s.fold_incremental(0, |count, _| *count += 1, |count, _| *count -= 1);
+    let check = |rt: &mut Runtime| {
+        let sc = &mut rt.sc();
+        let items = vec.borrow(sc).iter().copied().collect::<Vec<_>>();
+        let mut sorted_items = items.clone();
+        sorted_items.sort();
+        let even_items = items.iter().copied().filter(|x| x % 2 == 0).collect();
+        let group_keys = items.iter().map(|x| x % 3).collect::<BTreeSet<_>>();
+        let actual = (
+            sorted.borrow(sc).iter().copied().collect::<Vec<_>>(),
+            even.borrow(sc).iter().copied().collect::<Vec<_>>(),
+            groups.borrow(sc).keys().copied().collect::<BTreeSet<_>>(),
+            sum.get(sc),
+            max.get(sc),
+            count.get(sc),
+        );
+        let expected = (
+            sorted_items,
+            even_items,
+            group_keys,
+            items.iter().sum(),
+            items.iter().max().copied(),
+            items.len(),
+        );
+        assert_eq!(actual, expected);
+    };
+    check(&mut rt);
+
+    vec.borrow_mut(rt.ac()).push(6);
+    check(&mut rt);
+
+    vec.borrow_mut(rt.ac()).remove(0);
+    vec.borrow_mut(rt.ac()).set(0, 4);
+    vec.borrow_mut(rt.ac()).push(5);
+    vec.borrow_mut(rt.ac()).retain(|x| x % 3 != 0);
+    check(&mut rt);
+}
+
+#[test]
+fn signal_vec_aggregates() {
+    let mut rt = Runtime::new();
+    let vec = StateVec::new();
+    let s = vec.to_signal_vec();
+    let len = s.len();
+    let sum = s.sum();
+    let even = s.count_where(|x| x % 2 == 0);
+    let any_negative = s.any(|&x| x < 0);
+    let all_positive = s.all(|&x| x > 0);
+    let min = s.min();
+    let max = s.max();
+    let check =
+        |rt: &mut Runtime, expected: (usize, i32, usize, bool, bool, Option<i32>, Option<i32>)| {
+            let sc = &mut rt.sc();
+            let actual = (
+                len.get(sc),
+                sum.get(sc),
+                even.get(sc),
+                any_negative.get(sc),
+                all_positive.get(sc),
+                min.get(sc),
+                max.get(sc),
+            );
+            assert_eq!(actual, expected);
+        };
+    check(&mut rt, (0, 0, 0, false, true, None, None));
+
+    vec.borrow_mut(rt.ac()).extend([3, 1, 4, 1, 5]);
+    check(&mut rt, (5, 14, 1, false, true, Some(1), Some(5)));
+
+    {
+        let mut items = vec.borrow_mut(rt.ac());
+        items.remove(4);
+        items.set(0, -2);
+        items.sort();
+    }
+    check(&mut rt, (4, 4, 2, true, false, Some(-2), Some(4)));
+
+    {
+        let mut items = vec.borrow_mut(rt.ac());
+        items.remove(0);
+        items.remove(0);
+    }
+    check(&mut rt, (2, 5, 1, false, true, Some(1), Some(4)));
+}
+
+#[test]
+fn signal_vec_aggregate_notifies_only_on_change() {
+    let mut rt = Runtime::new();
+    let vec = StateVec::new();
+    vec.borrow_mut(rt.ac()).extend([1, 2]);
+    let max = vec.to_signal_vec().max();
+    let count = Rc::new(Cell::new(0));
+    let _e = effect({
+        let count = count.clone();
+        move |sc| {
+            max.get(sc);
+            count.set(count.get() + 1);
+        }
+    });
+    rt.flush();
+    assert_eq!(count.get(), 1);
+
+    vec.borrow_mut(rt.ac()).push(0);
+    rt.flush();
+    assert_eq!(count.get(), 1);
+
+    vec.borrow_mut(rt.ac()).push(3);
+    rt.flush();
+    assert_eq!(count.get(), 2);
+}
+
+#[test]
+fn signal_vec_fold_incremental() {
+    let mut rt = Runtime::new();
+    let vec = StateVec::new();
+    let product = vec
+        .to_signal_vec()
+        .fold_incremental(1.0, |p, &x: &f64| *p *= x, |p, &x| *p /= x);
+
+    vec.borrow_mut(rt.ac()).extend([2.0, 4.0]);
+    assert_eq!(product.get(&mut rt.sc()), 8.0);
+
+    vec.borrow_mut(rt.ac()).set(0, 0.5);
+    assert_eq!(product.get(&mut rt.sc()), 2.0);
+}
+
+#[test]
+fn signal_vec_window_scroll_records_edges() {
+    let mut rt = Runtime::new();
+    let range = State::new(0..3);
+    let window = SignalVec::from((0..10).collect::<Vec<_>>()).window(range.to_signal());
+    let mut reader = window.reader();
+    assert_eq!(
+        reader
+            .read(&mut rt.sc())
+            .iter()
+            .copied()
+            .collect::<Vec<_>>(),
+        vec![0, 1, 2]
+    );
+
+    range.set(1..4, rt.ac());
+    assert_eq!(
+        reader.read(&mut rt.sc()).delta().collect::<Vec<_>>(),
+        vec![
+            VecChange::Remove {
+                index: 0,
+                old_value: &0,
+            },
+            VecChange::Insert {
+                index: 2,
+                new_value: &3,
+            },
+        ]
+    );
+
+    range.set(8..12, rt.ac());
+    assert_eq!(
+        reader
+            .read(&mut rt.sc())
+            .iter()
+            .copied()
+            .collect::<Vec<_>>(),
+        vec![8, 9]
+    );
+}
+
+#[test]
+fn signal_vec_window_translates_changes() {
+    let mut rt = Runtime::new();
+    let vec = StateVec::new();
+    vec.borrow_mut(rt.ac()).extend(0..10);
+    let range = State::new(3..6);
+    let window = vec.to_signal_vec().window(range.to_signal());
+    let mut reader = window.reader();
+    drop(reader.read(&mut rt.sc()));
+
+    vec.borrow_mut(rt.ac()).set(4, 40);
+    assert_eq!(
+        reader.read(&mut rt.sc()).delta().collect::<Vec<_>>(),
+        vec![VecChange::Set {
+            index: 1,
+            new_value: &40,
+            old_value: &4,
+        }]
+    );
+
+    vec.borrow_mut(rt.ac()).set(8, 80);
+    assert_eq!(reader.read(&mut rt.sc()).delta().count(), 0);
+
+    vec.borrow_mut(rt.ac()).remove(0);
+    assert_eq!(
+        reader.read(&mut rt.sc()).delta().collect::<Vec<_>>(),
+        vec![
+            VecChange::Remove {
+                index: 0,
+                old_value: &3,
+            },
+            VecChange::Insert {
+                index: 2,
+                new_value: &6,
+            },
+        ]
+    );
+}
+
+#[test]
+fn signal_vec_window_reproduces_slices() {
+    let mut rt = Runtime::new();
+    let vec = StateVec::new();
+    let range = State::new(2..5);
+    let window = vec.to_signal_vec().window(range.to_signal());
+    let mut reader = window.reader();
+    let mut mirror = Vec::new();
+    let mut source = Vec::new();
+
+    let check = |rt: &mut Runtime,
+                 reader: &mut SignalVecReader<i32>,
+                 mirror: &mut Vec<i32>,
+                 source: &[i32],
+                 range: Range<usize>| {
+        apply_delta(mirror, &reader.read(&mut rt.sc()));
+        let end = range.end.min(source.len());
+        let start = range.start.min(end);
+        assert_eq!(mirror, &source[start..end]);
+    };
+
+    vec.borrow_mut(rt.ac()).extend(0..8);
+    source.extend(0..8);
+    check(&mut rt, &mut reader, &mut mirror, &source, 2..5);
+
+    {
+        let mut items = vec.borrow_mut(rt.ac());
+        items.move_item(7, 3);
+        items.swap(0, 4);
+        items.insert(1, 100);
+        items.set(5, 50);
+    }
+    source.remove(7);
+    source.insert(3, 7);
+    source.swap(0, 4);
+    source.insert(1, 100);
+    source[5] = 50;
+    range.set(3..7, rt.ac());
+    check(&mut rt, &mut reader, &mut mirror, &source, 3..7);
+
+    vec.borrow_mut(rt.ac()).sort();
+    source.sort();
+    check(&mut rt, &mut reader, &mut mirror, &source, 3..7);
+
+    vec.borrow_mut(rt.ac()).drain(2..);
+    source.truncate(2);
+    check(&mut rt, &mut reader, &mut mirror, &source, 3..7);
+}
+
+#[test]
+fn signal_vec_window_follows_history_reset() {
+    let mut rt = Runtime::new();
+    let vec = StateVec::new();
+    vec.set_history_limit(HistoryLimit::UNBOUNDED.with_max_changes(2));
+    vec.borrow_mut(rt.ac()).extend(0..6);
+    let window = vec.to_signal_vec().window(Signal::from_value(1..4));
+    let mut reader = window.reader();
+    let mut mirror = Vec::new();
+    apply_delta(&mut mirror, &reader.read(&mut rt.sc()));
+    assert_eq!(mirror, vec![1, 2, 3]);
+
+    {
+        let mut items = vec.borrow_mut(rt.ac());
+        items.remove(0);
+        items.set(1, 20);
+        items.push(6);
+    }
+    apply_delta(&mut mirror, &reader.read(&mut rt.sc()));
+    assert_eq!(mirror, vec![20, 3, 4]);
+
+    vec.borrow_mut(rt.ac()).swap(1, 3);
+    apply_delta(&mut mirror, &reader.read(&mut rt.sc()));
+    assert_eq!(mirror, vec![4, 3, 20]);
+}
+
+#[test]
+fn signal_vec_partition() {
+    let mut rt = Runtime::new();
+    let vec = StateVec::new();
+    let (even, odd) = vec.to_signal_vec().partition(|x| x % 2 == 0);
+    let mut even_reader = even.reader();
+    let mut odd_reader = odd.reader();
+    let mut even_mirror = Vec::new();
+    let mut odd_mirror = Vec::new();
+    let mut source = Vec::new();
+
+    let mut check = |rt: &mut Runtime, source: &[i32]| {
+        apply_delta(&mut even_mirror, &even_reader.read(&mut rt.sc()));
+        apply_delta(&mut odd_mirror, &odd_reader.read(&mut rt.sc()));
+        let (even, odd): (Vec<i32>, Vec<i32>) = source.iter().partition(|&&x| x % 2 == 0);
+        assert_eq!(even_mirror, even);
+        assert_eq!(odd_mirror, odd);
+    };
+
+    vec.borrow_mut(rt.ac()).extend([5, 2, 8, 3, 4, 7]);
+    source.extend([5, 2, 8, 3, 4, 7]);
+    check(&mut rt, &source);
+
+    {
+        let mut items = vec.borrow_mut(rt.ac());
+        items.set(0, 6);
+        items.set(2, 9);
+        items.swap(1, 3);
+        items.swap(0, 5);
+        items.move_item(4, 0);
+        items.remove(2);
+    }
+    source[0] = 6;
+    source[2] = 9;
+    source.swap(1, 3);
+    source.swap(0, 5);
+    let value = source.remove(4);
+    source.insert(0, value);
+    source.remove(2);
+    check(&mut rt, &source);
+
+    vec.borrow_mut(rt.ac()).sort();
+    source.sort();
+    check(&mut rt, &source);
+}
+
+#[test]
+fn signal_vec_partition_set_within_group_records_set() {
+    let mut rt = Runtime::new();
+    let vec = StateVec::new();
+    vec.borrow_mut(rt.ac()).extend([1, 2, 3]);
+    let (odd, _) = vec.to_signal_vec().partition(|x| x % 2 == 1);
+    let mut reader = odd.reader();
+    drop(reader.read(&mut rt.sc()));
+
+    vec.borrow_mut(rt.ac()).set(2, 5);
+    assert_eq!(
+        reader.read(&mut rt.sc()).delta().collect::<Vec<_>>(),
+        vec![VecChange::Set {
+            index: 1,
+            new_value: &5,
+            old_value: &3,
+        }]
+    );
+}
+
+#[test]
+fn signal_vec_group_by() {
+    let mut rt = Runtime::new();
+    let vec = StateVec::new();
+    vec.borrow_mut(rt.ac())
+        .extend([("a", 1), ("b", 2), ("a", 3)]);
+    let groups = vec.to_signal_vec().group_by(|&(key, _)| key);
+    let values = |rt: &mut Runtime| {
+        let sc = &mut rt.sc();
+        groups
+            .get(sc)
+            .iter()
+            .map(|(key, group)| (*key, group.borrow(sc).iter().map(|x| x.1).collect()))
+            .collect::<Vec<(_, Vec<_>)>>()
+    };
+    assert_eq!(values(&mut rt), vec![("a", vec![1, 3]), ("b", vec![2])]);
+
+    let group_a = groups.get(&mut rt.sc())["a"].clone();
+    {
+        let mut items = vec.borrow_mut(rt.ac());
+        items.remove(1);
+        items.push(("c", 4));
+        items.push(("a", 5));
+    }
+    assert_eq!(values(&mut rt), vec![("a", vec![1, 3, 5]), ("c", vec![4])]);
+    assert_eq!(
+        group_a
+            .borrow(&mut rt.sc())
+            .iter()
+            .copied()
+            .collect::<Vec<_>>(),
+        vec![("a", 1), ("a", 3), ("a", 5)]
+    );
+
+    vec.borrow_mut(rt.ac()).set(3, ("c", 5));
+    assert_eq!(values(&mut rt), vec![("a", vec![1, 3]), ("c", vec![4, 5])]);
+}
+
+#[test]
+fn signal_vec_group_by_follows_random_edits() {
+    let mut rt = Runtime::new();
+    let vec = StateVec::new();
+    let s = vec.to_signal_vec();
+    let groups = s.group_by(|x| x % 5);
+    let (even, _) = s.partition(|x| x % 2 == 0);
+    let mut even_reader = even.reader();
+    let mut even_mirror = Vec::new();
+    let mut readers = BTreeMap::new();
+    let mut source = Vec::new();
+
+    let mut seed = 7u32;
+    let mut next = move |n: usize| {
+        seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+        (seed >> 16) as usize % n
+    };
+    for round in 0..300 {
+        {
+            let mut items = vec.borrow_mut(rt.ac());
+            for _ in 0..4 {
+                let len = source.len();
+                match next(if len == 0 { 1 } else { 6 }) {
+                    0 => {
+                        let index = next(len + 1);
+                        let value = next(1000) as i32;
+                        items.insert(index, value);
+                        source.insert(index, value);
+                    }
+                    1 if len > 200 => {
+                        let index = next(len);
+                        items.remove(index);
+                        source.remove(index);
+                    }
+                    1 | 2 => {
+                        let index = next(len);
+                        let value = next(1000) as i32;
+                        items.set(index, value);
+                        source[index] = value;
+                    }
+                    3 => {
+                        let (i0, i1) = (next(len), next(len));
+                        items.move_item(i0, i1);
+                        let value = source.remove(i0);
+                        source.insert(i1, value);
+                    }
+                    4 => {
+                        let (i0, i1) = (next(len), next(len));
+                        items.swap(i0, i1);
+                        source.swap(i0, i1);
+                    }
+                    _ => {
+                        if round % 50 == 0 {
+                            items.sort();
+                            source.sort();
+                        } else if round % 50 == 10 {
+                            items.reverse();
+                            source.reverse();
+                        } else if round % 50 == 20 {
+                            let mid = next(len + 1);
+                            items.rotate_left(mid);
+                            source.rotate_left(mid);
+                        } else {
+                            items.insert(len, round);
+                            source.push(round);
+                        }
+                    }
+                }
+            }
+        }
+        let sc = &mut rt.sc();
+        apply_delta(&mut even_mirror, &even_reader.read(sc));
+        let expected_even: Vec<_> = source.iter().copied().filter(|x| x % 2 == 0).collect();
+        assert_eq!(even_mirror, expected_even);
+
+        let groups = groups.get(sc);
+        readers.retain(|key, _| groups.contains_key(key));
+        for (key, group) in &groups {
+            let (reader, mirror) = readers
+                .entry(*key)
+                .or_insert_with(|| (group.reader(), Vec::new()));
+            apply_delta(mirror, &reader.read(sc));
+            let expected: Vec<_> = source.iter().copied().filter(|x| x % 5 == *key).collect();
+            assert_eq!(mirror, &expected);
+        }
+        let keys: BTreeSet<_> = source.iter().map(|x| x % 5).collect();
+        assert_eq!(groups.keys().copied().collect::<BTreeSet<_>>(), keys);
+    }
+}
+
+struct ItemReads {
+    count: Rc<Cell<usize>>,
+    value: Rc<Cell<Option<i32>>>,
+    _effect: Subscription,
+}
+
+impl ItemReads {
+    fn new(rt: &mut Runtime, read: impl Fn(&mut SignalContext) -> Option<i32> + 'static) -> Self {
+        let count = Rc::new(Cell::new(0));
+        let value = Rc::new(Cell::new(None));
+        let effect = effect({
+            let count = count.clone();
+            let value = value.clone();
+            move |sc| {
+                value.set(read(sc));
+                count.set(count.get() + 1);
+            }
+        });
+        rt.flush();
+        Self {
+            count,
+            value,
+            _effect: effect,
+        }
+    }
+
+    fn get(&self) -> (usize, Option<i32>) {
+        (self.count.get(), self.value.get())
+    }
+}
+
+#[test]
+fn state_vec_item_tracks_index() {
+    let mut rt = Runtime::new();
+    let vec = StateVec::new();
+    vec.borrow_mut(rt.ac()).extend([1, 2, 3]);
+    let reads = ItemReads::new(&mut rt, {
+        let vec = vec.clone();
+        move |sc| vec.item(1, sc).map(|x| *x)
+    });
+    assert_eq!(reads.get(), (1, Some(2)));
+
+    vec.borrow_mut(rt.ac()).set(2, 30);
+    rt.flush();
+    assert_eq!(reads.get(), (1, Some(2)));
+
+    vec.borrow_mut(rt.ac()).set(1, 20);
+    rt.flush();
+    assert_eq!(reads.get(), (2, Some(20)));
+
+    vec.borrow_mut(rt.ac()).push(4);
+    rt.flush();
+    assert_eq!(reads.get().0, 2);
+
+    vec.borrow_mut(rt.ac()).insert(0, 0);
+    rt.flush();
+    assert_eq!(reads.get(), (3, Some(1)));
+
+    vec.borrow_mut(rt.ac()).swap(2, 3);
+    rt.flush();
+    assert_eq!(reads.get(), (3, Some(1)));
+}
+
+#[test]
+fn state_vec_item_by_handle_tracks_identity() {
+    let mut rt = Runtime::new();
+    let vec = StateVec::new();
+    vec.borrow_mut(rt.ac()).extend([1, 2, 3]);
+    let handle = vec.borrow(&mut rt.sc()).handle(1).unwrap();
+    let reads = ItemReads::new(&mut rt, {
+        let vec = vec.clone();
+        move |sc| vec.item_by_handle(handle, sc).map(|x| *x)
+    });
+    assert_eq!(reads.get(), (1, Some(2)));
+
+    {
+        let mut items = vec.borrow_mut(rt.ac());
+        items.insert(0, 0);
+        items.set(3, 30);
+    }
+    rt.flush();
+    assert_eq!(reads.get(), (1, Some(2)));
+
+    vec.borrow_mut(rt.ac()).set(2, 20);
+    rt.flush();
+    assert_eq!(reads.get(), (2, Some(20)));
+
+    vec.borrow_mut(rt.ac()).sort_by_key(|&x| -x);
+    rt.flush();
+    assert_eq!(reads.get(), (3, Some(20)));
+    assert_eq!(vec.borrow(&mut rt.sc()).handle(1), Some(handle));
+
+    vec.borrow_mut(rt.ac()).remove(1);
+    rt.flush();
+    assert_eq!(reads.get(), (4, None));
+}
+
+#[test]
+fn signal_vec_from_scan_item_tracks_index() {
+    let mut rt = Runtime::new();
+    let source = State::new(vec![1, 2, 3]);
+    let vec = SignalVec::from_signal_diff(source.to_signal());
+    let reads = ItemReads::new(&mut rt, {
+        let vec = vec.clone();
+        move |sc| vec.item(0, sc).map(|x| *x)
+    });
+    assert_eq!(reads.get(), (1, Some(1)));
+
+    source.set(vec![1, 2, 30], rt.ac());
+    rt.flush();
+    assert_eq!(reads.get(), (1, Some(1)));
+
+    source.set(vec![10, 2, 30], rt.ac());
+    rt.flush();
+    assert_eq!(reads.get(), (2, Some(10)));
+}
+
+#[test]
+fn state_vec_handle_survives_reordering() {
+    let mut rt = Runtime::new();
+    let vec = StateVec::new();
+    let (h0, h1, h2) = {
+        let mut items = vec.borrow_mut(rt.ac());
+        let h2 = items.push_with_handle(3);
+        let h0 = items.insert_with_handle(0, 1);
+        let h1 = items.insert_with_handle(1, 2);
+        (h0, h1, h2)
+    };
+    let items = vec.borrow(&mut rt.sc());
+    assert_eq!(items.get_by_handle(h1), Some(&2));
+    assert_eq!(items.index_of(h2), Some(2));
+    drop(items);
+
+    let mut items = vec.borrow_mut(rt.ac());
+    items.move_item(0, 2);
+    assert_eq!(items.index_of(h0), Some(2));
+    items.swap(0, 1);
+    assert_eq!(items.index_of(h1), Some(1));
+    assert_eq!(items.index_of(h2), Some(0));
+    items.set(1, 20);
+    assert_eq!(items.get_by_handle(h1), Some(&20));
+    items.sort_by_key(|&x| -x);
+    assert_eq!(items.index_of(h1), Some(0));
+    assert_eq!(items.index_of(h2), Some(1));
+    assert_eq!(items.index_of(h0), Some(2));
+    items.drain(0..2);
+    assert_eq!(items.get_by_handle(h1), None);
+    assert_eq!(items.index_of(h2), None);
+    assert_eq!(items.index_of(h0), Some(0));
+    assert_eq!(items.get_by_handle(h0), Some(&1));
+}
+
+#[test]
+fn items_mut_index_of_follows_random_edits() {
+    let mut rt = Runtime::new();
+    let vec = StateVec::from_iter(0..50);
+    let mut seed = 5u32;
+    let mut next = move |n: usize| {
+        seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+        (seed >> 16) as usize % n
+    };
+    for round in 0..300 {
+        let mut items = vec.borrow_mut(rt.ac());
+        let len = items.len();
+        match next(7) {
+            0 => items.insert(next(len + 1), round),
+            1 if len > 10 => items.remove(next(len)),
+            1 | 2 => items.set(next(len), round),
+            3 => items.move_item(next(len), next(len)),
+            4 => items.swap(next(len), next(len)),
+            5 => {
+                let start = next(len);
+                items.splice(start..len.min(start + 2), [round; 3]);
+            }
+            _ => items.retain(|x| x % 7 != round % 7),
+        }
+        let handles: Vec<_> = (0..items.len()).map(|i| items.handle(i).unwrap()).collect();
+        for _ in 0..3 {
+            let index = next(handles.len());
+            assert_eq!(items.index_of(handles[index]), Some(index), "round {round}");
+        }
+    }
+}
+
+#[test]
+fn state_vec_index_of_tracks_position() {
+    let mut rt = Runtime::new();
+    let vec = StateVec::new();
+    vec.borrow_mut(rt.ac()).extend([1, 2, 3]);
+    let handle = vec.borrow(&mut rt.sc()).handle(1).unwrap();
+    let reads = ItemReads::new(&mut rt, {
+        let vec = vec.clone();
+        move |sc| vec.index_of(handle, sc).map(|x| x as i32)
+    });
+    assert_eq!(reads.get(), (1, Some(1)));
+
+    {
+        let mut items = vec.borrow_mut(rt.ac());
+        items.set(1, 20);
+        items.push(4);
+    }
+    rt.flush();
+    assert_eq!(reads.get(), (1, Some(1)));
+
+    vec.borrow_mut(rt.ac()).insert(0, 0);
+    rt.flush();
+    assert_eq!(reads.get(), (2, Some(2)));
+
+    {
+        let mut items = vec.borrow_mut(rt.ac());
+        items.swap(0, 1);
+        items.swap(0, 1);
+    }
+    rt.flush();
+    assert_eq!(reads.get(), (2, Some(2)));
+
+    vec.borrow_mut(rt.ac()).sort_by_key(|&x| -x);
+    rt.flush();
+    assert_eq!(reads.get(), (3, Some(0)));
+
+    vec.borrow_mut(rt.ac()).remove(0);
+    rt.flush();
+    assert_eq!(reads.get(), (4, None));
+}
+
+#[test]
+fn state_vec_item_reads_follow_random_edits() {
+    let mut rt = Runtime::new();
+    let vec = StateVec::from_iter(0..30);
+    let mut source: Vec<i32> = (0..30).collect();
+    let handles: Vec<_> = {
+        let items = vec.borrow(&mut rt.sc());
+        (0..30)
+            .step_by(6)
+            .map(|i| items.handle(i).unwrap())
+            .collect()
+    };
+    let index_reads: Vec<_> = (0..8)
+        .map(|index| {
+            let vec = vec.clone();
+            ItemReads::new(&mut rt, move |sc| vec.item(index, sc).map(|x| *x))
+        })
+        .collect();
+    let position_reads: Vec<_> = handles
+        .iter()
+        .map(|&handle| {
+            let vec = vec.clone();
+            ItemReads::new(&mut rt, move |sc| {
+                vec.index_of(handle, sc).map(|x| x as i32)
+            })
+        })
+        .collect();
+    let handle_reads: Vec<_> = handles
+        .iter()
+        .map(|&handle| {
+            let vec = vec.clone();
+            ItemReads::new(&mut rt, move |sc| {
+                vec.item_by_handle(handle, sc).map(|x| *x)
+            })
+        })
+        .collect();
+
+    let mut seed = 11u32;
+    let mut next = move |n: usize| {
+        seed = seed.wrapping_mul(1103515245).wrapping_add(12345);
+        (seed >> 16) as usize % n
+    };
+    for round in 0..200 {
+        {
+            let mut items = vec.borrow_mut(rt.ac());
+            for _ in 0..3 {
+                let len = source.len();
+                match next(if len == 0 { 1 } else { 6 }) {
+                    0 => {
+                        let index = next(len + 1);
+                        let value = 1000 + round;
+                        items.insert(index, value);
+                        source.insert(index, value);
+                    }
+                    1 if len > 10 => {
+                        let index = next(len);
+                        items.remove(index);
+                        source.remove(index);
+                    }
+                    1 | 2 => {
+                        let index = next(len);
+                        let value = source[index] + 100;
+                        items.set(index, value);
+                        source[index] = value;
+                    }
+                    3 => {
+                        let (i0, i1) = (next(len), next(len));
+                        items.move_item(i0, i1);
+                        let value = source.remove(i0);
+                        source.insert(i1, value);
+                    }
+                    4 => {
+                        let (i0, i1) = (next(len), next(len));
+                        items.swap(i0, i1);
+                        source.swap(i0, i1);
+                    }
+                    _ => match round % 3 {
+                        0 => {
+                            items.sort_by_key(|&x| -x);
+                            source.sort_by_key(|&x| -x);
+                        }
+                        1 => {
+                            items.reverse();
+                            source.reverse();
+                        }
+                        _ => {
+                            let mid = next(len + 1);
+                            items.rotate_left(mid);
+                            source.rotate_left(mid);
+                        }
+                    },
+                }
+            }
+        }
+        rt.flush();
+        let items = vec.borrow(&mut rt.sc());
+        for (index, reads) in index_reads.iter().enumerate() {
+            assert_eq!(reads.get().1, source.get(index).copied(), "round {round}");
+        }
+        for ((&handle, position), value) in handles.iter().zip(&position_reads).zip(&handle_reads) {
+            let index = items.index_of(handle);
+            assert_eq!(position.get().1, index.map(|x| x as i32), "round {round}");
+            assert_eq!(value.get().1, index.map(|i| source[i]), "round {round}");
+        }
+    }
+}
+
+#[test]
+fn state_vec_item_out_of_range_index() {
+    let mut rt = Runtime::new();
+    let vec = StateVec::from_iter([1, 2, 3]);
+    let reads = ItemReads::new(&mut rt, {
+        let vec = vec.clone();
+        move |sc| vec.item(usize::MAX, sc).map(|x| *x)
+    });
+    assert_eq!(reads.get(), (1, None));
+
+    vec.borrow_mut(rt.ac()).push(4);
+    rt.flush();
+    assert_eq!(reads.get(), (1, None));
+}
+
+#[test]
+fn items_mut_retain_records_removes() {
+    let mut rt = Runtime::new();
+    let vec = StateVec::from_iter([1, 2, 3, 4, 5]);
+    let mut reader = vec.reader();
+    drop(reader.read(&mut rt.sc()));
+
+    vec.borrow_mut(rt.ac()).retain(|&x| x % 2 == 1);
+    assert_eq!(
+        reader.read(&mut rt.sc()).delta().collect::<Vec<_>>(),
+        vec![
+            VecChange::Remove {
+                index: 1,
+                old_value: &2,
+            },
+            VecChange::Remove {
+                index: 2,
+                old_value: &4,
+            },
+        ]
+    );
+    assert_eq!(vec.borrow(&mut rt.sc()), [1, 3, 5]);
+}
+
+#[test]
+fn items_mut_splice_records_sets_first() {
+    let mut rt = Runtime::new();
+    let vec = StateVec::from_iter([1, 2, 3, 4]);
+    let handle = vec.borrow(&mut rt.sc()).handle(1).unwrap();
+    let mut reader = vec.reader();
+    drop(reader.read(&mut rt.sc()));
+
+    vec.borrow_mut(rt.ac()).splice(1..3, [20, 30, 35]);
+    assert_eq!(
+        reader.read(&mut rt.sc()).delta().collect::<Vec<_>>(),
+        vec![
+            VecChange::Set {
+                index: 1,
+                new_value: &20,
+                old_value: &2,
+            },
+            VecChange::Set {
+                index: 2,
+                new_value: &30,
+                old_value: &3,
+            },
+            VecChange::Insert {
+                index: 3,
+                new_value: &35,
+            },
+        ]
+    );
+    assert_eq!(vec.borrow(&mut rt.sc()), [1, 20, 30, 35, 4]);
+    assert_eq!(vec.borrow(&mut rt.sc()).index_of(handle), Some(1));
+
+    vec.borrow_mut(rt.ac()).splice(0..4, [10]);
+    assert_eq!(reader.read(&mut rt.sc()).delta().count(), 4);
+    assert_eq!(vec.borrow(&mut rt.sc()), [10, 4]);
+}
+
+#[test]
+fn items_mut_bulk_operations() {
+    let mut rt = Runtime::new();
+    let vec = StateVec::from_iter([1, 1, 2, 3, 3, 3, 4]);
+    let mut reader = vec.reader();
+    let mut mirror = Vec::new();
+    apply_delta(&mut mirror, &reader.read(&mut rt.sc()));
+    let mut expected = mirror.clone();
+
+    let mut check = |rt: &mut Runtime,
+                     f: &dyn Fn(&mut ItemsMut<i32>),
+                     g: &dyn Fn(&mut Vec<i32>),
+                     max_changes: usize| {
+        f(&mut vec.borrow_mut(rt.ac()));
+        g(&mut expected);
+        let items = reader.read(&mut rt.sc());
+        assert!(items.delta().count() <= max_changes);
+        apply_delta(&mut mirror, &items);
+        assert_eq!(mirror, expected);
+        assert_eq!(items, expected);
+    };
+    check(&mut rt, &|v| v.dedup(), &|v| v.dedup(), 3);
+    check(&mut rt, &|v| v.reverse(), &|v| v.reverse(), 1);
+    check(&mut rt, &|v| v.rotate_left(1), &|v| v.rotate_left(1), 1);
+    check(&mut rt, &|v| v.rotate_right(1), &|v| v.rotate_right(1), 1);
+    check(&mut rt, &|v| v.rotate_left(2), &|v| v.rotate_left(2), 1);
+    check(
+        &mut rt,
+        &|v| v.extend_from_slice(&[5, 6]),
+        &|v| v.extend_from_slice(&[5, 6]),
+        2,
+    );
+    check(&mut rt, &|v| v.truncate(3), &|v| v.truncate(3), 3);
+    check(
+        &mut rt,
+        &|v| v.resize_with(5, || 0),
+        &|v| v.resize_with(5, || 0),
+        2,
+    );
+    check(
+        &mut rt,
+        &|v| v.dedup_by_key(|x| *x / 2),
+        &|v| v.dedup_by_key(|x| *x / 2),
+        4,
+    );
+}
+
+#[test]
+fn signal_vec_reader_into_delta_stream() {
+    let mut rt = Runtime::new();
+    let vec = StateVec::from_iter([1, 2]);
+    let mut stream = vec.reader().into_delta_stream();
+    assert_eq!(stream.next().now_or_never(), None);
+
+    rt.flush();
+    assert_eq!(
+        stream.next().now_or_never(),
+        Some(Some(vec![OwnedVecChange::Initial(vec![1, 2])]))
+    );
+    assert_eq!(stream.next().now_or_never(), None);
+
+    vec.borrow_mut(rt.ac()).push(3);
+    rt.flush();
+    vec.borrow_mut(rt.ac()).swap(0, 2);
+    rt.flush();
+    assert_eq!(
+        stream.next().now_or_never(),
+        Some(Some(vec![
+            OwnedVecChange::Insert {
+                index: 2,
+                new_value: 3,
+            },
+            OwnedVecChange::Swap { index: (0, 2) },
+        ]))
+    );
+
+    rt.flush();
+    assert_eq!(stream.next().now_or_never(), None);
+}
+
+#[test]
+fn signal_vec_from_vec_into_delta_stream() {
+    let mut rt = Runtime::new();
+    let mut stream = SignalVec::from(vec![1, 2]).reader().into_delta_stream();
+    assert_eq!(stream.next().now_or_never(), None);
+
+    rt.flush();
+    assert_eq!(
+        stream.next().now_or_never(),
+        Some(Some(vec![OwnedVecChange::Initial(vec![1, 2])]))
+    );
+    rt.flush();
+    assert_eq!(stream.next().now_or_never(), None);
+}
+
+#[test]
+fn items_mut_reverse_and_rotate_record_single_changes() {
+    let mut rt = Runtime::new();
+    let vec = StateVec::from_iter([1, 2, 3, 4, 5]);
+    let s = vec.to_signal_vec();
+    let derived = [
+        s.filter(|x| x % 2 == 1),
+        s.sorted_by_key(|&x| x),
+        s.window(Signal::from_value(1..4)),
+    ];
+    let mut reader = vec.reader();
+    let mut readers: Vec<_> = derived.iter().map(|s| (s.reader(), Vec::new())).collect();
+    reader.read(&mut rt.sc());
+    let mut check = |rt: &mut Runtime, expected: Vec<VecChange<i32>>| {
+        let sc = &mut rt.sc();
+        assert_eq!(reader.read(sc).delta().collect::<Vec<_>>(), expected);
+        let source: Vec<_> = vec.borrow(sc).iter().copied().collect();
+        let expected = [
+            source.iter().copied().filter(|x| x % 2 == 1).collect(),
+            {
+                let mut sorted = source.clone();
+                sorted.sort();
+                sorted
+            },
+            source[1..4].to_vec(),
+        ];
+        for ((reader, mirror), expected) in readers.iter_mut().zip(expected) {
+            apply_delta(mirror, &reader.read(sc));
+            assert_eq!(*mirror, expected);
+        }
+    };
+    check(&mut rt, vec![]);
+
+    vec.borrow_mut(rt.ac()).reverse();
+    check(&mut rt, vec![VecChange::Reverse { len: 5 }]);
+
+    vec.borrow_mut(rt.ac()).rotate_left(2);
+    check(&mut rt, vec![VecChange::Rotate { len: 5, mid: 2 }]);
+
+    vec.borrow_mut(rt.ac()).rotate_right(2);
+    check(&mut rt, vec![VecChange::Rotate { len: 5, mid: 3 }]);
+}
+
+#[test]
+fn items_mut_apply_changes_mirrors_source() {
+    let mut rt = Runtime::new();
+    let source = StateVec::from_iter([1, 2, 3]);
+    let mirror = StateVec::new();
+    let mut reader = source.reader();
+    let mut apply = |rt: &mut Runtime| {
+        let changes: Vec<_> = reader
+            .read(&mut rt.sc())
+            .delta()
+            .map(OwnedVecChange::from)
+            .collect();
+        mirror.borrow_mut(rt.ac()).apply_changes(changes).unwrap();
+        let expected: Vec<_> = source.borrow(&mut rt.sc()).iter().copied().collect();
+        assert_eq!(mirror.borrow(&mut rt.sc()), expected);
+    };
+    apply(&mut rt);
+
+    {
+        let mut items = source.borrow_mut(rt.ac());
+        items.insert(1, 4);
+        items.set(0, 10);
+        items.move_item(0, 3);
+        items.swap(0, 2);
+        items.sort();
+        items.remove(1);
+    }
+    apply(&mut rt);
+
+    {
+        let mut items = source.borrow_mut(rt.ac());
+        items.push(5);
+        items.reverse();
+        items.rotate_left(2);
+    }
+    apply(&mut rt);
+}
+
+#[test]
+fn items_mut_apply_change_rejects_bad_indices() {
+    let mut rt = Runtime::new();
+    let vec = StateVec::from_iter([1, 2]);
+    let mut items = vec.borrow_mut(rt.ac());
+    assert_eq!(
+        items.apply_change(OwnedVecChange::Remove {
+            index: 2,
+            old_value: 0,
+        }),
+        Err(ApplyVecChangeError::IndexOutOfBounds { index: 2, len: 2 })
+    );
+    assert_eq!(
+        items.apply_change(OwnedVecChange::Insert {
+            index: 3,
+            new_value: 0,
+        }),
+        Err(ApplyVecChangeError::IndexOutOfBounds { index: 3, len: 3 })
+    );
+    assert_eq!(
+        items.apply_change(OwnedVecChange::Swap { index: (0, 5) }),
+        Err(ApplyVecChangeError::IndexOutOfBounds { index: 5, len: 2 })
+    );
+    assert_eq!(
+        items.apply_change(OwnedVecChange::Sort(vec![0, 0])),
+        Err(ApplyVecChangeError::InvalidPermutation { len: 2 })
+    );
+    assert_eq!(
+        items.apply_changes([
+            OwnedVecChange::Insert {
+                index: 2,
+                new_value: 3,
+            },
+            OwnedVecChange::Set {
+                index: 3,
+                new_value: 0,
+                old_value: 0,
+            },
+        ]),
+        Err(ApplyVecChangeError::IndexOutOfBounds { index: 3, len: 3 })
+    );
+    assert_eq!(items, [1, 2, 3]);
+}
+
+#[test]
+fn state_vec_apply_stream() {
+    let mut rt = Runtime::new();
+    let source = StateVec::from_iter([1, 2]);
+    let mirror = StateVec::new();
+    let result = Rc::new(Cell::new(None));
+    let mut stream = source.reader().into_delta_stream();
+    let (sender, receiver) = futures::channel::mpsc::unbounded();
+    spawn_action_async({
+        let mirror = mirror.clone();
+        let result = result.clone();
+        async move |ac| result.set(Some(mirror.apply_stream(receiver, ac).await))
+    });
+    let mut forward = |rt: &mut Runtime| {
+        rt.flush();
+        while let Some(Some(batch)) = stream.next().now_or_never() {
+            sender.unbounded_send(batch).unwrap();
+        }
+        rt.flush();
+    };
+    forward(&mut rt);
+    forward(&mut rt);
+    assert_eq!(mirror.borrow(&mut rt.sc()), [1, 2]);
+
+    source.borrow_mut(rt.ac()).splice(0..1, [10, 11]);
+    forward(&mut rt);
+    assert_eq!(mirror.borrow(&mut rt.sc()), [10, 11, 2]);
+
+    sender
+        .unbounded_send(vec![OwnedVecChange::Remove {
+            index: 3,
+            old_value: 0,
+        }])
+        .unwrap();
+    rt.flush();
+    assert_eq!(
+        result.get(),
+        Some(Err(ApplyVecChangeError::IndexOutOfBounds {
+            index: 3,
+            len: 3
+        }))
+    );
+}
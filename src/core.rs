@@ -5,6 +5,7 @@ use std::{
     future::{Future, poll_fn},
     mem::{replace, swap, take, transmute},
     ops::AsyncFnOnce,
+    panic::{AssertUnwindSafe, catch_unwind, resume_unwind},
     pin::Pin,
     rc::{Rc, Weak},
     result::Result,
@@ -18,17 +19,23 @@ use derive_ex::Ex;
 use parse_display::Display;
 use slabmap::SlabMap;
 
+mod action_handle;
+mod action_queue;
 mod async_signal_context;
 mod context_channel;
 mod dirty;
+mod fork;
 mod raw_context;
 mod source_binder;
 mod state_ref;
 mod state_ref_builder;
 
+pub use action_handle::{ActionHandle, ActionStatus};
+pub use action_queue::ActionQueue;
 pub use async_signal_context::*;
 pub use context_channel::*;
 pub use dirty::*;
+pub(crate) use fork::{Fork, ForkSignal, ForkWrite};
 pub use source_binder::SourceBinder;
 pub use state_ref::StateRef;
 pub use state_ref_builder::StateRefBuilder;
@@ -133,7 +140,6 @@ impl Globals {
             g.wake();
         })
     }
-
     fn schedule_action(phase: ActionPhase, action: Action) {
         Self::with(|g| {
             Self::push_action(g.runtime_config.as_ref(), &mut g.actions, phase, action);
@@ -375,6 +381,7 @@ impl Runtime {
             actions_buffer: Vec::new(),
             reactions_buffer: Vec::new(),
             unbinds_buffer: Vec::new(),
+            batch: None,
         });
         Globals::activate_runtime(config);
         Self {
@@ -542,6 +549,7 @@ struct RawRuntime {
     actions_buffer: Vec<Action>,
     reactions_buffer: Vec<Reaction>,
     unbinds_buffer: Vec<SourceBindingsData>,
+    batch: Option<Vec<(NotifyReaction, DirtyLevel)>>,
 }
 impl RawRuntime {
     pub fn ac(&mut self) -> &mut ActionContext {
@@ -616,7 +624,14 @@ impl RawRuntime {
     fn apply_notify(&mut self) -> bool {
         let mut handled = self.apply_unbind();
         let mut notifys = take(&mut self.notifys_buffer);
-        while Globals::get_notifys(&mut notifys) {
+        loop {
+            if self.apply_batch() {
+                handled = true;
+                continue;
+            }
+            if !Globals::get_notifys(&mut notifys) {
+                break;
+            }
             for notify in notifys.drain(..) {
                 notify.call_notify(self.nc());
                 handled = true;
@@ -625,6 +640,19 @@ impl RawRuntime {
         self.notifys_buffer = notifys;
         handled
     }
+    fn apply_batch(&mut self) -> bool {
+        let mut handled = false;
+        loop {
+            let notifys = match &mut self.batch {
+                Some(notifys) if !notifys.is_empty() => take(notifys),
+                _ => return handled,
+            };
+            for (notify, level) in notifys {
+                notify.call_notify_with(level, self.nc());
+                handled = true;
+            }
+        }
+    }
 
     fn dispatch_discards(&mut self) -> bool {
         let mut handled = false;
@@ -648,6 +676,10 @@ impl RawRuntime {
             if self.dispatch_actions_with(None) {
                 continue;
             }
+            // Notifications may schedule actions.
+            if self.apply_notify() {
+                continue;
+            }
             if self.dispatch_reactions_with(None) {
                 continue;
             }
@@ -680,6 +712,9 @@ impl Drop for RawRuntime {
 struct RuntimeData {
     discards: Vec<Reaction>,
     async_actions: SlabMap<Rc<AsyncAction>>,
+    boundaries: Vec<Rc<dyn PendingBoundary>>,
+    fork: Rc<Fork>,
+    is_fork: bool,
 }
 
 impl RuntimeData {
@@ -687,6 +722,9 @@ impl RuntimeData {
         Self {
             discards: Vec::new(),
             async_actions: SlabMap::new(),
+            boundaries: Vec::new(),
+            fork: Rc::new(Fork::default()),
+            is_fork: false,
         }
     }
 }
@@ -802,6 +840,7 @@ impl SourceBindings {
             slot,
             sources: take(self),
             sources_len,
+            is_rearm: false,
         };
         let mut sc = SignalContext {
             rt: rc.0.rt,
@@ -809,7 +848,7 @@ impl SourceBindings {
             sink: Some(&mut sink),
         };
 
-        let ret = f(&mut sc);
+        let ret = sc.with_fork(false, f);
         *self = sink.sources;
         for b in self.0.drain(sink.sources_len..) {
             b.unbind(rc);
@@ -830,6 +869,36 @@ impl SourceBindings {
             b.unbind(rc)
         }
     }
+
+    /// Keeps the dependencies and discards the changes made to them so far.
+    ///
+    /// After this call, the sink is notified again when one of the sources changes,
+    /// as if the sources had been read again.
+    pub fn rearm(
+        &mut self,
+        sink: Weak<dyn BindSink>,
+        slot: Slot,
+        rc: &mut ReactionContext<'_, '_>,
+    ) {
+        for source in &self.0 {
+            source.check(rc);
+        }
+        let mut sources = take(self);
+        let mut sink = Sink {
+            sink,
+            slot,
+            sources: SourceBindings::new(),
+            sources_len: 0,
+            is_rearm: true,
+        };
+        let mut sc = SignalContext {
+            rt: rc.0.rt,
+            bump: rc.0.bump,
+            sink: Some(&mut sink),
+        };
+        sc.extend(&mut sources);
+        *self = sink.sources;
+    }
 }
 impl Drop for SourceBindings {
     fn drop(&mut self) {
@@ -847,6 +916,14 @@ struct SinkBinding {
 
 impl SinkBinding {
     fn notify(&self, level: DirtyLevel, nc: &mut NotifyContext) {
+        if let Some(batch) = &mut nc.0.0.batch {
+            let notify = NotifyReaction {
+                sink: self.sink.clone(),
+                slot: self.slot,
+            };
+            batch.push((notify, level));
+            return;
+        }
         if let Some(node) = self.sink.upgrade() {
             node.notify(self.slot, level, nc)
         }
@@ -898,7 +975,11 @@ impl SinkBindings {
         sc: &mut SignalContext<'_, '_>,
     ) {
         if let Some(sink) = &mut sc.sink {
-            self.0[key.0].slot = sink.slot;
+            let binding = &mut self.0[key.0];
+            binding.slot = sink.slot;
+            if sink.is_rearm {
+                binding.dirty = Dirty::Clean;
+            }
             if let Some(old) = sink.push(SourceBinding {
                 source: this,
                 slot: this_slot,
@@ -935,6 +1016,14 @@ impl SinkBindings {
             binding.dirty.apply_notify(level);
         }
     }
+    /// Notifies all sinks that this source has changed in the fork of a transition.
+    pub(crate) fn notify_fork(&self, nc: &mut NotifyContext) {
+        for binding in self.0.values() {
+            if let Some(node) = binding.sink.upgrade() {
+                node.notify_fork(nc)
+            }
+        }
+    }
     pub fn update(&mut self, is_dirty: bool, _uc: &mut ReactionContext<'_, '_>) {
         self.0.optimize();
         for binding in self.0.values_mut() {
@@ -950,6 +1039,8 @@ struct Sink {
     slot: Slot,
     sources: SourceBindings,
     sources_len: usize,
+    // If `true`, rebound dependencies are marked as clean so that the sink is notified again.
+    is_rearm: bool,
 }
 impl Sink {
     #[must_use]
@@ -1003,6 +1094,9 @@ impl NotifyContext {
     fn new(ac: &mut ActionContext) -> &mut Self {
         unsafe { transmute(ac) }
     }
+    pub(crate) fn fork(&self) -> &Rc<Fork> {
+        self.0.fork()
+    }
 }
 
 /// Schedules state invalidation notifications.
@@ -1041,6 +1135,57 @@ impl<'r, 's> SignalContext<'r, 's> {
         }
         .sc)
     }
+
+    /// Call a function with `boundary` as the innermost [`PendingBoundary`].
+    pub(crate) fn with_boundary<T>(
+        &mut self,
+        boundary: Rc<dyn PendingBoundary>,
+        f: impl FnOnce(&mut SignalContext<'r, 's>) -> T,
+    ) -> T {
+        struct BoundaryGuard<'r, 's, 'a> {
+            sc: &'a mut SignalContext<'r, 's>,
+        }
+        impl Drop for BoundaryGuard<'_, '_, '_> {
+            fn drop(&mut self) {
+                self.sc.rt.boundaries.pop();
+            }
+        }
+        self.rt.boundaries.push(boundary);
+        f(BoundaryGuard { sc: self }.sc)
+    }
+    pub(crate) fn boundary(&self) -> Option<&Rc<dyn PendingBoundary>> {
+        self.rt.boundaries.last()
+    }
+    pub(crate) fn fork(&self) -> &Rc<Fork> {
+        &self.rt.fork
+    }
+
+    /// Returns whether signals are read in the transition's fork.
+    ///
+    /// In the fork, states return the values written by the transition.
+    pub(crate) fn is_fork(&self) -> bool {
+        self.rt.is_fork
+    }
+
+    /// Call a function that reads signals in the transition's fork if `is_fork` is `true`, or
+    /// outside it otherwise.
+    pub(crate) fn with_fork<T>(
+        &mut self,
+        is_fork: bool,
+        f: impl FnOnce(&mut SignalContext<'r, 's>) -> T,
+    ) -> T {
+        struct ForkGuard<'r, 's, 'a> {
+            sc: &'a mut SignalContext<'r, 's>,
+            is_fork: bool,
+        }
+        impl Drop for ForkGuard<'_, '_, '_> {
+            fn drop(&mut self) {
+                self.sc.rt.is_fork = self.is_fork;
+            }
+        }
+        let is_fork = replace(&mut self.rt.is_fork, is_fork);
+        f(ForkGuard { sc: self, is_fork }.sc)
+    }
     fn extend(&mut self, from: &mut SourceBindings) {
         for binding in from.0.drain(..) {
             binding.rebind(self);
@@ -1051,6 +1196,11 @@ impl<'r, 's> SignalContext<'r, 's> {
 /// A trait for types that can be notified of state changes.
 pub trait BindSink: 'static {
     fn notify(self: Rc<Self>, slot: Slot, level: DirtyLevel, nc: &mut NotifyContext);
+
+    /// Notifies that a source has changed in the fork of a transition.
+    ///
+    /// Sinks that are not evaluated in the fork ignore this notification.
+    fn notify_fork(self: Rc<Self>, _nc: &mut NotifyContext) {}
 }
 
 /// A trait for types that can hold a state and be monitored for changes.
@@ -1070,8 +1220,11 @@ struct NotifyReaction {
 }
 impl NotifyReaction {
     fn call_notify(&self, nc: &mut NotifyContext) {
+        self.call_notify_with(DirtyLevel::Dirty, nc)
+    }
+    fn call_notify_with(&self, level: DirtyLevel, nc: &mut NotifyContext) {
         if let Some(sink) = self.sink.upgrade() {
-            sink.notify(self.slot, DirtyLevel::Dirty, nc)
+            sink.notify(self.slot, level, nc)
         }
     }
 }
@@ -1093,6 +1246,33 @@ impl ActionContext {
     pub fn sc(&mut self) -> SignalContext<'_, '_> {
         self.0.sc()
     }
+    pub(crate) fn fork(&self) -> &Rc<Fork> {
+        &self.0.rt.fork
+    }
+
+    /// Calls `f` and holds back the notifications of the state changes it makes until it returns.
+    ///
+    /// Dependants of the states changed by `f` are notified together after `f` returns.
+    /// Obtaining a [`SignalContext`] or [`ReactionContext`] from the context passed to `f`
+    /// delivers the notifications held so far, so signals read inside `f` reflect the changes
+    /// made so far. A batch within a batch is part of the outer one.
+    ///
+    /// If `f` panics, the held notifications are delivered while unwinding.
+    pub fn batch<T>(&mut self, f: impl FnOnce(&mut ActionContext) -> T) -> T {
+        if self.0.batch.is_some() {
+            return f(self);
+        }
+        struct BatchGuard<'a>(&'a mut ActionContext);
+        impl Drop for BatchGuard<'_> {
+            fn drop(&mut self) {
+                self.0.0.apply_batch();
+                self.0.0.batch = None;
+            }
+        }
+        self.0.batch = Some(Vec::new());
+        let guard = BatchGuard(self);
+        f(guard.0)
+    }
 }
 
 /// Spawns a new action.
@@ -1133,11 +1313,45 @@ pub fn spawn_action_async_in(
     phase: ActionPhase,
     f: impl AsyncFnOnce(&mut AsyncActionContext) + 'static,
 ) {
+    spawn_action_async_with_handle_in(phase, f).detach()
+}
+
+/// Spawns a new asynchronous action and returns its [`ActionHandle`].
+///
+/// The handle can be used to await the output, cancel the action and observe its status.
+///
+/// # Panics
+///
+/// Panics if a [`Runtime`] exists and the default [`ActionPhase`] is not valid according to its
+/// [`RuntimeConfig`].
+pub fn spawn_action_async_with_handle<T: 'static>(
+    f: impl AsyncFnOnce(&mut AsyncActionContext) -> T + 'static,
+) -> ActionHandle<T> {
+    spawn_action_async_with_handle_in(ActionPhase::default(), f)
+}
+
+/// Spawns a new asynchronous action with a specific phase and returns its [`ActionHandle`].
+///
+/// # Panics
+///
+/// Panics if a [`Runtime`] exists and `phase` is not valid according to its [`RuntimeConfig`].
+pub fn spawn_action_async_with_handle_in<T: 'static>(
+    phase: ActionPhase,
+    f: impl AsyncFnOnce(&mut AsyncActionContext) -> T + 'static,
+) -> ActionHandle<T> {
+    let handle = ActionHandle::new(phase);
+    let data = handle.data().clone();
     spawn_action_in(phase, move |ac| {
-        AsyncAction::start(phase, ac, |mut ac| async move {
-            f(&mut ac).await;
-        })
-    })
+        if !data.is_running() {
+            return;
+        }
+        let action = AsyncAction::start(phase, ac, data.clone(), {
+            let data = data.clone();
+            |mut ac| async move { data.set_value(f(&mut ac).await) }
+        });
+        data.set_action(&action);
+    });
+    handle
 }
 
 /// Operations that modify state.
@@ -1225,13 +1439,16 @@ struct AsyncAction {
     phase: ActionPhase,
     aac_source: AsyncActionContextSource,
     data: RefCell<Option<AsyncActionData>>,
+    handle: Rc<dyn action_handle::FinishAction>,
 }
 impl AsyncAction {
     fn start<Fut>(
         phase: ActionPhase,
         ac: &mut ActionContext,
+        handle: Rc<dyn action_handle::FinishAction>,
         f: impl FnOnce(AsyncActionContext) -> Fut + 'static,
-    ) where
+    ) -> Rc<Self>
+    where
         Fut: Future<Output = ()> + 'static,
     {
         let aac_source = AsyncActionContextSource::new();
@@ -1241,6 +1458,7 @@ impl AsyncAction {
             phase,
             aac_source,
             data: RefCell::new(None),
+            handle,
         });
         let id = ac.0.rt.async_actions.insert(action.clone());
         *action.data.borrow_mut() = Some(AsyncActionData {
@@ -1248,32 +1466,42 @@ impl AsyncAction {
             waker: WakeReaction::AsyncAction(action.clone()).into_waker(),
             future: Box::pin(future),
         });
-        action.next(ac);
+        action.clone().next(ac);
+        action
     }
     fn call(
         self: &Rc<Self>,
         ac: &mut ActionContext,
-        f: impl FnOnce(&mut Option<AsyncActionData>) -> Option<usize>,
+        f: impl FnOnce(&mut Option<AsyncActionData>) -> Option<(usize, ActionStatus)>,
     ) {
-        let id_remove = self.aac_source.call(ac, || f(&mut self.data.borrow_mut()));
-        if let Some(id_remove) = id_remove {
+        let finished = self.aac_source.call(ac, || f(&mut self.data.borrow_mut()));
+        if let Some((id_remove, status)) = finished {
             ac.0.rt.async_actions.remove(id_remove);
+            self.handle.finish(status, ac);
         }
     }
 
     fn cancel(self: &Rc<Self>, ac: &mut ActionContext) {
-        self.call(ac, |data| Some(data.take()?.id))
+        self.call(ac, |data| Some((data.take()?.id, ActionStatus::Cancelled)))
     }
     fn next(self: Rc<Self>, ac: &mut ActionContext) {
+        let mut panic = None;
         self.call(ac, |data| {
             let d = data.as_mut()?;
             let mut cx = Context::from_waker(&d.waker);
-            if d.future.as_mut().poll(&mut cx).is_ready() {
-                Some(data.take()?.id)
-            } else {
-                None
-            }
+            let status = match catch_unwind(AssertUnwindSafe(|| d.future.as_mut().poll(&mut cx))) {
+                Ok(Poll::Pending) => return None,
+                Ok(Poll::Ready(())) => ActionStatus::Done,
+                Err(e) => {
+                    panic = Some(e);
+                    ActionStatus::Panicked
+                }
+            };
+            Some((data.take()?.id, status))
         });
+        if let Some(panic) = panic {
+            resume_unwind(panic);
+        }
     }
     fn to_action(self: &Rc<Self>) -> Action {
         Action::from_rc_fn(self.clone(), Self::next)
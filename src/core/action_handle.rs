@@ -0,0 +1,150 @@
+use std::{
+    cell::{Cell, RefCell},
+    future::Future,
+    pin::Pin,
+    rc::{Rc, Weak},
+    task::{Context, Poll, Waker},
+};
+
+use crate::{Signal, State};
+
+use super::{Action, ActionContext, ActionPhase, AsyncAction};
+
+/// The status of an action spawned by
+/// [`spawn_action_async_with_handle`](super::spawn_action_async_with_handle).
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum ActionStatus {
+    /// The action has not completed yet.
+    Running,
+    /// The action has completed and its output is available.
+    Done,
+    /// The action was cancelled by [`ActionHandle::abort`] or by dropping the runtime.
+    Cancelled,
+    /// The action panicked.
+    Panicked,
+}
+
+/// A handle to an action spawned by
+/// [`spawn_action_async_with_handle`](super::spawn_action_async_with_handle).
+///
+/// Awaiting the handle returns the output of the action,
+/// or `None` if the action was cancelled or panicked.
+///
+/// By default, dropping the handle detaches the action and lets it run to completion.
+/// Use [`abort_on_drop`](Self::abort_on_drop) to cancel the action when the handle is dropped
+/// instead.
+pub struct ActionHandle<T: 'static> {
+    data: Rc<ActionHandleData<T>>,
+    abort_on_drop: bool,
+}
+
+impl<T: 'static> ActionHandle<T> {
+    pub(super) fn new(phase: ActionPhase) -> Self {
+        Self {
+            data: Rc::new(ActionHandleData {
+                phase,
+                status: Cell::new(ActionStatus::Running),
+                status_state: State::new(ActionStatus::Running),
+                value: RefCell::new(None),
+                waker: RefCell::new(None),
+                action: RefCell::new(Weak::new()),
+            }),
+            abort_on_drop: false,
+        }
+    }
+    pub(super) fn data(&self) -> &Rc<ActionHandleData<T>> {
+        &self.data
+    }
+
+    /// Returns a signal with the status of the action.
+    pub fn status(&self) -> Signal<ActionStatus> {
+        self.data.status_state.to_signal()
+    }
+
+    /// Cancels the action.
+    ///
+    /// The future of the action is dropped and the status becomes [`ActionStatus::Cancelled`].
+    /// Does nothing if the action has already finished.
+    pub fn abort(&self, ac: &mut ActionContext) {
+        self.data.abort(ac);
+    }
+
+    /// Cancels the action when this handle is dropped.
+    pub fn abort_on_drop(mut self) -> Self {
+        self.abort_on_drop = true;
+        self
+    }
+
+    /// Drops this handle and lets the action run to completion.
+    pub fn detach(mut self) {
+        self.abort_on_drop = false;
+    }
+}
+impl<T: 'static> Future for ActionHandle<T> {
+    type Output = Option<T>;
+
+    fn poll(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Self::Output> {
+        let d = &self.data;
+        match d.status.get() {
+            ActionStatus::Running => {
+                *d.waker.borrow_mut() = Some(cx.waker().clone());
+                Poll::Pending
+            }
+            ActionStatus::Done => Poll::Ready(d.value.borrow_mut().take()),
+            ActionStatus::Cancelled | ActionStatus::Panicked => Poll::Ready(None),
+        }
+    }
+}
+impl<T: 'static> Drop for ActionHandle<T> {
+    fn drop(&mut self) {
+        if self.abort_on_drop && self.data.status.get() == ActionStatus::Running {
+            Action::from_rc_fn(self.data.clone(), |data, ac| data.abort(ac))
+                .schedule_in(self.data.phase);
+        }
+    }
+}
+
+pub(super) struct ActionHandleData<T> {
+    phase: ActionPhase,
+    status: Cell<ActionStatus>,
+    status_state: State<ActionStatus>,
+    value: RefCell<Option<T>>,
+    waker: RefCell<Option<Waker>>,
+    action: RefCell<Weak<AsyncAction>>,
+}
+
+impl<T: 'static> ActionHandleData<T> {
+    pub fn is_running(&self) -> bool {
+        self.status.get() == ActionStatus::Running
+    }
+    pub fn set_action(&self, action: &Rc<AsyncAction>) {
+        *self.action.borrow_mut() = Rc::downgrade(action);
+    }
+    pub fn set_value(&self, value: T) {
+        *self.value.borrow_mut() = Some(value);
+    }
+    fn abort(&self, ac: &mut ActionContext) {
+        let action = self.action.borrow().upgrade();
+        if let Some(action) = action {
+            action.cancel(ac);
+        } else {
+            self.finish(ActionStatus::Cancelled, ac);
+        }
+    }
+}
+
+pub(super) trait FinishAction {
+    fn finish(&self, status: ActionStatus, ac: &mut ActionContext);
+}
+impl<T: 'static> FinishAction for ActionHandleData<T> {
+    fn finish(&self, status: ActionStatus, ac: &mut ActionContext) {
+        if !self.is_running() {
+            return;
+        }
+        self.status.set(status);
+        self.status_state.set(status, ac);
+        if let Some(waker) = self.waker.take() {
+            waker.wake();
+        }
+    }
+}
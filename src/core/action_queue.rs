@@ -0,0 +1,301 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::{BTreeMap, HashMap},
+    future::poll_fn,
+    hash::Hash,
+    rc::Rc,
+    task::{Poll, Waker},
+};
+
+use derive_ex::derive_ex;
+
+use crate::{Signal, SignalBuilder, State};
+
+use super::{ActionHandle, ActionPhase, AsyncActionContext, spawn_action_async_with_handle_in};
+
+/// A queue of asynchronous actions that runs actions with the same key one after another.
+///
+/// Actions with different keys run concurrently, up to the limit given to [`new`](Self::new).
+/// Actions that cannot run yet wait in the order they were spawned.
+#[derive_ex(Clone(bound()))]
+pub struct ActionQueue<K: 'static>(Rc<ActionQueueData<K>>);
+
+impl<K> ActionQueue<K>
+where
+    K: Eq + Hash + Clone + 'static,
+{
+    /// Creates a queue that runs at most `limit` actions at once.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `limit` is `0`.
+    pub fn new(limit: usize) -> Self {
+        assert!(limit > 0, "`ActionQueue` requires a limit of at least 1.");
+        Self(Rc::new(ActionQueueData {
+            limit,
+            s: RefCell::new(ActionQueueState {
+                running: 0,
+                next_job_id: 0,
+                keys: HashMap::new(),
+                ready: BTreeMap::new(),
+            }),
+        }))
+    }
+
+    /// Spawns an asynchronous action that runs after the actions spawned earlier with the same key.
+    ///
+    /// The returned handle reports [`ActionStatus::Running`](super::ActionStatus::Running)
+    /// while the action is waiting for its turn.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a [`Runtime`](super::Runtime) exists and the default [`ActionPhase`] is not valid
+    /// according to its [`RuntimeConfig`](super::RuntimeConfig).
+    pub fn spawn<T: 'static>(
+        &self,
+        key: K,
+        f: impl AsyncFnOnce(&mut AsyncActionContext) -> T + 'static,
+    ) -> ActionHandle<T> {
+        self.spawn_in(ActionPhase::default(), key, f)
+    }
+
+    /// Spawns an asynchronous action with a specific phase.
+    ///
+    /// # Panics
+    ///
+    /// Panics if a [`Runtime`](super::Runtime) exists and `phase` is not valid according to its
+    /// [`RuntimeConfig`](super::RuntimeConfig).
+    pub fn spawn_in<T: 'static>(
+        &self,
+        phase: ActionPhase,
+        key: K,
+        f: impl AsyncFnOnce(&mut AsyncActionContext) -> T + 'static,
+    ) -> ActionHandle<T> {
+        let this = self.clone();
+        spawn_action_async_with_handle_in(phase, async move |ac| {
+            let ticket = Ticket::new(this, key, ac);
+            ticket.wait().await;
+            f(ac).await
+        })
+    }
+
+    /// Returns a signal with the number of actions with `key` that are waiting or running.
+    pub fn pending_count(&self, key: K) -> Signal<usize> {
+        let queue = self.clone();
+        SignalBuilder::from_scan((None, 0), move |st, sc| {
+            let observer =
+                st.0.get_or_insert_with(|| KeyObserver::new(queue.clone(), key.clone()));
+            st.1 = observer.pending.get(sc);
+        })
+        .on_discard(|st| st.0 = None)
+        .map(|st| &st.1)
+        .build()
+    }
+}
+
+struct ActionQueueData<K> {
+    limit: usize,
+    s: RefCell<ActionQueueState<K>>,
+}
+
+impl<K> ActionQueueData<K>
+where
+    K: Eq + Hash + Clone + 'static,
+{
+    fn dispatch(&self) {
+        let mut wakers = Vec::new();
+        {
+            let s = &mut *self.s.borrow_mut();
+            while s.running < self.limit
+                && let Some((_, key)) = s.ready.pop_first()
+            {
+                let key = s.keys.get_mut(&key).unwrap();
+                let (_, job) = key.waiting.pop_first().unwrap();
+                key.is_running = true;
+                s.running += 1;
+                job.is_started.set(true);
+                wakers.extend(job.waker.take());
+            }
+        }
+        for waker in wakers {
+            waker.wake();
+        }
+    }
+}
+
+struct ActionQueueState<K> {
+    running: usize,
+    next_job_id: u64,
+    keys: HashMap<K, KeyState>,
+
+    // Keys that have waiting actions and no running action, by the ID of their first waiting action.
+    ready: BTreeMap<u64, K>,
+}
+
+impl<K> ActionQueueState<K>
+where
+    K: Eq + Hash + Clone + 'static,
+{
+    fn key(&mut self, key: &K) -> &mut KeyState {
+        if !self.keys.contains_key(key) {
+            self.keys.insert(
+                key.clone(),
+                KeyState {
+                    pending: State::new(0),
+                    observers: 0,
+                    is_running: false,
+                    waiting: BTreeMap::new(),
+                },
+            );
+        }
+        self.keys.get_mut(key).unwrap()
+    }
+    fn update_ready(&mut self, key: &K) {
+        let state = &self.keys[key];
+        if !state.is_running
+            && let Some((&id, _)) = state.waiting.first_key_value()
+        {
+            self.ready.insert(id, key.clone());
+        }
+    }
+    fn remove_if_unused(&mut self, key: &K) {
+        let state = &self.keys[key];
+        if !state.is_running && state.waiting.is_empty() && state.observers == 0 {
+            self.keys.remove(key);
+        }
+    }
+}
+
+struct KeyState {
+    pending: State<usize>,
+    observers: usize,
+    is_running: bool,
+    waiting: BTreeMap<u64, Rc<Job>>,
+}
+
+struct Job {
+    id: u64,
+    is_started: Cell<bool>,
+    waker: RefCell<Option<Waker>>,
+}
+
+struct Ticket<K>
+where
+    K: Eq + Hash + Clone + 'static,
+{
+    queue: ActionQueue<K>,
+    key: K,
+    job: Rc<Job>,
+    ac: AsyncActionContext,
+}
+
+impl<K> Ticket<K>
+where
+    K: Eq + Hash + Clone + 'static,
+{
+    fn new(queue: ActionQueue<K>, key: K, ac: &AsyncActionContext) -> Self {
+        let (job, pending) = {
+            let s = &mut *queue.0.s.borrow_mut();
+            let id = s.next_job_id;
+            s.next_job_id += 1;
+            let job = Rc::new(Job {
+                id,
+                is_started: Cell::new(false),
+                waker: RefCell::new(None),
+            });
+            let state = s.key(&key);
+            state.waiting.insert(id, job.clone());
+            let pending = state.pending.clone();
+            if state.waiting.len() == 1 {
+                s.update_ready(&key);
+            }
+            (job, pending)
+        };
+        ac.call(|ac| *pending.borrow_mut(ac) += 1);
+        queue.0.dispatch();
+        Self {
+            queue,
+            key,
+            job,
+            ac: AsyncActionContext(ac.0.clone()),
+        }
+    }
+    async fn wait(&self) {
+        poll_fn(|cx| {
+            if self.job.is_started.get() {
+                Poll::Ready(())
+            } else {
+                *self.job.waker.borrow_mut() = Some(cx.waker().clone());
+                Poll::Pending
+            }
+        })
+        .await
+    }
+}
+impl<K> Drop for Ticket<K>
+where
+    K: Eq + Hash + Clone + 'static,
+{
+    fn drop(&mut self) {
+        let pending = {
+            let s = &mut *self.queue.0.s.borrow_mut();
+            let id = self.job.id;
+            let state = s.keys.get_mut(&self.key).unwrap();
+            let pending = state.pending.clone();
+            if self.job.is_started.get() {
+                state.is_running = false;
+                s.running -= 1;
+                s.update_ready(&self.key);
+            } else {
+                let is_first = state.waiting.first_key_value().map(|(&id, _)| id) == Some(id);
+                state.waiting.remove(&id);
+                if is_first && s.ready.remove(&id).is_some() {
+                    s.update_ready(&self.key);
+                }
+            }
+            s.remove_if_unused(&self.key);
+            pending
+        };
+        self.ac.0.try_with(|ac| *pending.borrow_mut(ac) -= 1);
+        self.queue.0.dispatch();
+    }
+}
+
+/// Keeps the state of a key while its pending count is observed.
+struct KeyObserver<K>
+where
+    K: Eq + Hash + Clone + 'static,
+{
+    queue: ActionQueue<K>,
+    key: K,
+    pending: State<usize>,
+}
+
+impl<K> KeyObserver<K>
+where
+    K: Eq + Hash + Clone + 'static,
+{
+    fn new(queue: ActionQueue<K>, key: K) -> Self {
+        let pending = {
+            let s = &mut *queue.0.s.borrow_mut();
+            let state = s.key(&key);
+            state.observers += 1;
+            state.pending.clone()
+        };
+        Self {
+            queue,
+            key,
+            pending,
+        }
+    }
+}
+impl<K> Drop for KeyObserver<K>
+where
+    K: Eq + Hash + Clone + 'static,
+{
+    fn drop(&mut self) {
+        let s = &mut *self.queue.0.s.borrow_mut();
+        s.keys.get_mut(&self.key).unwrap().observers -= 1;
+        s.remove_if_unused(&self.key);
+    }
+}
@@ -1,30 +1,54 @@
 use std::{
     cell::RefCell,
     future::{Future, poll_fn},
+    mem::take,
     pin::Pin,
     rc::{Rc, Weak},
     task::{Context, Poll, Waker},
 };
 
+use crate::utils::sync::oneshot_broadcast::{Receiver, Sender, oneshot_broadcast};
+
 use super::{
     BindSink, Dirty, DirtyLevel, ReactionContext, SignalContext, SignalContextChannel, Slot,
     SourceBindings, waker_from_sink,
 };
 
+// The slots of an `AsyncSourceBinder`, relative to its first slot.
 const SLOT_WAKE: Slot = Slot(0);
 const SLOT_DEPS: Slot = Slot(1);
 const SLOT_POLL: Slot = Slot(2);
+const SLOT_COUNT: usize = 3;
 
 #[derive(Default)]
 struct AsyncSignalContextState {
     poll_waker: Option<Waker>,
     poll_bindings: SourceBindings,
+    cancellation: Option<Cancellation>,
+}
+
+struct Cancellation {
+    sender: Sender<()>,
+    receiver: Receiver<()>,
+    hooks: Vec<Box<dyn FnOnce()>>,
+}
+impl Cancellation {
+    fn new() -> Self {
+        let (sender, receiver) = oneshot_broadcast();
+        Self {
+            sender,
+            receiver,
+            hooks: Vec::new(),
+        }
+    }
 }
 
 struct AsyncSignalContextData {
     context: SignalContextChannel,
     s: RefCell<AsyncSignalContextState>,
     sink: Weak<dyn BindSink>,
+    slot_poll: Slot,
+    is_fork: bool,
 }
 
 /// Context for asynchronous state retrieval and dependency tracking.
@@ -53,9 +77,14 @@ impl AsyncSignalContext {
                 .try_with(|context| {
                     let s = &mut *self.0.s.borrow_mut();
                     let sink = self.0.sink.clone();
-                    let ret = s
-                        .poll_bindings
-                        .update(sink, SLOT_POLL, true, &f, context.rc());
+                    let is_fork = self.0.is_fork;
+                    let ret = s.poll_bindings.update(
+                        sink,
+                        self.0.slot_poll,
+                        true,
+                        |sc| sc.with_fork(is_fork, &f),
+                        context.rc(),
+                    );
                     if ret.is_ready() {
                         context.extend(&mut s.poll_bindings);
                         s.poll_waker.take();
@@ -68,16 +97,64 @@ impl AsyncSignalContext {
         })
         .await
     }
+
+    /// Waits until `f` returns `true`.
+    ///
+    /// `f` is called again each time the dependencies recorded in the `SignalContext` change.
+    /// As with [`poll_fn`](Self::poll_fn), the dependencies of the last call are added to
+    /// the dependencies in `AsyncSignalContext` when this function completes.
+    pub async fn wait_until(&mut self, f: impl Fn(&mut SignalContext<'_, '_>) -> bool) {
+        self.poll_fn(|sc| {
+            if f(sc) {
+                Poll::Ready(())
+            } else {
+                Poll::Pending
+            }
+        })
+        .await
+    }
+
+    /// Returns a future that completes when the running asynchronous function is cancelled.
+    ///
+    /// The asynchronous function is cancelled when it is dropped before completion,
+    /// either because a dependency has changed or because it is no longer needed.
+    /// The returned future does not borrow `self` and can be sent to other threads,
+    /// so it can be used to stop work that is running outside of the asynchronous function.
+    ///
+    /// If the asynchronous function completes, the returned future never completes.
+    pub fn cancelled(&mut self) -> impl Future<Output = ()> + Send + Sync + 'static {
+        let receiver = self.cancellation(|c| c.receiver.clone());
+        async move { receiver.recv().await }
+    }
+
+    /// Registers a function to be called when the running asynchronous function is cancelled.
+    ///
+    /// `f` is called before the future is dropped and before the next asynchronous function starts.
+    /// If the asynchronous function completes, `f` is dropped without being called.
+    pub fn on_cancel(&mut self, f: impl FnOnce() + 'static) {
+        self.cancellation(|c| c.hooks.push(Box::new(f)));
+    }
+
+    fn cancellation<T>(&self, f: impl FnOnce(&mut Cancellation) -> T) -> T {
+        f(self
+            .0
+            .s
+            .borrow_mut()
+            .cancellation
+            .get_or_insert_with(Cancellation::new))
+    }
 }
 
 struct AsyncSignalContextSource(Rc<AsyncSignalContextData>);
 
 impl AsyncSignalContextSource {
-    pub fn new(sink: Weak<dyn BindSink>) -> Self {
+    pub fn new(sink: Weak<dyn BindSink>, slot_poll: Slot, is_fork: bool) -> Self {
         Self(Rc::new(AsyncSignalContextData {
             context: SignalContextChannel::new(),
             s: RefCell::new(AsyncSignalContextState::default()),
             sink,
+            slot_poll,
+            is_fork,
         }))
     }
     pub fn sc(&self) -> AsyncSignalContext {
@@ -88,26 +165,88 @@ impl AsyncSignalContextSource {
     }
 }
 
+/// A boundary that counts the asynchronous signals read beneath it that are `Pending`.
+///
+/// Boundaries are entered with [`SignalContext::with_boundary`].
+pub(crate) trait PendingBoundary: 'static {
+    fn increment(self: Rc<Self>);
+    fn decrement(self: Rc<Self>);
+}
+
+/// The membership of an asynchronous signal in a [`PendingBoundary`].
+struct BoundaryTicket {
+    boundary: Rc<dyn PendingBoundary>,
+    is_counted: bool,
+}
+impl BoundaryTicket {
+    fn set_pending(&mut self, is_pending: bool) {
+        if self.is_counted != is_pending {
+            self.is_counted = is_pending;
+            if is_pending {
+                self.boundary.clone().increment();
+            } else {
+                self.boundary.clone().decrement();
+            }
+        }
+    }
+}
+impl Drop for BoundaryTicket {
+    fn drop(&mut self) {
+        self.set_pending(false);
+    }
+}
+
 pub struct AsyncSourceBinder {
     sc: AsyncSignalContextSource,
+    slot: usize,
+    is_fork: bool,
     sources: SourceBindings,
     dirty: Dirty,
     is_wake: bool,
+    is_pending: bool,
+    boundaries: Vec<BoundaryTicket>,
     waker: Waker,
 }
 impl AsyncSourceBinder {
     pub fn new(sink: &Weak<impl BindSink>) -> Self {
+        Self::with_slot(sink, 0, false)
+    }
+
+    /// Creates a binder that evaluates the asynchronous function in the fork of a transition.
+    ///
+    /// It notifies `sink` with slots that do not overlap with those of [`new`](Self::new).
+    pub(crate) fn new_fork(sink: &Weak<impl BindSink>) -> Self {
+        Self::with_slot(sink, SLOT_COUNT, true)
+    }
+
+    /// Returns whether `slot` is notified by a binder created by [`new_fork`](Self::new_fork).
+    pub(crate) fn is_fork_slot(slot: Slot) -> bool {
+        slot.0 >= SLOT_COUNT
+    }
+
+    fn with_slot(sink: &Weak<impl BindSink>, slot: usize, is_fork: bool) -> Self {
+        let slot_poll = Slot(slot + SLOT_POLL.0);
         Self {
-            sc: AsyncSignalContextSource::new(sink.clone()),
+            sc: AsyncSignalContextSource::new(sink.clone(), slot_poll, is_fork),
+            slot,
+            is_fork,
             sources: SourceBindings::new(),
             dirty: Dirty::Dirty,
             is_wake: false,
-            waker: waker_from_sink(sink.clone(), SLOT_WAKE),
+            is_pending: false,
+            boundaries: Vec::new(),
+            waker: waker_from_sink(sink.clone(), Slot(slot + SLOT_WAKE.0)),
         }
     }
+    fn slot(&self, slot: Slot) -> Slot {
+        Slot(self.slot + slot.0)
+    }
     pub fn is_clean(&self) -> bool {
         self.dirty.is_clean() && !self.is_wake
     }
+    pub(crate) fn is_wake(&self) -> bool {
+        self.is_wake
+    }
     pub fn check(&mut self, rc: &mut ReactionContext<'_, '_>) -> bool {
         self.sources.check_with(&mut self.dirty, rc)
     }
@@ -120,8 +259,14 @@ impl AsyncSourceBinder {
         self.is_wake = true;
         let asc = self.sc.sc();
         let sink = self.sc.0.sink.clone();
-        self.sources
-            .update(sink, SLOT_DEPS, true, |sc| self.sc.with(sc, || f(asc)), rc)
+        let slot = self.slot(SLOT_DEPS);
+        self.sources.update(
+            sink,
+            slot,
+            true,
+            |sc| sc.with_fork(self.is_fork, |sc| self.sc.with(sc, || f(asc))),
+            rc,
+        )
     }
     pub fn poll<T>(
         &mut self,
@@ -130,25 +275,95 @@ impl AsyncSourceBinder {
     ) -> Poll<T> {
         self.is_wake = false;
         let sink = self.sc.0.sink.clone();
-        self.sources.update(
+        let slot = self.slot(SLOT_DEPS);
+        let ret = self.sources.update(
             sink,
-            SLOT_DEPS,
+            slot,
             false,
             |sc| {
-                self.sc
-                    .with(sc, || fut.poll(&mut Context::from_waker(&self.waker)))
+                sc.with_fork(self.is_fork, |sc| {
+                    self.sc
+                        .with(sc, || fut.poll(&mut Context::from_waker(&self.waker)))
+                })
             },
             rc,
-        )
+        );
+        if ret.is_ready() {
+            self.sc.0.s.borrow_mut().cancellation = None;
+        }
+        self.set_pending(ret.is_pending());
+        ret
+    }
+
+    /// Adds this signal to the innermost boundary of `sc`, so that the boundary counts it while
+    /// the asynchronous function is `Pending`.
+    ///
+    /// The signal stays in the boundary until [`clear`](Self::clear) is called.
+    pub(crate) fn join_boundary(&mut self, sc: &SignalContext<'_, '_>) {
+        let Some(boundary) = sc.boundary() else {
+            return;
+        };
+        if self
+            .boundaries
+            .iter()
+            .any(|t| Rc::ptr_eq(&t.boundary, boundary))
+        {
+            return;
+        }
+        let mut ticket = BoundaryTicket {
+            boundary: boundary.clone(),
+            is_counted: false,
+        };
+        ticket.set_pending(self.is_pending);
+        self.boundaries.push(ticket);
+    }
+    fn set_pending(&mut self, is_pending: bool) {
+        self.is_pending = is_pending;
+        for ticket in &mut self.boundaries {
+            ticket.set_pending(is_pending);
+        }
+    }
+    /// Cancels the running asynchronous function and returns the functions registered by
+    /// [`AsyncSignalContext::on_cancel`].
+    ///
+    /// The returned functions must be called with [`CancelHooks::run`] after the caller releases
+    /// its borrows, because they may access the signal being cancelled.
+    pub(crate) fn cancel(&mut self) -> CancelHooks {
+        let cancellation = self.sc.0.s.borrow_mut().cancellation.take();
+        let Some(c) = cancellation else {
+            return CancelHooks(Vec::new());
+        };
+        c.sender.send(());
+        CancelHooks(c.hooks)
+    }
+    pub(crate) fn rearm(&mut self, rc: &mut ReactionContext<'_, '_>) {
+        let sink = self.sc.0.sink.clone();
+        let slot = self.slot(SLOT_DEPS);
+        self.sources.rearm(sink, slot, rc);
+        self.dirty = Dirty::Clean;
+    }
+
+    /// Replaces the dependencies with those of `fork`, which has completed the same
+    /// asynchronous function in the fork of a transition.
+    ///
+    /// The running asynchronous function must be cancelled before this call.
+    pub(crate) fn adopt(&mut self, fork: &mut AsyncSourceBinder, rc: &mut ReactionContext<'_, '_>) {
+        self.sources.clear(rc);
+        self.sources = take(&mut fork.sources);
+        self.rearm(rc);
+        self.is_wake = false;
+        self.set_pending(false);
     }
     pub fn clear(&mut self, rc: &mut ReactionContext<'_, '_>) {
         self.sources.clear(rc);
         self.dirty = Dirty::Dirty;
+        self.is_pending = false;
+        self.boundaries.clear();
     }
 
     pub fn on_notify(&mut self, slot: Slot, level: DirtyLevel) -> bool {
         let mut needs_notify = false;
-        match slot {
+        match Slot(slot.0.wrapping_sub(self.slot)) {
             SLOT_WAKE => {
                 needs_notify = !self.is_wake;
                 self.is_wake = true;
@@ -167,3 +382,20 @@ impl AsyncSourceBinder {
         needs_notify
     }
 }
+impl Drop for AsyncSourceBinder {
+    fn drop(&mut self) {
+        self.cancel().run();
+    }
+}
+
+/// Functions registered by [`AsyncSignalContext::on_cancel`] that are waiting to be called.
+#[must_use]
+pub(crate) struct CancelHooks(Vec<Box<dyn FnOnce()>>);
+
+impl CancelHooks {
+    pub(crate) fn run(self) {
+        for hook in self.0 {
+            hook();
+        }
+    }
+}
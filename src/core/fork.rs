@@ -0,0 +1,189 @@
+use std::{
+    cell::RefCell,
+    mem::take,
+    rc::{Rc, Weak},
+};
+
+use super::{
+    Action, ActionContext, BindKey, BindSource, DirtyLevel, NotifyContext, ReactionContext,
+    SignalContext, SinkBindings, Slot,
+};
+
+/// The transition of a [`Runtime`](super::Runtime).
+///
+/// State changes made while the fork is open are written to the fork instead of the states, so
+/// signals read outside the fork keep returning the committed values. The asynchronous signals
+/// that depend on those changes are evaluated in the fork in the background. The transition
+/// commits when none of the observed ones is `Pending`, and then the changes are written to the
+/// states and the asynchronous signals take over the values evaluated in the fork.
+#[derive(Default)]
+pub(crate) struct Fork {
+    s: RefCell<ForkState>,
+    sinks: RefCell<SinkBindings>,
+}
+
+#[derive(Default)]
+struct ForkState {
+    generation: usize,
+    is_active: bool,
+    is_open: bool,
+    is_update_scheduled: bool,
+    writes: Vec<Rc<dyn ForkWrite>>,
+    signals: Vec<Weak<dyn ForkSignal>>,
+}
+
+/// A state that holds a value written in the fork.
+pub(crate) trait ForkWrite: 'static {
+    /// Writes the value held in the fork to the state and notifies the dependants.
+    fn commit(self: Rc<Self>, nc: &mut NotifyContext);
+
+    /// Drops the value held in the fork.
+    fn discard(&self);
+}
+
+/// An asynchronous signal that depends on the changes made in the fork.
+pub(crate) trait ForkSignal: 'static {
+    /// Evaluates this signal in the fork if it is observed, and returns whether it is `Pending`.
+    fn update_fork(self: Rc<Self>, ac: &mut ActionContext) -> bool;
+    fn is_clean(&self) -> bool;
+
+    /// Replaces the value outside the fork with the value evaluated in the fork.
+    fn commit_fork(self: Rc<Self>, ac: &mut ActionContext);
+
+    /// Drops the value evaluated in the fork.
+    fn discard_fork(&self);
+}
+
+impl Fork {
+    /// Calls `f` with the fork open.
+    ///
+    /// If a transition is already in progress, the changes made by `f` belong to it.
+    pub fn run(self: &Rc<Self>, ac: &mut ActionContext, f: impl FnOnce(&mut ActionContext)) {
+        let is_started = {
+            let mut s = self.s.borrow_mut();
+            let is_started = !s.is_active;
+            if is_started {
+                s.generation += 1;
+                s.is_active = true;
+            }
+            is_started
+        };
+        if is_started {
+            self.sinks.borrow_mut().notify(DirtyLevel::Dirty, ac.nc());
+        }
+        self.s.borrow_mut().is_open = true;
+
+        // The fork is evaluated after `f` returns, even if `f` panics,
+        // so that the transition can commit.
+        struct CloseGuard<'a>(&'a Rc<Fork>);
+        impl Drop for CloseGuard<'_> {
+            fn drop(&mut self) {
+                self.0.s.borrow_mut().is_open = false;
+                self.0.schedule_update();
+            }
+        }
+        let _guard = CloseGuard(self);
+        f(ac);
+    }
+
+    /// Returns whether state changes made now belong to the transition.
+    pub fn is_open(&self) -> bool {
+        self.s.borrow().is_open
+    }
+
+    /// Returns whether a transition is in progress and adds a dependency on it to `sc`.
+    pub fn is_active(self: &Rc<Self>, sc: &mut SignalContext<'_, '_>) -> bool {
+        self.sinks.borrow_mut().bind(self.clone(), Slot(0), sc);
+        self.s.borrow().is_active
+    }
+
+    /// Returns the generation of the transition in progress.
+    pub fn generation(&self) -> Option<usize> {
+        let s = self.s.borrow();
+        s.is_active.then_some(s.generation)
+    }
+
+    /// Adds `state` to the states written in the fork.
+    pub fn write(&self, state: Rc<dyn ForkWrite>) {
+        self.s.borrow_mut().writes.push(state);
+    }
+
+    /// Adds `signal` to the signals that depend on the changes made in the fork.
+    pub fn join(&self, signal: Weak<dyn ForkSignal>) {
+        self.s.borrow_mut().signals.push(signal);
+    }
+
+    /// Schedules the evaluation of the fork.
+    pub fn schedule_update(self: &Rc<Self>) {
+        let mut s = self.s.borrow_mut();
+        if s.is_active && !s.is_open && !s.is_update_scheduled {
+            s.is_update_scheduled = true;
+            Action::from_rc_fn(self.clone(), |this, ac| this.update(ac)).schedule();
+        }
+    }
+    fn update(self: Rc<Self>, ac: &mut ActionContext) {
+        self.s.borrow_mut().is_update_scheduled = false;
+        loop {
+            let signals: Vec<_> = {
+                let s = self.s.borrow();
+                if !s.is_active || s.is_open {
+                    return;
+                }
+                s.signals.iter().filter_map(Weak::upgrade).collect()
+            };
+            let mut is_pending = false;
+            for signal in &signals {
+                is_pending |= signal.clone().update_fork(ac);
+            }
+            if is_pending {
+                return;
+            }
+            // A signal that became `Ready` may have invalidated a signal evaluated before it,
+            // and a signal read in the fork may have started to be evaluated.
+            if signals.iter().all(|signal| signal.is_clean()) {
+                break;
+            }
+        }
+        self.commit(ac);
+    }
+    fn commit(self: &Rc<Self>, ac: &mut ActionContext) {
+        let (writes, signals) = {
+            let mut s = self.s.borrow_mut();
+            s.is_active = false;
+            (take(&mut s.writes), take(&mut s.signals))
+        };
+        for state in writes {
+            state.commit(ac.nc());
+        }
+        for signal in signals.iter().filter_map(Weak::upgrade) {
+            signal.commit_fork(ac);
+        }
+        self.sinks.borrow_mut().notify(DirtyLevel::Dirty, ac.nc());
+    }
+}
+
+// A transition that has not committed when the runtime is dropped is discarded.
+impl Drop for ForkState {
+    fn drop(&mut self) {
+        for state in &self.writes {
+            state.discard();
+        }
+        for signal in self.signals.iter().filter_map(Weak::upgrade) {
+            signal.discard_fork();
+        }
+    }
+}
+
+impl BindSource for Fork {
+    fn check(self: Rc<Self>, _slot: Slot, key: BindKey, rc: &mut ReactionContext<'_, '_>) -> bool {
+        self.sinks.borrow().is_dirty(key, rc)
+    }
+
+    fn unbind(self: Rc<Self>, _slot: Slot, key: BindKey, rc: &mut ReactionContext<'_, '_>) {
+        self.sinks.borrow_mut().unbind(key, rc);
+    }
+
+    fn rebind(self: Rc<Self>, slot: Slot, key: BindKey, sc: &mut SignalContext<'_, '_>) {
+        self.sinks.borrow_mut().rebind(self.clone(), slot, key, sc);
+    }
+}
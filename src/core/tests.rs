@@ -1,5 +1,6 @@
 use super::*;
 use crate::utils::sync::oneshot_broadcast;
+use futures::FutureExt;
 use pretty_assertions::assert_eq;
 use std::{
     cell::{Cell, RefCell},
@@ -389,3 +390,357 @@ fn runtime_call_outside_lend() {
 fn runtime_call_without_runtime() {
     Runtime::call(|_rt| {});
 }
+
+#[test]
+fn batch_reads_are_consistent() {
+    let mut rt = Runtime::new();
+    let a = crate::State::new(1);
+    let b = crate::State::new(2);
+    let sum = crate::Signal::new({
+        let a = a.clone();
+        let b = b.clone();
+        move |sc| a.get(sc) + b.get(sc)
+    });
+    let runs = Rc::new(RefCell::new(Vec::new()));
+    let _e = crate::effect({
+        let sum = sum.clone();
+        let runs = runs.clone();
+        move |sc| runs.borrow_mut().push(sum.get(sc))
+    });
+    rt.flush();
+
+    rt.ac().batch(|ac| {
+        a.set(10, ac);
+        assert_eq!(sum.get(&mut ac.sc()), 12);
+        ac.batch(|ac| b.set(20, ac));
+        assert_eq!(sum.get(&mut ac.sc()), 30);
+    });
+    rt.flush();
+
+    assert_eq!(*runs.borrow(), [3, 30]);
+}
+
+#[test]
+fn batch_schedules_reactions_after_it_returns() {
+    let mut rt = Runtime::new();
+    let a = crate::State::new(1);
+    let runs = Rc::new(Cell::new(0));
+    let _e = crate::effect({
+        let a = a.clone();
+        let runs = runs.clone();
+        move |sc| {
+            a.get(sc);
+            runs.set(runs.get() + 1);
+        }
+    });
+    rt.flush();
+
+    rt.ac().batch(|ac| {
+        a.set(2, ac);
+        assert!(!has_scheduled_reactions());
+    });
+    assert!(has_scheduled_reactions());
+    rt.flush();
+    assert_eq!(runs.get(), 2);
+}
+
+fn has_scheduled_reactions() -> bool {
+    Globals::with(|g| !g.reactions.is_empty())
+}
+
+#[test]
+fn batch_reads_change_feeds_consistently() {
+    let mut rt = Runtime::new();
+    let a = crate::collections::vec::StateVec::new();
+    let b = crate::collections::vec::StateVec::new();
+    let len = crate::Signal::new({
+        let a = a.clone();
+        let b = b.clone();
+        move |sc| a.borrow(sc).len() + b.borrow(sc).len()
+    });
+    assert_eq!(len.get(&mut rt.sc()), 0);
+
+    rt.ac().batch(|ac| {
+        a.borrow_mut(ac).push(1);
+        assert_eq!(len.get(&mut ac.sc()), 1);
+        b.borrow_mut(ac).extend([2, 3]);
+    });
+
+    assert_eq!(len.get(&mut rt.sc()), 3);
+}
+
+#[test]
+fn batch_holds_notifications_until_it_returns() {
+    struct CountSink(Cell<usize>);
+
+    impl BindSink for CountSink {
+        fn notify(self: Rc<Self>, _slot: Slot, _level: DirtyLevel, _nc: &mut NotifyContext) {
+            self.0.set(self.0.get() + 1);
+        }
+    }
+
+    let mut rt = Runtime::new();
+    let a = crate::State::new(1);
+    let b = crate::State::new(2);
+    let sink = Rc::new(CountSink(Cell::new(0)));
+    let mut sb = SourceBinder::new(&Rc::downgrade(&sink), Slot(0));
+    sb.update(
+        |sc| {
+            a.get(sc);
+            b.get(sc);
+        },
+        &mut rt.rc(),
+    );
+
+    rt.ac().batch(|ac| {
+        a.set(10, ac);
+        ac.batch(|ac| b.set(20, ac));
+        assert_eq!(sink.0.get(), 0);
+    });
+    assert_eq!(sink.0.get(), 2);
+}
+
+#[test]
+fn batch_notifies_dependants_on_panic() {
+    let mut rt = Runtime::new();
+    let a = crate::State::new(1);
+    let runs = Rc::new(RefCell::new(Vec::new()));
+    let _e = crate::effect({
+        let a = a.clone();
+        let runs = runs.clone();
+        move |sc| runs.borrow_mut().push(a.get(sc))
+    });
+    rt.flush();
+
+    let ac = rt.ac();
+    let result = catch_unwind(AssertUnwindSafe(|| {
+        ac.batch(|ac| {
+            a.set(2, ac);
+            std::panic!("error");
+        })
+    }));
+    assert!(result.is_err());
+    assert!(rt.ac().0.batch.is_none());
+    rt.flush();
+    a.set(3, rt.ac());
+    rt.flush();
+    assert_eq!(*runs.borrow(), [1, 2, 3]);
+}
+
+#[test]
+fn action_handle_returns_output() {
+    let mut rt = Runtime::new();
+    let (sender, receiver) = oneshot_broadcast::<i32>();
+    let handle = spawn_action_async_with_handle(async move |_| receiver.recv().await * 2);
+    let status = handle.status();
+    let result = Rc::new(Cell::new(None));
+    spawn_action_async({
+        let result = result.clone();
+        async move |_| result.set(handle.await)
+    });
+
+    rt.flush();
+    assert_eq!(status.get(&mut rt.sc()), ActionStatus::Running);
+    assert_eq!(result.get(), None);
+
+    sender.send(10);
+    rt.flush();
+    assert_eq!(status.get(&mut rt.sc()), ActionStatus::Done);
+    assert_eq!(result.get(), Some(20));
+}
+
+#[test]
+fn action_handle_abort() {
+    let mut rt = Runtime::new();
+    let (_sender, receiver) = oneshot_broadcast::<i32>();
+    let dropped = Rc::new(Cell::new(false));
+    let handle = spawn_action_async_with_handle({
+        let dropped = dropped.clone();
+        async move |_| {
+            let _dropped = DropFlag(dropped);
+            receiver.recv().await
+        }
+    });
+    rt.flush();
+    assert!(!dropped.get());
+
+    handle.abort(rt.ac());
+    assert!(dropped.get());
+    assert_eq!(handle.status().get(&mut rt.sc()), ActionStatus::Cancelled);
+    assert_eq!(handle.now_or_never(), Some(None));
+}
+
+#[test]
+fn action_handle_abort_before_start() {
+    let mut rt = Runtime::new();
+    let called = Rc::new(Cell::new(false));
+    let handle = spawn_action_async_with_handle({
+        let called = called.clone();
+        async move |_| called.set(true)
+    });
+    handle.abort(rt.ac());
+    rt.flush();
+    assert!(!called.get());
+    assert_eq!(handle.status().get(&mut rt.sc()), ActionStatus::Cancelled);
+}
+
+#[test]
+fn action_handle_detach_on_drop() {
+    let mut rt = Runtime::new();
+    let (sender, receiver) = oneshot_broadcast::<()>();
+    let called = Rc::new(Cell::new(false));
+    let handle = spawn_action_async_with_handle({
+        let called = called.clone();
+        async move |_| {
+            receiver.recv().await;
+            called.set(true);
+        }
+    });
+    rt.flush();
+    drop(handle);
+    sender.send(());
+    rt.flush();
+    assert!(called.get());
+}
+
+#[test]
+fn action_handle_abort_on_drop() {
+    let mut rt = Runtime::new();
+    let (_sender, receiver) = oneshot_broadcast::<()>();
+    let dropped = Rc::new(Cell::new(false));
+    let handle = spawn_action_async_with_handle({
+        let dropped = dropped.clone();
+        async move |_| {
+            let _dropped = DropFlag(dropped);
+            receiver.recv().await;
+        }
+    })
+    .abort_on_drop();
+    let status = handle.status();
+    rt.flush();
+
+    drop(handle);
+    rt.flush();
+    assert!(dropped.get());
+    assert_eq!(status.get(&mut rt.sc()), ActionStatus::Cancelled);
+}
+
+#[test]
+fn action_handle_panicked() {
+    let mut rt = Runtime::new();
+    let handle = spawn_action_async_with_handle(async move |_| std::panic!("error"));
+    assert!(catch_unwind(AssertUnwindSafe(|| rt.flush())).is_err());
+    assert_eq!(handle.status().get(&mut rt.sc()), ActionStatus::Panicked);
+    assert_eq!(handle.now_or_never(), Some(None::<()>));
+}
+
+struct DropFlag(Rc<Cell<bool>>);
+
+impl Drop for DropFlag {
+    fn drop(&mut self) {
+        self.0.set(true);
+    }
+}
+
+#[test]
+fn action_queue_runs_same_key_in_order() {
+    let mut rt = Runtime::new();
+    let queue = ActionQueue::new(4);
+    let calls = Rc::new(RefCell::new(Vec::new()));
+    let (sender_a, receiver_a) = oneshot_broadcast::<()>();
+    let (sender_b, receiver_b) = oneshot_broadcast::<()>();
+    for (name, receiver) in [("a", receiver_a), ("b", receiver_b)] {
+        let calls = calls.clone();
+        queue
+            .spawn(42, async move |_| {
+                calls.borrow_mut().push(format!("start_{name}"));
+                receiver.recv().await;
+                calls.borrow_mut().push(format!("end_{name}"));
+            })
+            .detach();
+    }
+    let pending = queue.pending_count(42);
+
+    rt.flush();
+    assert_eq!(*calls.borrow(), ["start_a"]);
+    assert_eq!(pending.get(&mut rt.sc()), 2);
+
+    sender_a.send(());
+    rt.flush();
+    assert_eq!(*calls.borrow(), ["start_a", "end_a", "start_b"]);
+    assert_eq!(pending.get(&mut rt.sc()), 1);
+
+    sender_b.send(());
+    rt.flush();
+    assert_eq!(*calls.borrow(), ["start_a", "end_a", "start_b", "end_b"]);
+    assert_eq!(pending.get(&mut rt.sc()), 0);
+}
+
+#[test]
+fn action_queue_limits_concurrent_actions() {
+    let mut rt = Runtime::new();
+    let queue = ActionQueue::new(2);
+    let calls = Rc::new(RefCell::new(Vec::new()));
+    let (sender, receiver) = oneshot_broadcast::<()>();
+    let mut handles = Vec::new();
+    for key in [1, 2, 3] {
+        let calls = calls.clone();
+        let receiver = receiver.clone();
+        handles.push(queue.spawn(key, async move |_| {
+            calls.borrow_mut().push(key);
+            receiver.recv().await;
+        }));
+    }
+
+    rt.flush();
+    assert_eq!(*calls.borrow(), [1, 2]);
+
+    handles[0].abort(rt.ac());
+    rt.flush();
+    assert_eq!(*calls.borrow(), [1, 2, 3]);
+
+    sender.send(());
+    rt.flush();
+    for handle in &handles[1..] {
+        assert_eq!(handle.status().get(&mut rt.sc()), ActionStatus::Done);
+    }
+}
+
+#[test]
+fn action_queue_abort_waiting_action() {
+    let mut rt = Runtime::new();
+    let queue = ActionQueue::new(1);
+    let (_sender, receiver) = oneshot_broadcast::<()>();
+    let _running = queue.spawn("doc", async move |_| receiver.recv().await);
+    let waiting = queue.spawn("doc", async move |_| {});
+    let pending = queue.pending_count("doc");
+
+    rt.flush();
+    assert_eq!(pending.get(&mut rt.sc()), 2);
+
+    waiting.abort(rt.ac());
+    rt.flush();
+    assert_eq!(pending.get(&mut rt.sc()), 1);
+    assert_eq!(waiting.status().get(&mut rt.sc()), ActionStatus::Cancelled);
+}
+
+#[test]
+fn action_queue_pending_count_across_idle_key() {
+    let mut rt = Runtime::new();
+    let queue = ActionQueue::new(1);
+    let pending = queue.pending_count("doc");
+    let _e = pending.effect(|_| {});
+    rt.flush();
+    assert_eq!(pending.get(&mut rt.sc()), 0);
+
+    for _ in 0..2 {
+        let (sender, receiver) = oneshot_broadcast::<()>();
+        queue.spawn("doc", async move |_| receiver.recv().await);
+        rt.flush();
+        assert_eq!(pending.get(&mut rt.sc()), 1);
+
+        sender.send(());
+        rt.flush();
+        assert_eq!(pending.get(&mut rt.sc()), 0);
+    }
+}
@@ -1,4 +1,11 @@
-use std::{cell::RefCell, future::Future, ops::AsyncFnMut, pin::Pin, rc::Rc};
+use std::{
+    cell::RefCell,
+    future::Future,
+    mem::take,
+    ops::AsyncFnMut,
+    pin::Pin,
+    rc::{Rc, Weak},
+};
 
 use crate::{
     Subscription,
@@ -11,8 +18,33 @@ use crate::{
 #[cfg(test)]
 mod tests;
 
+/// How [`effect_async_with`] handles a dependency change while the asynchronous function is running.
+///
+/// Changes that occur while the effect is waiting to run again are handled as one change,
+/// and the next call of the function sees the latest state.
+#[derive(Clone, Copy, Debug, Default, Eq, PartialEq, Hash)]
+pub enum EffectAsyncPolicy {
+    /// Cancels the running function and calls it again, like `switchMap` of Rx.
+    #[default]
+    Switch,
+    /// Calls the function again after the running one completes, like `concatMap` of Rx.
+    ///
+    /// Unlike `concatMap`, changes that occur while the function is running are merged into one,
+    /// so the function is called again only once with the latest state.
+    Queue,
+    /// Ignores changes that occur while the function is running, like `exhaustMap` of Rx.
+    Exhaust,
+    /// Calls the function again while others are running, up to the specified number of running functions,
+    /// like `mergeMap` of Rx.
+    ///
+    /// When the limit is reached, the function is called again after one of the running functions completes.
+    Merge(usize),
+}
+
 /// Call an asynchronous function each time a dependency changes.
 ///
+/// If a dependency changes while the function is running, the running function is cancelled.
+///
 /// # Panics
 ///
 /// Panics if a [`Runtime`](crate::core::Runtime) exists and the default [`ReactionPhase`] is not
@@ -33,7 +65,7 @@ pub fn effect_async_in(
     f: impl AsyncFnMut(&mut AsyncSignalContext) + 'static,
 ) -> Subscription {
     let f = Rc::new(RefCell::new(f));
-    let this = EffectAsyncNode::new(
+    EffectAsyncNode::start(
         move |mut sc| {
             let f = f.clone();
             async move {
@@ -41,64 +73,209 @@ pub fn effect_async_in(
             }
         },
         phase,
-    );
-    this.schedule();
-    Subscription::from_rc(this)
+        EffectAsyncPolicy::Switch,
+    )
+}
+
+/// Call an asynchronous function each time a dependency changes with [`EffectAsyncPolicy`] specified.
+///
+/// Unlike [`effect_async`], `f` is [`AsyncFn`] because it may run several times concurrently.
+///
+/// # Panics
+///
+/// Panics if `policy` is `EffectAsyncPolicy::Merge(0)`.
+///
+/// Panics if a [`Runtime`](crate::core::Runtime) exists and the default [`ReactionPhase`] is not
+/// valid according to its [`RuntimeConfig`](crate::core::RuntimeConfig).
+pub fn effect_async_with(
+    policy: EffectAsyncPolicy,
+    f: impl AsyncFn(&mut AsyncSignalContext) + 'static,
+) -> Subscription {
+    effect_async_with_in(ReactionPhase::default(), policy, f)
+}
+
+/// Call an asynchronous function each time a dependency changes with [`ReactionPhase`] and
+/// [`EffectAsyncPolicy`] specified.
+///
+/// # Panics
+///
+/// Panics if `policy` is `EffectAsyncPolicy::Merge(0)`.
+///
+/// Panics if a [`Runtime`](crate::core::Runtime) exists and `phase` is not valid according to its
+/// [`RuntimeConfig`](crate::core::RuntimeConfig).
+pub fn effect_async_with_in(
+    phase: ReactionPhase,
+    policy: EffectAsyncPolicy,
+    f: impl AsyncFn(&mut AsyncSignalContext) + 'static,
+) -> Subscription {
+    let f = Rc::new(f);
+    EffectAsyncNode::start(
+        move |mut sc| {
+            let f = f.clone();
+            async move {
+                f(&mut sc).await;
+            }
+        },
+        phase,
+        policy,
+    )
 }
 
 struct EffectAsyncData<GetFut, Fut> {
     get_fut: GetFut,
-    fut: Pin<Box<Option<Fut>>>,
-    asb: AsyncSourceBinder,
+    runs: Vec<Rc<EffectAsyncRun<GetFut, Fut>>>,
 }
 
 struct EffectAsyncNode<GetFut, Fut> {
     data: RefCell<EffectAsyncData<GetFut, Fut>>,
     phase: ReactionPhase,
+    policy: EffectAsyncPolicy,
+    limit: usize,
 }
 impl<GetFut, Fut> EffectAsyncNode<GetFut, Fut>
 where
     GetFut: FnMut(AsyncSignalContext) -> Fut + 'static,
     Fut: Future<Output = ()> + 'static,
 {
-    fn new(f: GetFut, phase: ReactionPhase) -> Rc<Self> {
-        Rc::new_cyclic(|this| Self {
+    fn start(f: GetFut, phase: ReactionPhase, policy: EffectAsyncPolicy) -> Subscription {
+        let limit = match policy {
+            EffectAsyncPolicy::Switch | EffectAsyncPolicy::Queue | EffectAsyncPolicy::Exhaust => 1,
+            EffectAsyncPolicy::Merge(limit) => limit,
+        };
+        assert!(
+            limit > 0,
+            "`EffectAsyncPolicy::Merge` requires a limit of at least 1."
+        );
+        let this = Rc::new(Self {
             data: RefCell::new(EffectAsyncData {
                 get_fut: f,
-                fut: Box::pin(None),
-                asb: AsyncSourceBinder::new(this),
+                runs: Vec::new(),
             }),
             phase,
-        })
+            policy,
+            limit,
+        });
+        this.schedule();
+        Subscription::from_rc(this)
     }
     fn schedule(self: &Rc<Self>) {
         Reaction::from_weak_fn(Rc::downgrade(self), |this, rc| this.call(rc))
             .schedule_in(self.phase)
     }
+
+    // `runs` holds the running functions and, at the end, the latest one.
+    // The latest one is kept after completion because its dependencies decide when to call the function again.
     fn call(self: &Rc<Self>, rc: &mut ReactionContext) {
+        let latest = self.data.borrow().runs.last().cloned();
+        let is_changed = match latest {
+            Some(latest) => latest.check(rc),
+            None => true,
+        };
+        if is_changed && self.policy == EffectAsyncPolicy::Switch {
+            let runs = take(&mut self.data.borrow_mut().runs);
+            for run in runs {
+                run.cancel();
+            }
+        }
         let d = &mut *self.data.borrow_mut();
-        if d.asb.is_clean() {
+        let latest = d.runs.len().saturating_sub(1);
+        let mut index = 0;
+        d.runs.retain(|run| {
+            let is_latest = index == latest;
+            index += 1;
+            run.poll(rc) || is_latest
+        });
+        if !is_changed {
             return;
         }
-        if d.asb.check(rc) {
-            d.fut.set(None);
-            d.fut.set(Some(d.asb.init(&mut d.get_fut, rc)));
+        let running = d.runs.iter().filter(|run| run.is_running()).count();
+        if running < self.limit {
+            if d.runs.last().is_some_and(|latest| !latest.is_running()) {
+                d.runs.pop();
+            }
+            let run = EffectAsyncRun::new(Rc::downgrade(self));
+            run.start(&mut d.get_fut, rc);
+            d.runs.push(run);
+        } else if self.policy == EffectAsyncPolicy::Exhaust
+            && let Some(latest) = d.runs.last()
+        {
+            latest.rearm(rc);
+        }
+    }
+}
+
+struct EffectAsyncRunData<Fut> {
+    asb: AsyncSourceBinder,
+    fut: Pin<Box<Option<Fut>>>,
+}
+
+struct EffectAsyncRun<GetFut, Fut> {
+    node: Weak<EffectAsyncNode<GetFut, Fut>>,
+    data: RefCell<EffectAsyncRunData<Fut>>,
+}
+impl<GetFut, Fut> EffectAsyncRun<GetFut, Fut>
+where
+    GetFut: FnMut(AsyncSignalContext) -> Fut + 'static,
+    Fut: Future<Output = ()> + 'static,
+{
+    fn new(node: Weak<EffectAsyncNode<GetFut, Fut>>) -> Rc<Self> {
+        Rc::new_cyclic(|this| Self {
+            node,
+            data: RefCell::new(EffectAsyncRunData {
+                asb: AsyncSourceBinder::new(this),
+                fut: Box::pin(None),
+            }),
+        })
+    }
+    fn is_running(&self) -> bool {
+        self.data.borrow().fut.is_some()
+    }
+    fn check(&self, rc: &mut ReactionContext) -> bool {
+        self.data.borrow_mut().asb.check(rc)
+    }
+    fn rearm(&self, rc: &mut ReactionContext) {
+        self.data.borrow_mut().asb.rearm(rc)
+    }
+    // The cancel hooks are called without borrowing `data` because they may access this effect.
+    fn cancel(&self) {
+        let hooks = self.data.borrow_mut().asb.cancel();
+        hooks.run();
+        self.data.borrow_mut().fut.set(None);
+    }
+    fn start(&self, get_fut: &mut GetFut, rc: &mut ReactionContext) {
+        let d = &mut *self.data.borrow_mut();
+        d.fut.set(Some(d.asb.init(get_fut, rc)));
+        d.poll(rc);
+    }
+
+    /// Polls the function if it has been woken, and returns `false` if it has completed.
+    fn poll(&self, rc: &mut ReactionContext) -> bool {
+        let d = &mut *self.data.borrow_mut();
+        if d.asb.is_wake() {
+            d.poll(rc);
         }
-        if let Some(fut) = d.fut.as_mut().as_pin_mut()
-            && d.asb.poll(fut, rc).is_ready()
+        d.fut.is_some()
+    }
+}
+impl<Fut: Future<Output = ()>> EffectAsyncRunData<Fut> {
+    fn poll(&mut self, rc: &mut ReactionContext) {
+        if let Some(fut) = self.fut.as_mut().as_pin_mut()
+            && self.asb.poll(fut, rc).is_ready()
         {
-            d.fut.set(None);
+            self.fut.set(None);
         }
     }
 }
-impl<F, Fut> BindSink for EffectAsyncNode<F, Fut>
+impl<GetFut, Fut> BindSink for EffectAsyncRun<GetFut, Fut>
 where
-    F: FnMut(AsyncSignalContext) -> Fut + 'static,
+    GetFut: FnMut(AsyncSignalContext) -> Fut + 'static,
     Fut: Future<Output = ()> + 'static,
 {
     fn notify(self: Rc<Self>, slot: Slot, level: DirtyLevel, _nc: &mut NotifyContext) {
-        if self.data.borrow_mut().asb.on_notify(slot, level) {
-            self.schedule();
+        if self.data.borrow_mut().asb.on_notify(slot, level)
+            && let Some(node) = self.node.upgrade()
+        {
+            node.schedule();
         }
     }
 }
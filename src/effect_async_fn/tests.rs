@@ -1,8 +1,17 @@
-use std::future::pending;
+use std::{cell::RefCell, collections::HashMap, future::pending, rc::Rc};
 
 use assert_call::{CallRecorder, call};
+use futures::FutureExt;
 
-use crate::{State, core::Runtime, effect_async, utils::test_helpers::call_on_drop};
+use crate::{
+    EffectAsyncPolicy, State, Subscription,
+    core::Runtime,
+    effect_async, effect_async_with,
+    utils::{
+        sync::oneshot_broadcast::{Sender, oneshot_broadcast},
+        test_helpers::call_on_drop,
+    },
+};
 
 #[test]
 fn test_effect_async() {
@@ -84,3 +93,219 @@ fn cancel_on_drop() {
     drop(e);
     cr.verify("drop_10");
 }
+
+#[test]
+fn on_cancel_on_changed() {
+    let mut rt = Runtime::new();
+
+    let mut cr = CallRecorder::new();
+    let s = State::new(10);
+
+    let _e = effect_async({
+        let s = s.to_signal();
+        async move |sc| {
+            let value = sc.with(|sc| s.get(sc));
+            let _on_drop = call_on_drop(format!("drop_{value}"));
+            sc.on_cancel(move || call!("cancel_{value}"));
+            call!("{value}");
+            pending::<()>().await;
+        }
+    });
+    rt.flush();
+    cr.verify("10");
+
+    s.set(20, rt.ac());
+    rt.flush();
+    cr.verify(["cancel_10", "drop_10", "20"]);
+}
+
+#[test]
+fn on_cancel_on_drop() {
+    let mut rt = Runtime::new();
+
+    let mut cr = CallRecorder::new();
+    let e = effect_async(async move |sc| {
+        let _on_drop = call_on_drop("drop");
+        sc.on_cancel(|| call!("cancel"));
+        pending::<()>().await;
+    });
+    rt.flush();
+    cr.verify(());
+
+    drop(e);
+    cr.verify(["cancel", "drop"]);
+}
+
+#[test]
+fn on_cancel_not_called_after_completion() {
+    let mut rt = Runtime::new();
+
+    let mut cr = CallRecorder::new();
+    let s = State::new(10);
+
+    let _e = effect_async({
+        let s = s.to_signal();
+        async move |sc| {
+            let value = sc.with(|sc| s.get(sc));
+            sc.on_cancel(move || call!("cancel_{value}"));
+            call!("{value}");
+        }
+    });
+    rt.flush();
+    cr.verify("10");
+
+    s.set(20, rt.ac());
+    rt.flush();
+    cr.verify("20");
+}
+
+#[test]
+fn cancelled_completes_on_changed() {
+    let mut rt = Runtime::new();
+
+    let s = State::new(10);
+    let cancelled = Rc::new(RefCell::new(Vec::new()));
+
+    let _e = effect_async({
+        let s = s.to_signal();
+        let cancelled = cancelled.clone();
+        async move |sc| {
+            sc.with(|sc| s.get(sc));
+            cancelled.borrow_mut().push(sc.cancelled().boxed());
+            pending::<()>().await;
+        }
+    });
+    rt.flush();
+    assert_eq!(cancelled.borrow_mut()[0].as_mut().now_or_never(), None);
+
+    s.set(20, rt.ac());
+    rt.flush();
+    assert_eq!(cancelled.borrow_mut()[0].as_mut().now_or_never(), Some(()));
+    assert_eq!(cancelled.borrow_mut()[1].as_mut().now_or_never(), None);
+}
+
+struct Gates(Rc<RefCell<HashMap<i32, Sender<()>>>>);
+
+impl Gates {
+    fn open(&self, value: i32) {
+        self.0.borrow()[&value].send(());
+    }
+}
+
+fn effect_async_with_gates(
+    policy: EffectAsyncPolicy,
+    s: &State<i32>,
+    values: impl IntoIterator<Item = i32>,
+) -> (Subscription, Gates) {
+    let mut senders = HashMap::new();
+    let mut receivers = HashMap::new();
+    for value in values {
+        let (sender, receiver) = oneshot_broadcast();
+        senders.insert(value, sender);
+        receivers.insert(value, receiver);
+    }
+    let s = s.to_signal();
+    let e = effect_async_with(policy, async move |sc| {
+        let value = sc.with(|sc| s.get(sc));
+        call!("start_{value}");
+        receivers[&value].recv().await;
+        call!("end_{value}");
+    });
+    (e, Gates(Rc::new(RefCell::new(senders))))
+}
+
+#[test]
+fn policy_queue() {
+    let mut rt = Runtime::new();
+    let mut cr = CallRecorder::new();
+    let s = State::new(10);
+    let (_e, gates) = effect_async_with_gates(EffectAsyncPolicy::Queue, &s, [10, 20, 30]);
+
+    rt.flush();
+    cr.verify("start_10");
+
+    s.set(20, rt.ac());
+    rt.flush();
+    s.set(30, rt.ac());
+    rt.flush();
+    cr.verify(());
+
+    gates.open(10);
+    rt.flush();
+    cr.verify(["end_10", "start_30"]);
+
+    gates.open(30);
+    rt.flush();
+    cr.verify("end_30");
+}
+
+#[test]
+fn policy_exhaust() {
+    let mut rt = Runtime::new();
+    let mut cr = CallRecorder::new();
+    let s = State::new(10);
+    let (_e, gates) = effect_async_with_gates(EffectAsyncPolicy::Exhaust, &s, [10, 20, 30]);
+
+    rt.flush();
+    cr.verify("start_10");
+
+    s.set(20, rt.ac());
+    rt.flush();
+    cr.verify(());
+
+    gates.open(10);
+    rt.flush();
+    cr.verify("end_10");
+
+    s.set(30, rt.ac());
+    rt.flush();
+    cr.verify("start_30");
+}
+
+#[test]
+fn policy_merge() {
+    let mut rt = Runtime::new();
+    let mut cr = CallRecorder::new();
+    let s = State::new(10);
+    let (_e, gates) = effect_async_with_gates(EffectAsyncPolicy::Merge(2), &s, [10, 20, 30]);
+
+    rt.flush();
+    cr.verify("start_10");
+
+    s.set(20, rt.ac());
+    rt.flush();
+    cr.verify("start_20");
+
+    s.set(30, rt.ac());
+    rt.flush();
+    cr.verify(());
+
+    gates.open(20);
+    rt.flush();
+    cr.verify(["end_20", "start_30"]);
+
+    gates.open(10);
+    gates.open(30);
+    rt.flush();
+    cr.verify(["end_10", "end_30"]);
+}
+
+#[test]
+fn policy_switch() {
+    let mut rt = Runtime::new();
+    let mut cr = CallRecorder::new();
+    let s = State::new(10);
+    let (_e, gates) = effect_async_with_gates(EffectAsyncPolicy::Switch, &s, [10, 20]);
+
+    rt.flush();
+    cr.verify("start_10");
+
+    s.set(20, rt.ac());
+    rt.flush();
+    cr.verify("start_20");
+
+    gates.open(10);
+    gates.open(20);
+    rt.flush();
+    cr.verify("end_20");
+}
@@ -5,10 +5,14 @@ mod effect_async_fn;
 mod effect_fn;
 #[doc(hidden)]
 pub mod fmt;
+mod query_client;
+mod resource;
 pub mod signal;
 pub mod state;
 mod stream;
 mod subscription;
+mod suspense;
+mod transition;
 pub mod utils;
 
 #[cfg(doctest)]
@@ -17,10 +21,11 @@ pub mod tests_readme {}
 
 #[doc(inline)]
 pub use crate::core::{
-    Action, ActionContext, ActionContextChannel, ActionPhase, AsyncActionContext,
-    AsyncSignalContext, Reaction, ReactionContextChannel, ReactionPhase, SignalContext,
-    SignalContextChannel, StateRef, StateRefBuilder, spawn_action, spawn_action_async,
-    spawn_action_async_in, spawn_action_in,
+    Action, ActionContext, ActionContextChannel, ActionHandle, ActionPhase, ActionQueue,
+    ActionStatus, AsyncActionContext, AsyncSignalContext, Reaction, ReactionContextChannel,
+    ReactionPhase, SignalContext, SignalContextChannel, StateRef, StateRefBuilder, spawn_action,
+    spawn_action_async, spawn_action_async_in, spawn_action_async_with_handle,
+    spawn_action_async_with_handle_in, spawn_action_in,
 };
 
 #[doc(inline)]
@@ -31,5 +36,9 @@ pub use crate::state::State;
 
 pub use crate::effect_async_fn::*;
 pub use crate::effect_fn::*;
+pub use crate::query_client::*;
+pub use crate::resource::*;
 pub use crate::stream::*;
 pub use crate::subscription::*;
+pub use crate::suspense::*;
+pub use crate::transition::*;
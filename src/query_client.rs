@@ -0,0 +1,264 @@
+use std::{
+    cell::{Cell, RefCell},
+    collections::HashMap,
+    future::Future,
+    hash::Hash,
+    pin::Pin,
+    rc::{Rc, Weak},
+    task::Poll,
+};
+
+use derive_ex::derive_ex;
+
+use crate::{
+    ActionContext, Signal, SignalBuilder, State, core::spawn_action_async, signal::KeepPrevious,
+};
+
+#[cfg(test)]
+mod tests;
+
+type Fetcher<K, T, E> = Rc<dyn Fn(K) -> Pin<Box<dyn Future<Output = Result<T, E>>>>>;
+type GcTimer = Rc<dyn Fn() -> Pin<Box<dyn Future<Output = ()>>>>;
+
+/// A cache of values fetched by an asynchronous function, keyed by `K`.
+///
+/// Signals returned by [`query`](Self::query) with the same key share one entry,
+/// and the value is fetched once for all of them.
+/// A fetch starts when the value of an entry is observed and the entry has no value or is invalidated.
+/// While an invalidated entry is fetched again, the signal keeps the previous value.
+///
+/// When no signal of an entry is observed, the entry is removed.
+/// Use [`with_gc_timer`](Self::with_gc_timer) to keep unobserved entries for a while.
+#[derive_ex(Clone(bound()))]
+pub struct QueryClient<K: 'static, T: 'static, E: 'static>(Rc<QueryClientData<K, T, E>>);
+
+impl<K, T, E> QueryClient<K, T, E>
+where
+    K: Eq + Hash + Clone + 'static,
+    T: Clone + 'static,
+    E: Clone + 'static,
+{
+    /// Creates a client that fetches values with `fetcher`.
+    pub fn new(fetcher: impl AsyncFn(K) -> Result<T, E> + 'static) -> Self {
+        let fetcher = Rc::new(fetcher);
+        Self(Rc::new(QueryClientData {
+            fetcher: Rc::new(move |key| {
+                let fetcher = fetcher.clone();
+                Box::pin(async move { fetcher(key).await })
+            }),
+            gc_timer: RefCell::new(None),
+            entries: RefCell::new(HashMap::new()),
+        }))
+    }
+
+    /// Keeps an unobserved entry until the future returned by `timer` completes.
+    ///
+    /// If the entry is observed again before that, it is not removed.
+    /// The timer is shared with the clones of this client.
+    pub fn with_gc_timer<Fut>(self, timer: impl Fn() -> Fut + 'static) -> Self
+    where
+        Fut: Future<Output = ()> + 'static,
+    {
+        *self.0.gc_timer.borrow_mut() = Some(Rc::new(move || Box::pin(timer())));
+        self
+    }
+
+    /// Returns a signal with the value for `key`.
+    pub fn query(&self, key: K) -> Signal<Poll<Result<T, E>>> {
+        let this = Rc::downgrade(&self.0);
+        SignalBuilder::from_scan((None, Poll::Pending), move |st, sc| {
+            if st.0.is_none()
+                && let Some(this) = this.upgrade()
+            {
+                st.0 = Some(QueryObserver::new(&this, key.clone()));
+            }
+            if let Some(observer) = &st.0 {
+                st.1 = observer.entry.value.borrow(sc).value.clone();
+            }
+        })
+        .on_discard(|st| st.0 = None)
+        .map(|st| &st.1)
+        .build()
+    }
+
+    /// Marks the entry for `key` as stale.
+    ///
+    /// If the entry is observed, it is fetched again and a running fetch is cancelled.
+    /// Otherwise, it is fetched again when it is observed next time.
+    pub fn invalidate(&self, key: &K, ac: &mut ActionContext) {
+        let entry = self.0.entries.borrow().get(key).cloned();
+        if let Some(entry) = entry {
+            entry.invalidate(ac);
+        }
+    }
+
+    /// Marks the entries whose keys start with `prefix` as stale.
+    ///
+    /// See [`invalidate`](Self::invalidate) for details.
+    pub fn invalidate_prefix<S: PartialEq>(&self, prefix: &[S], ac: &mut ActionContext)
+    where
+        K: AsRef<[S]>,
+    {
+        let entries: Vec<_> = self
+            .0
+            .entries
+            .borrow()
+            .iter()
+            .filter(|(key, _)| key.as_ref().starts_with(prefix))
+            .map(|(_, entry)| entry.clone())
+            .collect();
+        for entry in entries {
+            entry.invalidate(ac);
+        }
+    }
+
+    /// Sets the value for `key` and cancels a running fetch.
+    ///
+    /// Used for optimistic updates. The value is replaced when the entry is invalidated and fetched again.
+    /// If the entry is not observed, it is removed in the same way as other unobserved entries.
+    pub fn set_query_data(&self, key: K, value: T, ac: &mut ActionContext) {
+        let entry = self.0.entry(&key);
+        entry.data.set(Some(value), ac);
+        if entry.observers.get() == 0 {
+            self.0.schedule_gc(&key, &entry);
+        }
+    }
+}
+
+struct QueryClientData<K: 'static, T: 'static, E: 'static> {
+    fetcher: Fetcher<K, T, E>,
+    gc_timer: RefCell<Option<GcTimer>>,
+    entries: RefCell<HashMap<K, Rc<QueryEntry<T, E>>>>,
+}
+
+impl<K, T, E> QueryClientData<K, T, E>
+where
+    K: Eq + Hash + Clone + 'static,
+    T: Clone + 'static,
+    E: Clone + 'static,
+{
+    fn entry(&self, key: &K) -> Rc<QueryEntry<T, E>> {
+        if let Some(entry) = self.entries.borrow().get(key) {
+            return entry.clone();
+        }
+        let entry = Rc::new(QueryEntry::new(self.fetcher.clone(), key.clone()));
+        self.entries.borrow_mut().insert(key.clone(), entry.clone());
+        entry
+    }
+    fn schedule_gc(self: &Rc<Self>, key: &K, entry: &Rc<QueryEntry<T, E>>) {
+        let Some(gc_timer) = self.gc_timer.borrow().clone() else {
+            self.remove_if_unused(key, entry);
+            return;
+        };
+        let generation = entry.gc_generation.get();
+        let this = Rc::downgrade(self);
+        let key = key.clone();
+        let entry = entry.clone();
+        spawn_action_async(async move |_| {
+            gc_timer().await;
+            if entry.gc_generation.get() == generation
+                && let Some(this) = this.upgrade()
+            {
+                this.remove_if_unused(&key, &entry);
+            }
+        });
+    }
+    fn remove_if_unused(&self, key: &K, entry: &Rc<QueryEntry<T, E>>) {
+        let mut entries = self.entries.borrow_mut();
+        if entry.observers.get() == 0 && entries.get(key).is_some_and(|e| Rc::ptr_eq(e, entry)) {
+            entries.remove(key);
+        }
+    }
+}
+
+struct QueryEntry<T: 'static, E: 'static> {
+    value: Signal<KeepPrevious<Result<T, E>>>,
+    data: State<Option<T>>,
+    invalidations: State<usize>,
+    observers: Cell<usize>,
+    gc_generation: Cell<usize>,
+}
+
+impl<T, E> QueryEntry<T, E>
+where
+    T: Clone + 'static,
+    E: Clone + 'static,
+{
+    fn new<K: Clone + 'static>(fetcher: Fetcher<K, T, E>, key: K) -> Self {
+        let data = State::new(None);
+        let invalidations = State::new(0);
+        let value = SignalBuilder::from_async_keep_previous({
+            let data = data.clone();
+            let invalidations = invalidations.clone();
+            async move |sc| {
+                let data = sc.with(|sc| {
+                    invalidations.get(sc);
+                    data.get(sc)
+                });
+                match data {
+                    Some(value) => Ok(value),
+                    None => fetcher(key.clone()).await,
+                }
+            }
+        })
+        .build()
+        .keep();
+        Self {
+            value,
+            data,
+            invalidations,
+            observers: Cell::new(0),
+            gc_generation: Cell::new(0),
+        }
+    }
+    fn invalidate(&self, ac: &mut ActionContext) {
+        self.data.set(None, ac);
+        *self.invalidations.borrow_mut(ac) += 1;
+    }
+}
+
+/// Keeps an entry while a signal returned by [`QueryClient::query`] is observed.
+struct QueryObserver<K, T, E>
+where
+    K: Eq + Hash + Clone + 'static,
+    T: Clone + 'static,
+    E: Clone + 'static,
+{
+    client: Weak<QueryClientData<K, T, E>>,
+    key: K,
+    entry: Rc<QueryEntry<T, E>>,
+}
+
+impl<K, T, E> QueryObserver<K, T, E>
+where
+    K: Eq + Hash + Clone + 'static,
+    T: Clone + 'static,
+    E: Clone + 'static,
+{
+    fn new(client: &Rc<QueryClientData<K, T, E>>, key: K) -> Self {
+        let entry = client.entry(&key);
+        entry.observers.set(entry.observers.get() + 1);
+        entry.gc_generation.set(entry.gc_generation.get() + 1);
+        Self {
+            client: Rc::downgrade(client),
+            key,
+            entry,
+        }
+    }
+}
+impl<K, T, E> Drop for QueryObserver<K, T, E>
+where
+    K: Eq + Hash + Clone + 'static,
+    T: Clone + 'static,
+    E: Clone + 'static,
+{
+    fn drop(&mut self) {
+        let observers = self.entry.observers.get() - 1;
+        self.entry.observers.set(observers);
+        if observers == 0
+            && let Some(client) = self.client.upgrade()
+        {
+            client.schedule_gc(&self.key, &self.entry);
+        }
+    }
+}
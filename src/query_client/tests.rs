@@ -0,0 +1,253 @@
+use std::{cell::RefCell, rc::Rc};
+
+use pretty_assertions::assert_eq;
+
+use super::*;
+use crate::{
+    Subscription,
+    core::Runtime,
+    effect,
+    utils::sync::oneshot_broadcast::{Receiver, Sender, oneshot_broadcast},
+};
+
+type Key = Vec<&'static str>;
+type Requests = Rc<RefCell<Vec<(Key, Sender<Result<String, String>>)>>>;
+
+fn client() -> (QueryClient<Key, String, String>, Requests) {
+    let requests = Requests::default();
+    let client = QueryClient::new({
+        let requests = requests.clone();
+        async move |key: Key| {
+            let (sender, receiver) = oneshot_broadcast();
+            requests.borrow_mut().push((key, sender));
+            receiver.recv().await
+        }
+    });
+    (client, requests)
+}
+
+fn respond(requests: &Requests, value: &str) -> Key {
+    let (key, sender) = requests.borrow_mut().remove(0);
+    sender.send(Ok(value.to_string()));
+    key
+}
+
+fn observe(client: &QueryClient<Key, String, String>, key: Key) -> Subscription {
+    let query = client.query(key);
+    effect(move |sc| {
+        let _ = query.get(sc);
+    })
+}
+
+fn ready(value: &str) -> Poll<Result<String, String>> {
+    Poll::Ready(Ok(value.to_string()))
+}
+
+#[test]
+fn query_shares_fetch_between_subscribers() {
+    let mut rt = Runtime::new();
+    let (client, requests) = client();
+    let _e0 = observe(&client, vec!["user", "1"]);
+    let _e1 = observe(&client, vec!["user", "1"]);
+
+    rt.flush();
+    assert_eq!(requests.borrow().len(), 1);
+    assert_eq!(
+        client.query(vec!["user", "1"]).get(&mut rt.sc()),
+        Poll::Pending
+    );
+
+    respond(&requests, "alice");
+    rt.flush();
+    assert_eq!(
+        client.query(vec!["user", "1"]).get(&mut rt.sc()),
+        ready("alice")
+    );
+    assert_eq!(requests.borrow().len(), 0);
+}
+
+#[test]
+fn invalidate_refetches_and_keeps_previous_value() {
+    let mut rt = Runtime::new();
+    let (client, requests) = client();
+    let query = client.query(vec!["user", "1"]);
+    let _e = observe(&client, vec!["user", "1"]);
+    rt.flush();
+    respond(&requests, "alice");
+    rt.flush();
+
+    client.invalidate(&vec!["user", "1"], rt.ac());
+    rt.flush();
+    assert_eq!(requests.borrow().len(), 1);
+    assert_eq!(query.get(&mut rt.sc()), ready("alice"));
+
+    respond(&requests, "bob");
+    rt.flush();
+    assert_eq!(query.get(&mut rt.sc()), ready("bob"));
+}
+
+#[test]
+fn invalidate_prefix_refetches_matching_keys() {
+    let mut rt = Runtime::new();
+    let (client, requests) = client();
+    let _e0 = observe(&client, vec!["user", "1"]);
+    let _e1 = observe(&client, vec!["user", "2"]);
+    let _e2 = observe(&client, vec!["post", "1"]);
+    rt.flush();
+    for _ in 0..3 {
+        respond(&requests, "value");
+    }
+    rt.flush();
+
+    client.invalidate_prefix(&["user"], rt.ac());
+    rt.flush();
+    let mut keys: Vec<_> = requests
+        .borrow()
+        .iter()
+        .map(|(key, _)| key.clone())
+        .collect();
+    keys.sort();
+    assert_eq!(keys, [vec!["user", "1"], vec!["user", "2"]]);
+}
+
+#[test]
+fn set_query_data_cancels_fetch() {
+    let mut rt = Runtime::new();
+    let (client, requests) = client();
+    let query = client.query(vec!["user", "1"]);
+    let _e = observe(&client, vec!["user", "1"]);
+    rt.flush();
+
+    client.set_query_data(vec!["user", "1"], "optimistic".to_string(), rt.ac());
+    rt.flush();
+    assert_eq!(query.get(&mut rt.sc()), ready("optimistic"));
+
+    respond(&requests, "fetched");
+    rt.flush();
+    assert_eq!(query.get(&mut rt.sc()), ready("optimistic"));
+}
+
+#[test]
+fn unobserved_entry_is_removed() {
+    let mut rt = Runtime::new();
+    let (client, requests) = client();
+    let e = observe(&client, vec!["user", "1"]);
+    rt.flush();
+    respond(&requests, "alice");
+    rt.flush();
+
+    drop(e);
+    rt.flush();
+    let _e = observe(&client, vec!["user", "1"]);
+    rt.flush();
+    assert_eq!(requests.borrow().len(), 1);
+    assert_eq!(
+        client.query(vec!["user", "1"]).get(&mut rt.sc()),
+        Poll::Pending
+    );
+}
+
+#[test]
+fn gc_timer_keeps_unobserved_entry() {
+    let mut rt = Runtime::new();
+    let (client, requests) = client();
+    let timers = Rc::new(RefCell::new(Vec::<Sender<()>>::new()));
+    let client = client.with_gc_timer({
+        let timers = timers.clone();
+        move || {
+            let (sender, receiver): (_, Receiver<()>) = oneshot_broadcast();
+            timers.borrow_mut().push(sender);
+            async move { receiver.recv().await }
+        }
+    });
+    let e = observe(&client, vec!["user", "1"]);
+    rt.flush();
+    respond(&requests, "alice");
+    rt.flush();
+
+    drop(e);
+    rt.flush();
+    let e = observe(&client, vec!["user", "1"]);
+    rt.flush();
+    assert_eq!(requests.borrow().len(), 0);
+    assert_eq!(
+        client.query(vec!["user", "1"]).get(&mut rt.sc()),
+        ready("alice")
+    );
+    timers.borrow()[0].send(());
+    rt.flush();
+    assert_eq!(
+        client.query(vec!["user", "1"]).get(&mut rt.sc()),
+        ready("alice")
+    );
+
+    drop(e);
+    rt.flush();
+    timers.borrow()[1].send(());
+    rt.flush();
+    let _e = observe(&client, vec!["user", "1"]);
+    rt.flush();
+    assert_eq!(requests.borrow().len(), 1);
+}
+
+#[test]
+fn entry_is_kept_while_another_signal_is_observed() {
+    let mut rt = Runtime::new();
+    let (client, requests) = client();
+    let e0 = observe(&client, vec!["user", "1"]);
+    let _e1 = observe(&client, vec!["user", "1"]);
+    rt.flush();
+    respond(&requests, "alice");
+    rt.flush();
+
+    drop(e0);
+    rt.flush();
+    assert_eq!(client.0.entries.borrow().len(), 1);
+    assert_eq!(
+        client.query(vec!["user", "1"]).get(&mut rt.sc()),
+        ready("alice")
+    );
+    assert_eq!(requests.borrow().len(), 0);
+}
+
+#[test]
+fn signal_held_across_gc_shares_new_entry() {
+    let mut rt = Runtime::new();
+    let (client, requests) = client();
+    let query = client.query(vec!["user", "1"]);
+    let e = query.effect(|_| {});
+    rt.flush();
+    respond(&requests, "alice");
+    rt.flush();
+    drop(e);
+    rt.flush();
+    assert_eq!(client.0.entries.borrow().len(), 0);
+
+    let _e0 = query.effect(|_| {});
+    let _e1 = observe(&client, vec!["user", "1"]);
+    rt.flush();
+    assert_eq!(requests.borrow().len(), 1);
+    respond(&requests, "bob");
+    rt.flush();
+    assert_eq!(query.get(&mut rt.sc()), ready("bob"));
+    assert_eq!(
+        client.query(vec!["user", "1"]).get(&mut rt.sc()),
+        ready("bob")
+    );
+}
+
+#[test]
+fn set_query_data_on_unobserved_key_is_removed() {
+    let mut rt = Runtime::new();
+    let (client, _requests) = client();
+    client.set_query_data(vec!["user", "1"], "alice".to_string(), rt.ac());
+    rt.flush();
+    assert_eq!(client.0.entries.borrow().len(), 0);
+}
+
+#[test]
+fn with_gc_timer_after_clone() {
+    let (client, _requests) = client();
+    let _clone = client.clone();
+    let _client = client.with_gc_timer(|| async {});
+}
@@ -0,0 +1,129 @@
+use std::{cell::Cell, rc::Rc, task::Poll};
+
+use derive_ex::derive_ex;
+
+use crate::{ActionContext, AsyncSignalContext, Signal, State};
+
+#[cfg(test)]
+mod tests;
+
+/// The state of a [`Resource`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub enum ResourceState<T, E> {
+    /// The value is being fetched.
+    Loading,
+    /// The value has been fetched or set by [`Resource::mutate`].
+    Ready(T),
+    /// Fetching the value has failed.
+    Error(E),
+}
+
+impl<T, E> ResourceState<T, E> {
+    pub fn is_loading(&self) -> bool {
+        matches!(self, Self::Loading)
+    }
+    pub fn ready(&self) -> Option<&T> {
+        match self {
+            Self::Ready(value) => Some(value),
+            _ => None,
+        }
+    }
+    pub fn error(&self) -> Option<&E> {
+        match self {
+            Self::Error(error) => Some(error),
+            _ => None,
+        }
+    }
+}
+
+/// A value fetched by an asynchronous function, with loading and error states.
+///
+/// The value is fetched again when a dependency of the fetcher changes or when
+/// [`refetch`](Self::refetch) is called. While a fetch is in progress, the state is
+/// [`Loading`](ResourceState::Loading), unless a value set by [`mutate`](Self::mutate) is shown.
+///
+/// Like [`Signal::from_async`], fetching runs only while the state has dependants.
+#[derive_ex(Clone(bound()))]
+pub struct Resource<T: 'static, E: 'static> {
+    state: Signal<ResourceState<T, E>>,
+    refetches: State<usize>,
+    fetches: Rc<Cell<usize>>,
+    mutated: State<Option<(T, usize)>>,
+}
+
+impl<T, E> Resource<T, E>
+where
+    T: Clone + 'static,
+    E: Clone + 'static,
+{
+    /// Creates a resource that fetches a value from the value of `source`.
+    ///
+    /// Only `source` is tracked; `fetcher` is called again each time `source` changes.
+    pub fn new<S: Clone + 'static>(
+        source: Signal<S>,
+        fetcher: impl AsyncFn(S) -> Result<T, E> + 'static,
+    ) -> Self {
+        Self::from_async(async move |sc| {
+            let source = sc.with(|sc| source.get(sc));
+            fetcher(source).await
+        })
+    }
+
+    /// Creates a resource from an asynchronous function that tracks its own dependencies.
+    pub fn from_async(f: impl AsyncFn(&mut AsyncSignalContext) -> Result<T, E> + 'static) -> Self {
+        let refetches = State::new(0);
+        let fetches = Rc::new(Cell::new(0));
+        let mutated = State::new(None::<(T, usize)>);
+        let fetched = Signal::from_async({
+            let refetches = refetches.clone();
+            let fetches = fetches.clone();
+            async move |sc| {
+                sc.with(|sc| refetches.get(sc));
+                let fetch = fetches.get() + 1;
+                fetches.set(fetch);
+                (fetch, f(sc).await)
+            }
+        });
+        let state = Signal::new({
+            let mutated = mutated.clone();
+            move |sc| {
+                let fetched = fetched.borrow(sc);
+                if let Some((value, fetch)) = &*mutated.borrow(sc)
+                    && !matches!(&*fetched, Poll::Ready((f, _)) if f > fetch)
+                {
+                    return ResourceState::Ready(value.clone());
+                }
+                match &*fetched {
+                    Poll::Pending => ResourceState::Loading,
+                    Poll::Ready((_, Ok(value))) => ResourceState::Ready(value.clone()),
+                    Poll::Ready((_, Err(error))) => ResourceState::Error(error.clone()),
+                }
+            }
+        });
+        Self {
+            state,
+            refetches,
+            fetches,
+            mutated,
+        }
+    }
+
+    /// Returns a signal with the state of this resource.
+    pub fn state(&self) -> Signal<ResourceState<T, E>> {
+        self.state.clone()
+    }
+
+    /// Fetches the value again without a change of the dependencies.
+    pub fn refetch(&self, ac: &mut ActionContext) {
+        *self.refetches.borrow_mut(ac) += 1;
+    }
+
+    /// Replaces the value with `value` until a fetch that starts after this call completes.
+    ///
+    /// Used for optimistic updates: the state is [`Ready`](ResourceState::Ready) with `value`
+    /// immediately, and a later fetch, such as one started by [`refetch`](Self::refetch),
+    /// replaces it with the fetched value.
+    pub fn mutate(&self, ac: &mut ActionContext, value: T) {
+        self.mutated.set(Some((value, self.fetches.get())), ac);
+    }
+}
@@ -0,0 +1,117 @@
+use std::{cell::RefCell, rc::Rc};
+
+use pretty_assertions::assert_eq;
+
+use super::*;
+use crate::{
+    core::Runtime,
+    effect,
+    utils::sync::oneshot_broadcast::{self, Sender},
+};
+
+type Requests = Rc<RefCell<Vec<(i32, Sender<Result<String, String>>)>>>;
+
+fn fetch_resource(source: &State<i32>) -> (Resource<String, String>, Requests) {
+    let requests = Requests::default();
+    let resource = Resource::new(source.to_signal(), {
+        let requests = requests.clone();
+        async move |id| {
+            let (sender, receiver) = oneshot_broadcast::oneshot_broadcast();
+            requests.borrow_mut().push((id, sender));
+            receiver.recv().await
+        }
+    });
+    (resource, requests)
+}
+
+fn respond(requests: &Requests, result: Result<&str, &str>) -> i32 {
+    let (id, sender) = requests.borrow_mut().remove(0);
+    sender.send(result.map(String::from).map_err(String::from));
+    id
+}
+
+fn ready(value: &str) -> ResourceState<String, String> {
+    ResourceState::Ready(value.to_string())
+}
+
+#[test]
+fn resource_fetches_from_source() {
+    let mut rt = Runtime::new();
+    let source = State::new(1);
+    let (resource, requests) = fetch_resource(&source);
+    let state = resource.state();
+    let _e = effect({
+        let state = state.clone();
+        move |sc| {
+            state.get(sc);
+        }
+    });
+    rt.flush();
+    assert_eq!(state.get(&mut rt.sc()), ResourceState::Loading);
+
+    assert_eq!(respond(&requests, Ok("a")), 1);
+    rt.flush();
+    assert_eq!(state.get(&mut rt.sc()), ready("a"));
+
+    source.set(2, rt.ac());
+    rt.flush();
+    assert_eq!(state.get(&mut rt.sc()), ResourceState::Loading);
+    assert_eq!(respond(&requests, Err("failed")), 2);
+    rt.flush();
+    assert_eq!(
+        state.get(&mut rt.sc()),
+        ResourceState::Error("failed".to_string())
+    );
+}
+
+#[test]
+fn resource_refetch_fetches_again() {
+    let mut rt = Runtime::new();
+    let source = State::new(1);
+    let (resource, requests) = fetch_resource(&source);
+    let state = resource.state();
+    let _e = effect({
+        let state = state.clone();
+        move |sc| {
+            state.get(sc);
+        }
+    });
+    rt.flush();
+    respond(&requests, Ok("a"));
+    rt.flush();
+
+    resource.refetch(rt.ac());
+    rt.flush();
+    assert_eq!(state.get(&mut rt.sc()), ResourceState::Loading);
+    assert_eq!(respond(&requests, Ok("b")), 1);
+    rt.flush();
+    assert_eq!(state.get(&mut rt.sc()), ready("b"));
+}
+
+#[test]
+fn resource_mutate_overrides_until_a_later_fetch_completes() {
+    let mut rt = Runtime::new();
+    let source = State::new(1);
+    let (resource, requests) = fetch_resource(&source);
+    let state = resource.state();
+    let _e = effect({
+        let state = state.clone();
+        move |sc| {
+            state.get(sc);
+        }
+    });
+    rt.flush();
+
+    resource.mutate(rt.ac(), "optimistic".to_string());
+    assert_eq!(state.get(&mut rt.sc()), ready("optimistic"));
+    respond(&requests, Ok("stale"));
+    rt.flush();
+    assert_eq!(state.get(&mut rt.sc()), ready("optimistic"));
+
+    resource.refetch(rt.ac());
+    rt.flush();
+    assert_eq!(state.get(&mut rt.sc()), ready("optimistic"));
+    respond(&requests, Ok("fresh"));
+    rt.flush();
+    assert_eq!(state.get(&mut rt.sc()), ready("fresh"));
+}
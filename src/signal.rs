@@ -1,6 +1,8 @@
 mod builder;
+mod poll_signals;
 mod scan_async;
 mod signal_t;
 
-pub use builder::SignalBuilder;
+pub use builder::{KeepPrevious, SignalBuilder};
+pub use poll_signals::PollSignals;
 pub use signal_t::*;
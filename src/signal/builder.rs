@@ -1,18 +1,24 @@
 use std::{
     cell::{Cell, RefCell},
     future::Future,
+    mem,
+    ops::AsyncFn,
+    rc::Rc,
+    task::Poll,
 };
 
 use futures::Stream;
 
-use crate::{Signal, SignalContext, StateRef, StateRefBuilder, core::SinkBindings};
+use crate::{
+    AsyncSignalContext, Signal, SignalContext, StateRef, StateRefBuilder, core::SinkBindings,
+};
 
 use self::{
     future_scan::future_scan_builder, get::get_builder, scan::scan_builder,
     stream_scan::stream_scan_builder,
 };
 
-use super::SignalNode;
+use super::{SignalNode, scan_async::scan_async_builder};
 
 mod future_scan;
 mod get;
@@ -62,6 +68,47 @@ impl SignalBuilder<()> {
     ) -> SignalBuilder<impl Build<State = St>> {
         SignalBuilder(stream_scan_builder(initial_state, stream, f))
     }
+
+    /// Creates a signal like [`Signal::from_async`] that keeps the last `Ready` value while the
+    /// asynchronous function runs again after a dependency change.
+    ///
+    /// The value is `Pending` only until the first call completes. While a later call is running,
+    /// [`KeepPrevious::is_refreshing`] is `true`.
+    pub fn from_async_keep_previous<T: 'static>(
+        f: impl AsyncFn(&mut AsyncSignalContext) -> T + 'static,
+    ) -> SignalBuilder<impl Build<State = KeepPrevious<T>>> {
+        let f = Rc::new(f);
+        SignalBuilder(scan_async_builder(
+            || KeepPrevious {
+                value: Poll::Pending,
+                is_refreshing: false,
+            },
+            move |mut sc| {
+                let f = f.clone();
+                async move { f(&mut sc).await }
+            },
+            |st, poll| match poll {
+                Poll::Ready(value) => {
+                    st.value = Poll::Ready(value);
+                    st.is_refreshing = false;
+                    true
+                }
+                Poll::Pending => {
+                    let is_refreshing = st.value.is_ready();
+                    !mem::replace(&mut st.is_refreshing, is_refreshing) && is_refreshing
+                }
+            },
+        ))
+    }
+}
+
+/// The value of a signal created by [`SignalBuilder::from_async_keep_previous`].
+#[derive(Clone, Copy, Debug, Eq, PartialEq, Hash)]
+pub struct KeepPrevious<T> {
+    /// The value of the last completed call, or `Pending` if no call has completed yet.
+    pub value: Poll<T>,
+    /// Whether a call is running while the previous value is kept.
+    pub is_refreshing: bool,
 }
 impl<B: GetBuild> SignalBuilder<B>
 where
@@ -213,7 +260,7 @@ impl<St, F: Fn(&mut St)> DiscardFn<St> for DiscardFnVoid<F> {
     }
 }
 
-trait MapFn<Input: ?Sized> {
+pub(super) trait MapFn<Input: ?Sized> {
     type Output: ?Sized;
     fn apply<'a, 'r: 'a>(
         &self,
@@ -222,7 +269,7 @@ trait MapFn<Input: ?Sized> {
     ) -> StateRef<'a, Self::Output>;
 }
 
-struct MapFnNone;
+pub(super) struct MapFnNone;
 
 impl<Input> MapFn<Input> for MapFnNone
 where
@@ -239,9 +286,9 @@ where
     }
 }
 
-struct MapFnRaw<M, F> {
-    m: M,
-    f: F,
+pub(super) struct MapFnRaw<M, F> {
+    pub m: M,
+    pub f: F,
 }
 
 impl<Input, Output, M, F> MapFn<Input> for MapFnRaw<M, F>
@@ -1,6 +1,9 @@
-use crate::{Signal, SignalBuilder, SignalContext, StateRef};
+use crate::{Signal, SignalContext, StateRef};
 
-use super::{Build, DedupBuild, GetBuild, ScanBuild};
+use super::{
+    Build, DedupBuild, GetBuild, ScanBuild, ScanFnBool, ScanFnVoid,
+    scan::{ScanFnGet, scan_builder},
+};
 
 pub fn get_builder<T: 'static>(
     get: impl Fn(&mut SignalContext<'_, '_>) -> T + 'static,
@@ -29,10 +32,14 @@ where
 {
     type Output = T;
     fn into_scan_build(self) -> impl ScanBuild<State = Option<Self::Output>> {
-        SignalBuilder::from_scan(None, move |st, sc| {
-            *st = Some((self.0)(sc));
-        })
-        .0
+        scan_builder(
+            None,
+            ScanFnGet(ScanFnVoid(
+                move |st: &mut Option<T>, sc: &mut SignalContext| {
+                    *st = Some((self.0)(sc));
+                },
+            )),
+        )
     }
 }
 
@@ -46,17 +53,21 @@ where
 {
     type Output = T;
     fn into_scan_build(self) -> impl ScanBuild<State = Option<Self::Output>> {
-        SignalBuilder::from_scan_filter(None, move |st, sc| {
-            let value = (self.0)(sc);
-            if let Some(old) = st
-                && old == &value
-            {
-                return false;
-            }
-            *st = Some(value);
-            true
-        })
-        .0
+        scan_builder(
+            None,
+            ScanFnGet(ScanFnBool(
+                move |st: &mut Option<T>, sc: &mut SignalContext| {
+                    let value = (self.0)(sc);
+                    if let Some(old) = st
+                        && old == &value
+                    {
+                        return false;
+                    }
+                    *st = Some(value);
+                    true
+                },
+            )),
+        )
     }
 }
 
@@ -35,6 +35,26 @@ where
 pub(super) trait ScanFn<St> {
     const FILTER: bool;
     fn call(&mut self, st: &mut St, sc: &mut SignalContext<'_, '_>) -> bool;
+
+    /// Returns the state from which this function is evaluated when the signal is read in the
+    /// fork of a transition, or `None` if the signal returns its state outside the fork there.
+    fn fork_state(&self) -> Option<St> {
+        None
+    }
+}
+
+/// A function that computes the whole state from its dependencies each time it is called,
+/// so it can be evaluated from scratch in the fork of a transition.
+pub(super) struct ScanFnGet<S>(pub S);
+
+impl<T, S: ScanFn<Option<T>>> ScanFn<Option<T>> for ScanFnGet<S> {
+    const FILTER: bool = S::FILTER;
+    fn call(&mut self, st: &mut Option<T>, sc: &mut SignalContext<'_, '_>) -> bool {
+        self.0.call(st, sc)
+    }
+    fn fork_state(&self) -> Option<Option<T>> {
+        Some(None)
+    }
 }
 
 impl<St, F: FnMut(&mut St, &mut SignalContext<'_, '_>)> ScanFn<St> for ScanFnVoid<F> {
@@ -153,6 +173,11 @@ where
         rc_self: Rc<dyn Any>,
         sc: &mut SignalContext<'r, '_>,
     ) -> StateRef<'a, Self::Value> {
+        if sc.is_fork()
+            && let Some(st) = self.borrow_fork(sc)
+        {
+            return self.map.apply(StateRef::from_value(st, sc), sc);
+        }
         let this = rc_self.downcast::<Self>().unwrap();
         this.watch(sc);
         self.map
@@ -201,6 +226,11 @@ where
                 .notify(level.maybe_if(Scan::FILTER), nc)
         }
     }
+    fn notify_fork(self: Rc<Self>, nc: &mut NotifyContext) {
+        if self.data.borrow().scan.fork_state().is_some() {
+            self.sinks.borrow().notify_fork(nc);
+        }
+    }
 }
 
 impl<St, Scan, D, M> ScanNode<St, Scan, D, M>
@@ -223,6 +253,13 @@ where
         };
         self.sinks.borrow_mut().update(is_dirty, rc);
     }
+    // The state in the fork is not cached, so the dependencies are added to the reader.
+    fn borrow_fork(&self, sc: &mut SignalContext<'_, '_>) -> Option<St> {
+        let d = &mut *self.data.borrow_mut();
+        let mut st = d.scan.fork_state()?;
+        d.scan.call(&mut st, sc);
+        Some(st)
+    }
     fn watch(self: &Rc<Self>, sc: &mut SignalContext<'_, '_>) {
         self.sinks.borrow_mut().bind(self.clone(), Slot(0), sc);
         self.update(sc.rc());
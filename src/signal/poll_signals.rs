@@ -0,0 +1,93 @@
+use std::task::Poll;
+
+use crate::{SignalContext, core::AsyncSignalContext};
+
+use super::Signal;
+
+/// Signals of [`Poll`] that [`AsyncSignalContext::join`] and [`AsyncSignalContext::select`] wait on.
+///
+/// Implemented for tuples of up to 4 `&Signal<Poll<T>>` and for `&[Signal<Poll<T>>]`.
+pub trait PollSignals {
+    /// The values of all signals.
+    type Join;
+    /// The values of the signals, with `None` for the signals that are `Pending`.
+    type Select;
+
+    fn poll_join(&self, sc: &mut SignalContext<'_, '_>) -> Poll<Self::Join>;
+    fn poll_select(&self, sc: &mut SignalContext<'_, '_>) -> Poll<Self::Select>;
+}
+
+macro_rules! impl_poll_signals {
+    ($($t:ident $v:ident $i:tt),*) => {
+        impl<$($t: Clone + 'static),*> PollSignals for ($(&Signal<Poll<$t>>,)*) {
+            type Join = ($($t,)*);
+            type Select = ($(Option<$t>,)*);
+
+            fn poll_join(&self, sc: &mut SignalContext<'_, '_>) -> Poll<Self::Join> {
+                match ($(self.$i.get(sc),)*) {
+                    ($(Poll::Ready($v),)*) => Poll::Ready(($($v,)*)),
+                    _ => Poll::Pending,
+                }
+            }
+            fn poll_select(&self, sc: &mut SignalContext<'_, '_>) -> Poll<Self::Select> {
+                let values = ($(ready_to_option(self.$i.get(sc)),)*);
+                if $(values.$i.is_some())||* {
+                    Poll::Ready(values)
+                } else {
+                    Poll::Pending
+                }
+            }
+        }
+    };
+}
+
+impl_poll_signals!(T0 v0 0);
+impl_poll_signals!(T0 v0 0, T1 v1 1);
+impl_poll_signals!(T0 v0 0, T1 v1 1, T2 v2 2);
+impl_poll_signals!(T0 v0 0, T1 v1 1, T2 v2 2, T3 v3 3);
+
+impl<T: Clone + 'static> PollSignals for &[Signal<Poll<T>>] {
+    type Join = Vec<T>;
+    type Select = Vec<Option<T>>;
+
+    fn poll_join(&self, sc: &mut SignalContext<'_, '_>) -> Poll<Self::Join> {
+        let values: Vec<_> = self.iter().map(|s| ready_to_option(s.get(sc))).collect();
+        match values.into_iter().collect() {
+            Some(values) => Poll::Ready(values),
+            None => Poll::Pending,
+        }
+    }
+    fn poll_select(&self, sc: &mut SignalContext<'_, '_>) -> Poll<Self::Select> {
+        let values: Vec<_> = self.iter().map(|s| ready_to_option(s.get(sc))).collect();
+        if values.iter().any(Option::is_some) {
+            Poll::Ready(values)
+        } else {
+            Poll::Pending
+        }
+    }
+}
+
+fn ready_to_option<T>(value: Poll<T>) -> Option<T> {
+    match value {
+        Poll::Ready(value) => Some(value),
+        Poll::Pending => None,
+    }
+}
+
+impl AsyncSignalContext {
+    /// Waits until all signals are `Ready` and returns their values.
+    ///
+    /// As with [`poll_fn`](Self::poll_fn), the signals are added to the dependencies in
+    /// `AsyncSignalContext` when this function completes.
+    pub async fn join<S: PollSignals>(&mut self, signals: S) -> S::Join {
+        self.poll_fn(|sc| signals.poll_join(sc)).await
+    }
+
+    /// Waits until at least one of the signals is `Ready` and returns the values of the signals that are `Ready`.
+    ///
+    /// As with [`poll_fn`](Self::poll_fn), the signals are added to the dependencies in
+    /// `AsyncSignalContext` when this function completes.
+    pub async fn select<S: PollSignals>(&mut self, signals: S) -> S::Select {
+        self.poll_fn(|sc| signals.poll_select(sc)).await
+    }
+}
@@ -1,6 +1,6 @@
 use std::{
     any::Any,
-    cell::{Cell, RefCell},
+    cell::{Cell, Ref, RefCell},
     future::Future,
     pin::Pin,
     rc::Rc,
@@ -10,64 +10,130 @@ use std::{
 use crate::{
     Signal, SignalContext, StateRef,
     core::{
-        AsyncSignalContext, AsyncSourceBinder, BindKey, BindSink, BindSource, DirtyLevel,
-        NotifyContext, Reaction, ReactionContext, SinkBindings, Slot,
+        ActionContext, AsyncSignalContext, AsyncSourceBinder, BindKey, BindSink, BindSource,
+        DirtyLevel, ForkSignal, NotifyContext, Reaction, ReactionContext, SinkBindings, Slot,
     },
 };
 
-use super::SignalNode;
+use super::{
+    SignalNode,
+    builder::{Build, MapFn, MapFnNone, MapFnRaw},
+};
 
-pub(crate) fn build_scan_async<St, T, Fut>(
-    initial_state: St,
+pub(crate) fn scan_async_builder<St, Fut>(
+    initial_state: impl Fn() -> St + 'static,
     get_fut: impl Fn(AsyncSignalContext) -> Fut + 'static,
     scan: impl FnMut(&mut St, Poll<Fut::Output>) -> bool + 'static,
-    map: impl Fn(&St) -> &T + 'static,
-) -> Signal<T>
+) -> impl Build<State = St>
+where
+    St: 'static,
+    Fut: Future + 'static,
+{
+    ScanAsyncBuilder {
+        initial_state,
+        get_fut,
+        scan,
+        map: MapFnNone,
+    }
+}
+
+struct ScanAsyncBuilder<Init, GetFut, Scan, Map> {
+    initial_state: Init,
+    get_fut: GetFut,
+    scan: Scan,
+    map: Map,
+}
+impl<St, Init, GetFut, Fut, Scan, Map> Build for ScanAsyncBuilder<Init, GetFut, Scan, Map>
 where
     St: 'static,
-    T: ?Sized + 'static,
+    Init: Fn() -> St + 'static,
+    GetFut: Fn(AsyncSignalContext) -> Fut + 'static,
     Fut: Future + 'static,
+    Scan: FnMut(&mut St, Poll<Fut::Output>) -> bool + 'static,
+    Map: MapFn<St> + 'static,
 {
-    Signal::from_node(ScanAsyncNode::new(initial_state, get_fut, scan, map))
+    type State = Map::Output;
+
+    fn map_raw<T: ?Sized + 'static>(
+        self,
+        f: impl for<'a, 'r> Fn(
+            StateRef<'a, Self::State>,
+            &mut SignalContext<'r, '_>,
+            &'a &'r (),
+        ) -> StateRef<'a, T>
+        + 'static,
+    ) -> impl Build<State = T> {
+        ScanAsyncBuilder {
+            initial_state: self.initial_state,
+            get_fut: self.get_fut,
+            scan: self.scan,
+            map: MapFnRaw { m: self.map, f },
+        }
+    }
+
+    fn build(self) -> Signal<Self::State> {
+        Signal::from_node(ScanAsyncNode::new(
+            self.initial_state,
+            self.get_fut,
+            self.scan,
+            self.map,
+        ))
+    }
 }
 
 struct ScanAsyncNodeData<St, Fut, Scan> {
-    fut: Pin<Box<Option<Fut>>>,
     asb: AsyncSourceBinder,
+    fut: Pin<Box<Option<Fut>>>,
     state: St,
     scan: Scan,
+    fork: Option<ForkData<St, Fut>>,
 }
 
-struct ScanAsyncNode<St, GetFut, Fut, Scan, Map>
+/// The evaluation of an asynchronous signal in the fork of a transition.
+struct ForkData<St, Fut> {
+    generation: usize,
+    asb: AsyncSourceBinder,
+    fut: Pin<Box<Option<Fut>>>,
+    state: St,
+    // `true` if the signal is observed or read in the fork, and must be `Ready` before the
+    // transition commits.
+    is_joined: bool,
+    is_dirty: bool,
+}
+
+struct ScanAsyncNode<St, Init, GetFut, Fut, Scan, Map>
 where
     GetFut: Fn(AsyncSignalContext) -> Fut + 'static,
     Fut: Future,
     Scan: FnMut(&mut St, Poll<Fut::Output>) -> bool + 'static,
 {
+    initial_state: Init,
     get_fut: GetFut,
     data: RefCell<ScanAsyncNodeData<St, Fut, Scan>>,
     map: Map,
     sinks: RefCell<SinkBindings>,
     discard_scheduled: Cell<bool>,
 }
-impl<St, T, GetFut, Fut, Scan, Map> ScanAsyncNode<St, GetFut, Fut, Scan, Map>
+impl<St, Init, GetFut, Fut, Scan, Map> ScanAsyncNode<St, Init, GetFut, Fut, Scan, Map>
 where
     St: 'static,
-    T: ?Sized + 'static,
+    Init: Fn() -> St + 'static,
     GetFut: Fn(AsyncSignalContext) -> Fut + 'static,
     Fut: Future + 'static,
     Scan: FnMut(&mut St, Poll<Fut::Output>) -> bool + 'static,
-    Map: Fn(&St) -> &T + 'static,
+    Map: MapFn<St> + 'static,
 {
-    fn new(initial_state: St, get_fut: GetFut, scan: Scan, map: Map) -> Rc<Self> {
+    fn new(initial_state: Init, get_fut: GetFut, scan: Scan, map: Map) -> Rc<Self> {
         Rc::new_cyclic(|this| Self {
-            get_fut,
             data: RefCell::new(ScanAsyncNodeData {
-                fut: Box::pin(None),
                 asb: AsyncSourceBinder::new(this),
-                state: initial_state,
+                fut: Box::pin(None),
+                state: initial_state(),
                 scan,
+                fork: None,
             }),
+            initial_state,
+            get_fut,
             map,
             sinks: RefCell::new(SinkBindings::new()),
             discard_scheduled: Cell::new(false),
@@ -79,10 +145,13 @@ where
             return;
         }
         self.try_schedule_discard(rc);
+        let is_changed = self.data.borrow_mut().asb.check(rc);
+        if is_changed {
+            self.cancel();
+        }
         let d = &mut *self.data.borrow_mut();
         let mut is_dirty = false;
-        if d.asb.check(rc) {
-            d.fut.set(None);
+        if is_changed {
             d.fut.set(Some(d.asb.init(&self.get_fut, rc)));
             is_dirty = true;
         }
@@ -109,22 +178,93 @@ where
     fn discard(self: &Rc<Self>, rc: &mut ReactionContext<'_, '_>) {
         self.discard_scheduled.set(false);
         if self.sinks.borrow().is_empty() {
+            self.cancel();
+            self.data.borrow_mut().asb.clear(rc);
+        }
+    }
+
+    // The cancel hooks are called without borrowing `data` because they may read this signal.
+    fn cancel(&self) {
+        let hooks = self.data.borrow_mut().asb.cancel();
+        hooks.run();
+        self.data.borrow_mut().fut.set(None);
+    }
+
+    /// Returns whether this signal depends on the changes made in the transition in progress,
+    /// and joins the transition if it does.
+    fn join_fork(self: &Rc<Self>, sc: &mut SignalContext<'_, '_>) -> bool {
+        let Some(generation) = sc.fork().generation() else {
+            return false;
+        };
+        let mut d = self.data.borrow_mut();
+        let Some(f) = d.fork.as_mut().filter(|f| f.generation == generation) else {
+            return false;
+        };
+        if !f.is_joined {
+            f.is_joined = true;
+            sc.fork().clone().schedule_update();
+        }
+        true
+    }
+
+    /// Evaluates this signal in the fork and returns whether the state in the fork has changed.
+    fn update_fork_raw(self: &Rc<Self>, rc: &mut ReactionContext<'_, '_>) -> bool {
+        let is_changed = {
             let mut d = self.data.borrow_mut();
-            d.fut.set(None);
-            d.asb.clear(rc);
+            let Some(f) = d.fork.as_mut().filter(|f| f.is_joined) else {
+                return false;
+            };
+            if !f.is_dirty && f.asb.is_clean() {
+                return false;
+            }
+            f.is_dirty | f.asb.check(rc)
+        };
+        if is_changed {
+            self.cancel_fork();
+        }
+        let d = &mut *self.data.borrow_mut();
+        let f = d.fork.as_mut().unwrap();
+        let mut is_dirty = false;
+        if is_changed {
+            f.is_dirty = false;
+            f.state = (self.initial_state)();
+            f.fut.set(Some(f.asb.init(&self.get_fut, rc)));
+            is_dirty = true;
+        }
+        let Some(fut) = f.fut.as_mut().as_pin_mut() else {
+            return is_dirty;
+        };
+        let value = f.asb.poll(fut, rc);
+        if value.is_ready() {
+            f.fut.set(None);
+            is_dirty = true;
+        }
+        if is_dirty {
+            (d.scan)(&mut f.state, value);
+        }
+        is_dirty
+    }
+    fn cancel_fork(&self) {
+        let hooks = self.data.borrow_mut().fork.as_mut().map(|f| f.asb.cancel());
+        if let Some(hooks) = hooks {
+            hooks.run();
+        }
+        if let Some(f) = &mut self.data.borrow_mut().fork {
+            f.fut.set(None);
         }
     }
 }
-impl<St, T, GetFut, Fut, Scan, Map> SignalNode for ScanAsyncNode<St, GetFut, Fut, Scan, Map>
+impl<St, Init, GetFut, Fut, Scan, Map> SignalNode
+    for ScanAsyncNode<St, Init, GetFut, Fut, Scan, Map>
 where
     St: 'static,
-    T: ?Sized + 'static,
+    Init: Fn() -> St + 'static,
     GetFut: Fn(AsyncSignalContext) -> Fut + 'static,
     Fut: Future + 'static,
     Scan: FnMut(&mut St, Poll<Fut::Output>) -> bool + 'static,
-    Map: Fn(&St) -> &T + 'static,
+    Map: MapFn<St> + 'static,
 {
-    type Value = T;
+    type Value = Map::Output;
 
     fn borrow<'a, 'r: 'a>(
         &'a self,
@@ -133,12 +273,16 @@ where
     ) -> StateRef<'a, Self::Value> {
         let this = rc_self.clone().downcast::<Self>().unwrap();
         self.sinks.borrow_mut().bind(this.clone(), Slot(0), sc);
+        // In the fork, the state is evaluated by the transition, and the reader is notified
+        // when it changes.
+        if sc.is_fork() && this.join_fork(sc) {
+            let state = Ref::map(self.data.borrow(), |d| &d.fork.as_ref().unwrap().state);
+            return self.map.apply(state.into(), sc);
+        }
         this.update(sc.rc());
-        StateRef::map(
-            self.data.borrow().into(),
-            |data| (self.map)(&data.state),
-            sc,
-        )
+        self.data.borrow_mut().asb.join_boundary(sc);
+        self.map
+            .apply(Ref::map(self.data.borrow(), |data| &data.state).into(), sc)
     }
 
     fn fmt_debug(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result
@@ -148,14 +292,15 @@ where
         write!(f, "<async>")
     }
 }
-impl<St, T, GetFut, Fut, Scan, Map> BindSource for ScanAsyncNode<St, GetFut, Fut, Scan, Map>
+impl<St, Init, GetFut, Fut, Scan, Map> BindSource
+    for ScanAsyncNode<St, Init, GetFut, Fut, Scan, Map>
 where
     St: 'static,
-    T: ?Sized + 'static,
+    Init: Fn() -> St + 'static,
     GetFut: Fn(AsyncSignalContext) -> Fut + 'static,
     Fut: Future + 'static,
     Scan: FnMut(&mut St, Poll<Fut::Output>) -> bool + 'static,
-    Map: Fn(&St) -> &T + 'static,
+    Map: MapFn<St> + 'static,
 {
     fn check(self: Rc<Self>, _slot: Slot, key: BindKey, rc: &mut ReactionContext<'_, '_>) -> bool {
         self.update(rc);
@@ -173,19 +318,107 @@ where
     }
 }
 
-impl<St, T, GetFut, Fut, Scan, Map> BindSink for ScanAsyncNode<St, GetFut, Fut, Scan, Map>
+impl<St, Init, GetFut, Fut, Scan, Map> ForkSignal
+    for ScanAsyncNode<St, Init, GetFut, Fut, Scan, Map>
 where
     St: 'static,
-    T: ?Sized + 'static,
+    Init: Fn() -> St + 'static,
     GetFut: Fn(AsyncSignalContext) -> Fut + 'static,
     Fut: Future + 'static,
     Scan: FnMut(&mut St, Poll<Fut::Output>) -> bool + 'static,
-    Map: Fn(&St) -> &T + 'static,
+    Map: MapFn<St> + 'static,
+{
+    fn update_fork(self: Rc<Self>, ac: &mut ActionContext) -> bool {
+        if self.update_fork_raw(&mut ac.rc()) {
+            self.sinks.borrow().notify_fork(ac.nc());
+        }
+        let d = self.data.borrow();
+        d.fork
+            .as_ref()
+            .is_some_and(|f| f.is_joined && f.fut.is_some())
+    }
+    fn is_clean(&self) -> bool {
+        let d = self.data.borrow();
+        d.fork
+            .as_ref()
+            .is_none_or(|f| !f.is_joined || !f.is_dirty && f.asb.is_clean())
+    }
+    fn commit_fork(self: Rc<Self>, ac: &mut ActionContext) {
+        let fork = self.data.borrow_mut().fork.take();
+        let Some(mut f) = fork.filter(|f| f.is_joined && !self.sinks.borrow().is_empty()) else {
+            return;
+        };
+        self.cancel();
+        {
+            let rc = &mut ac.rc();
+            let d = &mut *self.data.borrow_mut();
+            d.state = f.state;
+            d.asb.adopt(&mut f.asb, rc);
+        }
+        self.sinks.borrow_mut().notify(DirtyLevel::Dirty, ac.nc());
+    }
+    fn discard_fork(&self) {
+        let fork = self.data.borrow_mut().fork.take();
+        drop(fork);
+    }
+}
+
+impl<St, Init, GetFut, Fut, Scan, Map> BindSink for ScanAsyncNode<St, Init, GetFut, Fut, Scan, Map>
+where
+    St: 'static,
+    Init: Fn() -> St + 'static,
+    GetFut: Fn(AsyncSignalContext) -> Fut + 'static,
+    Fut: Future + 'static,
+    Scan: FnMut(&mut St, Poll<Fut::Output>) -> bool + 'static,
+    Map: MapFn<St> + 'static,
 {
     fn notify(self: Rc<Self>, slot: Slot, level: DirtyLevel, nc: &mut NotifyContext) {
-        let mut d = self.data.borrow_mut();
-        if d.asb.on_notify(slot, level) {
+        if AsyncSourceBinder::is_fork_slot(slot) {
+            let fork = nc.fork().clone();
+            let mut d = self.data.borrow_mut();
+            if let Some(f) = &mut d.fork
+                && fork.generation() == Some(f.generation)
+            {
+                f.asb.on_notify(slot, level);
+                fork.schedule_update();
+            }
+            return;
+        }
+        if self.data.borrow_mut().asb.on_notify(slot, level) {
             self.sinks.borrow_mut().notify(DirtyLevel::MaybeDirty, nc)
         }
     }
+
+    // A signal that depends on the changes made in the transition is evaluated in the fork if it
+    // is observed.
+    fn notify_fork(self: Rc<Self>, nc: &mut NotifyContext) {
+        let fork = nc.fork().clone();
+        let Some(generation) = fork.generation() else {
+            return;
+        };
+        let old = {
+            let mut d = self.data.borrow_mut();
+            if let Some(f) = &mut d.fork
+                && f.generation == generation
+            {
+                f.is_dirty = true;
+                drop(d);
+                fork.schedule_update();
+                return;
+            }
+            let this = Rc::downgrade(&self);
+            d.fork.replace(ForkData {
+                generation,
+                asb: AsyncSourceBinder::new_fork(&this),
+                fut: Box::pin(None),
+                state: (self.initial_state)(),
+                is_joined: !self.sinks.borrow().is_empty(),
+                is_dirty: true,
+            })
+        };
+        drop(old);
+        fork.join(Rc::downgrade(&self) as _);
+        fork.schedule_update();
+        self.sinks.borrow().notify_fork(nc);
+    }
 }
@@ -1,5 +1,7 @@
 use std::{
     any::{Any, type_name},
+    borrow::Borrow,
+    cell::RefCell,
     fmt::Debug,
     future::Future,
     ops::{AsyncFn, BitAnd, BitOr},
@@ -16,7 +18,10 @@ use crate::{
     effect_in, stream_from,
 };
 
-use super::{builder::SignalBuilder, scan_async::build_scan_async};
+use super::{
+    builder::{Build, SignalBuilder},
+    scan_async::scan_async_builder,
+};
 
 #[cfg(test)]
 mod tests;
@@ -211,8 +216,8 @@ impl<T: ?Sized + 'static> Signal<T> {
         T: Sized,
     {
         let f = Rc::new(f);
-        build_scan_async(
-            Poll::Pending,
+        scan_async_builder(
+            || Poll::Pending,
             move |mut sc| {
                 let f = f.clone();
                 async move { f(&mut sc).await }
@@ -225,8 +230,8 @@ impl<T: ?Sized + 'static> Signal<T> {
                     true
                 }
             },
-            |st| st,
         )
+        .build()
     }
 
     /// Obtains a reference to the current value and adds a dependency on this `Signal` to the specified `SignalContext`.
@@ -260,6 +265,29 @@ impl<T: ?Sized + 'static> Signal<T> {
         self.borrow(sc).into_owned()
     }
 
+    /// Waits until the value differs from the value at the time of the call, and returns the new value.
+    ///
+    /// When this function completes, a dependency on this `Signal` is added to the `AsyncSignalContext`.
+    pub async fn changed(&self, sc: &mut AsyncSignalContext) -> <T as ToOwned>::Owned
+    where
+        T: ToOwned + PartialEq,
+    {
+        let initial = RefCell::new(None::<T::Owned>);
+        sc.poll_fn(|sc| {
+            let value = self.borrow(sc);
+            let mut initial = initial.borrow_mut();
+            match &*initial {
+                Some(initial) if *value == *initial.borrow() => Poll::Pending,
+                Some(_) => Poll::Ready(value.into_owned()),
+                None => {
+                    *initial = Some(value.into_owned());
+                    Poll::Pending
+                }
+            }
+        })
+        .await
+    }
+
     /// Creates a new `Signal` whose references are transformed by the specified function.
     ///
     /// Using [`SignalBuilder::map`], you can create similar `Signal` more efficiently.
@@ -7,7 +7,8 @@ use std::{
 use crate::{
     ReactionPhase, Signal, SignalBuilder, State, StateRef,
     core::Runtime,
-    effect,
+    effect, effect_async,
+    signal::KeepPrevious,
     utils::{sync::oneshot_broadcast, test_helpers::call_on_drop},
 };
 use assert_call::{CallRecorder, call};
@@ -653,6 +654,25 @@ fn from_async_no_dependants() {
     cr.verify("drop");
 }
 
+#[test]
+fn from_async_on_cancel_on_discard() {
+    let mut rt = Runtime::new();
+    let mut cr = CallRecorder::new();
+
+    let (_sender, receiver) = oneshot_broadcast::<i32>();
+
+    let s = Signal::from_async(async move |sc| {
+        let _x = call_on_drop("drop");
+        sc.on_cancel(|| call!("cancel"));
+        receiver.recv().await
+    });
+
+    assert_eq!(s.get(&mut rt.sc()), Poll::Pending);
+    cr.verify(());
+    rt.flush();
+    cr.verify(["cancel", "drop"]);
+}
+
 #[test]
 fn get_async() {
     let mut rt = Runtime::new();
@@ -676,6 +696,53 @@ fn get_async() {
     assert_eq!(s.get(&mut rt.sc()), Poll::Pending);
 }
 
+#[test]
+fn from_async_keep_previous() {
+    let mut rt = Runtime::new();
+    let source = State::new(1);
+    let gate = State::new(Poll::Pending);
+    let s = SignalBuilder::from_async_keep_previous({
+        let source = source.clone();
+        let gate = gate.clone();
+        async move |sc| {
+            let value = sc.with(|sc| source.get(sc));
+            gate.to_signal().get_async(sc).await;
+            value * 10
+        }
+    })
+    .build();
+    let state = |value, is_refreshing| KeepPrevious {
+        value,
+        is_refreshing,
+    };
+
+    assert_eq!(s.get(&mut rt.sc()), state(Poll::Pending, false));
+    gate.set(Poll::Ready(()), rt.ac());
+    assert_eq!(s.get(&mut rt.sc()), state(Poll::Ready(10), false));
+
+    gate.set(Poll::Pending, rt.ac());
+    source.set(2, rt.ac());
+    assert_eq!(s.get(&mut rt.sc()), state(Poll::Ready(10), true));
+    gate.set(Poll::Ready(()), rt.ac());
+    assert_eq!(s.get(&mut rt.sc()), state(Poll::Ready(20), false));
+}
+
+#[test]
+fn from_async_keep_previous_map() {
+    let mut rt = Runtime::new();
+    let source = State::new(1);
+    let s = SignalBuilder::from_async_keep_previous({
+        let source = source.clone();
+        async move |sc| sc.with(|sc| source.get(sc))
+    })
+    .map(|st| &st.value)
+    .build();
+
+    assert_eq!(s.get(&mut rt.sc()), Poll::Ready(1));
+    source.set(2, rt.ac());
+    assert_eq!(s.get(&mut rt.sc()), Poll::Ready(2));
+}
+
 #[test]
 async fn to_stream() {
     let mut rt = Runtime::new();
@@ -970,3 +1037,99 @@ fn debug_stream_scan_signal() {
     let debug_str = format!("{signal:?}");
     assert!(debug_str.contains("stream_scan"));
 }
+
+#[test]
+fn wait_until() {
+    let mut rt = Runtime::new();
+    let mut cr = CallRecorder::new();
+    let s = State::new(0);
+
+    let _e = effect_async({
+        let s = s.clone();
+        async move |sc| {
+            sc.wait_until(|sc| s.get(sc) >= 10).await;
+            call!("done");
+        }
+    });
+    rt.flush();
+    cr.verify(());
+
+    s.set(5, rt.ac());
+    rt.flush();
+    cr.verify(());
+
+    s.set(10, rt.ac());
+    rt.flush();
+    cr.verify("done");
+}
+
+#[test]
+fn changed() {
+    let mut rt = Runtime::new();
+    let mut cr = CallRecorder::new();
+    let s = State::new(1);
+
+    let _e = effect_async({
+        let s = s.to_signal();
+        async move |sc| {
+            let value = s.changed(sc).await;
+            call!("{value}");
+        }
+    });
+    rt.flush();
+    cr.verify(());
+
+    s.set(1, rt.ac());
+    rt.flush();
+    cr.verify(());
+
+    s.set(2, rt.ac());
+    rt.flush();
+    cr.verify("2");
+}
+
+#[test]
+fn join() {
+    let mut rt = Runtime::new();
+    let mut cr = CallRecorder::new();
+    let a = State::new(Poll::Pending);
+    let b = State::new(Poll::Pending);
+
+    let _e = effect_async({
+        let a = a.to_signal();
+        let b = b.to_signal();
+        async move |sc| {
+            let (a, b) = sc.join((&a, &b)).await;
+            call!("{a} {b}");
+        }
+    });
+    rt.flush();
+    a.set(Poll::Ready(1), rt.ac());
+    rt.flush();
+    cr.verify(());
+
+    b.set(Poll::Ready("x"), rt.ac());
+    rt.flush();
+    cr.verify("1 x");
+}
+
+#[test]
+fn select() {
+    let mut rt = Runtime::new();
+    let mut cr = CallRecorder::new();
+    let signals = [State::new(Poll::Pending), State::new(Poll::Pending)];
+
+    let _e = effect_async({
+        let signals: Vec<_> = signals.iter().map(|s| s.to_signal()).collect();
+        async move |sc| {
+            let values = sc.select(&signals[..]).await;
+            call!("{values:?}");
+        }
+    });
+    rt.flush();
+    cr.verify(());
+
+    signals[1].set(Poll::Ready(2), rt.ac());
+    rt.flush();
+    cr.verify("[None, Some(2)]");
+}
@@ -1,6 +1,6 @@
 use std::{
     any::Any,
-    cell::{RefCell, RefMut},
+    cell::{Ref, RefCell, RefMut},
     rc::Rc,
 };
 
@@ -10,8 +10,8 @@ use serde::{Deserialize, Serialize};
 use crate::{
     ActionContext, Signal, SignalContext, StateRef,
     core::{
-        BindKey, BindSink, BindSource, DirtyLevel, NotifyContext, ReactionContext, SinkBindings,
-        Slot, schedule_notify,
+        BindKey, BindSink, BindSource, DirtyLevel, ForkWrite, NotifyContext, ReactionContext,
+        SinkBindings, Slot, schedule_notify,
     },
     signal::{SignalNode, ToSignal},
 };
@@ -30,13 +30,14 @@ impl<T: 'static> State<T> {
         Self(Rc::new(StateNode {
             sinks: RefCell::new(SinkBindings::new()),
             value: RefCell::new(value),
+            fork_value: RefCell::new(None),
         }))
     }
 
     /// Obtains a reference to the current value and adds a dependency on this `State` to the specified `SignalContext`.
     pub fn borrow<'a, 'r: 'a>(&'a self, sc: &mut SignalContext<'r, '_>) -> StateRef<'a, T> {
         self.0.bind(sc);
-        self.0.value.borrow().into()
+        self.0.borrow_value(sc)
     }
 
     /// Gets the current value and adds a dependency on this `State` to the specified `SignalContext`.
@@ -92,16 +93,36 @@ impl<T: 'static> State<T> {
     }
 
     /// Sets the value of the state and notifies the dependencies.
+    ///
+    /// In [`start_transition`](crate::start_transition), the value is held in the transition
+    /// until it commits.
     pub fn set(&self, value: T, ac: &mut ActionContext) {
+        if ac.fork().is_open() {
+            self.0.set_fork(value, ac);
+            return;
+        }
         *self.0.value.borrow_mut() = value;
         self.0.notify_raw(ac.nc());
     }
 
     /// Sets the value of the state and notifies the dependencies only if the current state is different from the specified value.
+    ///
+    /// In [`start_transition`](crate::start_transition), the value is compared with the value
+    /// held in the transition, if any, and held in the transition until it commits.
     pub fn set_dedup(&self, value: T, ac: &mut ActionContext)
     where
         T: PartialEq,
     {
+        if ac.fork().is_open() {
+            let is_changed = match &*self.0.fork_value.borrow() {
+                Some(fork_value) => *fork_value != value,
+                None => *self.0.value.borrow() != value,
+            };
+            if is_changed {
+                self.0.set_fork(value, ac);
+            }
+            return;
+        }
         let mut this_value = self.0.value.borrow_mut();
         if *this_value != value {
             *this_value = value;
@@ -158,11 +179,26 @@ impl<T> ToSignal for State<T> {
 struct StateNode<T: 'static> {
     sinks: RefCell<SinkBindings>,
     value: RefCell<T>,
+    fork_value: RefCell<Option<T>>,
 }
 impl<T: 'static> StateNode<T> {
     fn bind(self: &Rc<Self>, sc: &mut SignalContext<'_, '_>) {
         self.sinks.borrow_mut().bind(self.clone(), Slot(0), sc);
     }
+    fn borrow_value<'a>(&'a self, sc: &SignalContext<'_, '_>) -> StateRef<'a, T> {
+        if sc.is_fork()
+            && let Ok(value) = Ref::filter_map(self.fork_value.borrow(), Option::as_ref)
+        {
+            return value.into();
+        }
+        self.value.borrow().into()
+    }
+    fn set_fork(self: &Rc<Self>, value: T, ac: &mut ActionContext) {
+        if self.fork_value.borrow_mut().replace(value).is_none() {
+            ac.fork().write(self.clone());
+        }
+        self.sinks.borrow().notify_fork(ac.nc());
+    }
     fn notify_raw(&self, nc: &mut NotifyContext) {
         self.sinks.borrow_mut().notify(DirtyLevel::Dirty, nc)
     }
@@ -194,6 +230,18 @@ impl<T: 'static> BindSink for StateNode<T> {
         self.notify_raw(nc);
     }
 }
+impl<T: 'static> ForkWrite for StateNode<T> {
+    fn commit(self: Rc<Self>, nc: &mut NotifyContext) {
+        let value = self.fork_value.borrow_mut().take();
+        if let Some(value) = value {
+            *self.value.borrow_mut() = value;
+            self.notify_raw(nc);
+        }
+    }
+    fn discard(&self) {
+        self.fork_value.borrow_mut().take();
+    }
+}
 
 impl<T: 'static> SignalNode for StateNode<T> {
     type Value = T;
@@ -203,7 +251,7 @@ impl<T: 'static> SignalNode for StateNode<T> {
         sc: &mut SignalContext<'r, '_>,
     ) -> StateRef<'a, Self::Value> {
         rc_self.downcast::<Self>().unwrap().bind(sc);
-        self.value.borrow().into()
+        self.borrow_value(sc)
     }
 
     fn fmt_debug(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result
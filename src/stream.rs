@@ -5,7 +5,7 @@ use crate::{
 use futures::Stream;
 use std::{
     cell::RefCell,
-    mem::{replace, take},
+    mem::take,
     pin::Pin,
     rc::Rc,
     task::{Context, Poll, Waker},
@@ -15,7 +15,18 @@ use std::{
 pub fn stream_from<T: 'static>(
     f: impl FnMut(&mut SignalContext<'_, '_>) -> T + 'static,
 ) -> impl Stream<Item = T> + Unpin + 'static {
-    SignalStream::new(f)
+    SignalStream::new(f, |_, value| Some(value))
+}
+
+/// Create a `Stream` from a signal function, combining values that have not been consumed yet.
+///
+/// `merge` receives the value waiting to be consumed, if any, and the new value,
+/// and returns the value to wait for consumption, or `None` to yield nothing.
+pub(crate) fn stream_from_merge<T: 'static>(
+    f: impl FnMut(&mut SignalContext<'_, '_>) -> T + 'static,
+    merge: fn(Option<T>, T) -> Option<T>,
+) -> impl Stream<Item = T> + Unpin + 'static {
+    SignalStream::new(f, merge)
 }
 
 #[derive(Default)]
@@ -32,6 +43,7 @@ struct Node<F, T>(RefCell<Data<F, T>>);
 
 struct Data<F, T> {
     f: F,
+    merge: fn(Option<T>, T) -> Option<T>,
     is_scheduled: bool,
     value: ValueState<T>,
     sb: SourceBinder,
@@ -42,10 +54,11 @@ where
     F: FnMut(&mut SignalContext<'_, '_>) -> T + 'static,
     T: 'static,
 {
-    pub fn new(f: F) -> Self {
+    pub fn new(f: F, merge: fn(Option<T>, T) -> Option<T>) -> Self {
         Self(Rc::new_cyclic(|this| {
             Node(RefCell::new(Data {
                 f,
+                merge,
                 is_scheduled: false,
                 value: ValueState::None,
                 sb: SourceBinder::new(this, Slot(0)),
@@ -106,9 +119,21 @@ where
         d.is_scheduled = false;
         if d.sb.check(rc) {
             let value = d.sb.update(|sc| (d.f)(sc), rc);
-            if let ValueState::Pending(waker) = replace(&mut d.value, ValueState::Ready(value)) {
-                waker.wake();
-            }
+            let (old, waker) = match take(&mut d.value) {
+                ValueState::None => (None, None),
+                ValueState::Pending(waker) => (None, Some(waker)),
+                ValueState::Ready(old) => (Some(old), None),
+            };
+            d.value = match ((d.merge)(old, value), waker) {
+                (Some(value), waker) => {
+                    if let Some(waker) = waker {
+                        waker.wake();
+                    }
+                    ValueState::Ready(value)
+                }
+                (None, Some(waker)) => ValueState::Pending(waker),
+                (None, None) => ValueState::None,
+            };
         }
     }
 }
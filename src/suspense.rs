@@ -0,0 +1,138 @@
+use std::{
+    any::Any,
+    cell::{Cell, RefCell},
+    rc::Rc,
+};
+
+use crate::{
+    Signal, SignalBuilder, SignalContext, StateRef,
+    core::{
+        BindKey, BindSink, BindSource, DirtyLevel, NotifyContext, PendingBoundary, ReactionContext,
+        SinkBindings, Slot, schedule_notify,
+    },
+    signal::SignalNode,
+};
+
+#[cfg(test)]
+mod tests;
+
+/// A boundary that counts the asynchronous signals read beneath it that are `Pending`.
+///
+/// Signals are read beneath the boundary with [`scope`](Self::scope) or [`hold`](Self::hold).
+/// Every signal created by an asynchronous function, such as [`Signal::from_async`], that is read
+/// in them, directly or through other signals, is counted while its asynchronous function is `Pending`.
+///
+/// A signal stays in the boundary until it is discarded because it is no longer observed.
+/// If it is read beneath nested boundaries, it is counted by the innermost one.
+#[derive(Clone)]
+pub struct Suspense(Rc<SuspenseNode>);
+
+impl Suspense {
+    /// Creates a boundary with no pending signals.
+    pub fn new() -> Self {
+        Self(Rc::new(SuspenseNode {
+            pending_count: Cell::new(0),
+            sinks: RefCell::new(SinkBindings::new()),
+        }))
+    }
+
+    /// Calls `f` with a [`SignalContext`] in which this boundary counts the signals that are read.
+    pub fn scope<T>(
+        &self,
+        sc: &mut SignalContext<'_, '_>,
+        f: impl FnOnce(&mut SignalContext<'_, '_>) -> T,
+    ) -> T {
+        sc.with_boundary(self.0.clone(), f)
+    }
+
+    /// Returns a signal with the number of signals beneath this boundary that are `Pending`.
+    pub fn pending_count(&self) -> Signal<usize> {
+        Signal::from_node(self.0.clone())
+    }
+
+    /// Returns a signal that is `true` while at least one signal beneath this boundary is `Pending`.
+    pub fn is_pending(&self) -> Signal<bool> {
+        let pending_count = self.pending_count();
+        Signal::new_dedup(move |sc| pending_count.get(sc) > 0)
+    }
+
+    /// Returns a signal that reads `view` beneath this boundary and keeps the last value while
+    /// a signal beneath this boundary is `Pending`.
+    ///
+    /// If no value has been taken yet, the value of `view` is taken even while pending.
+    pub fn hold<T: Clone + 'static>(&self, view: Signal<T>) -> Signal<T> {
+        let this = self.clone();
+        SignalBuilder::from_scan_filter(None, move |st, sc| {
+            let value = this.scope(sc, |sc| view.get(sc));
+            if this.pending_count().get(sc) > 0 && st.is_some() {
+                return false;
+            }
+            *st = Some(value);
+            true
+        })
+        .map(|st: &Option<T>| st.as_ref().unwrap())
+        .build()
+    }
+}
+impl Default for Suspense {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+struct SuspenseNode {
+    pending_count: Cell<usize>,
+    sinks: RefCell<SinkBindings>,
+}
+impl SuspenseNode {
+    // The count changes while signals are evaluated, so the notification is scheduled.
+    fn set_pending_count(self: &Rc<Self>, pending_count: usize) {
+        self.pending_count.set(pending_count);
+        let node = Rc::downgrade(self);
+        schedule_notify(node, Slot(0));
+    }
+}
+impl PendingBoundary for SuspenseNode {
+    fn increment(self: Rc<Self>) {
+        self.set_pending_count(self.pending_count.get() + 1);
+    }
+    fn decrement(self: Rc<Self>) {
+        self.set_pending_count(self.pending_count.get() - 1);
+    }
+}
+
+impl BindSource for SuspenseNode {
+    fn check(self: Rc<Self>, _slot: Slot, key: BindKey, rc: &mut ReactionContext<'_, '_>) -> bool {
+        self.sinks.borrow().is_dirty(key, rc)
+    }
+
+    fn unbind(self: Rc<Self>, _slot: Slot, key: BindKey, rc: &mut ReactionContext<'_, '_>) {
+        self.sinks.borrow_mut().unbind(key, rc);
+    }
+
+    fn rebind(self: Rc<Self>, slot: Slot, key: BindKey, sc: &mut SignalContext<'_, '_>) {
+        self.sinks.borrow_mut().rebind(self.clone(), slot, key, sc);
+    }
+}
+impl BindSink for SuspenseNode {
+    fn notify(self: Rc<Self>, _slot: Slot, _level: DirtyLevel, nc: &mut NotifyContext) {
+        self.sinks.borrow_mut().notify(DirtyLevel::Dirty, nc)
+    }
+}
+
+impl SignalNode for SuspenseNode {
+    type Value = usize;
+    fn borrow<'a, 'r: 'a>(
+        &'a self,
+        rc_self: Rc<dyn Any>,
+        sc: &mut SignalContext<'r, '_>,
+    ) -> StateRef<'a, Self::Value> {
+        let this = rc_self.downcast::<Self>().unwrap();
+        self.sinks.borrow_mut().bind(this, Slot(0), sc);
+        StateRef::from_value(self.pending_count.get(), sc)
+    }
+
+    fn fmt_debug(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        std::fmt::Debug::fmt(&self.pending_count.get(), f)
+    }
+}
@@ -0,0 +1,199 @@
+use std::task::Poll;
+
+use pretty_assertions::assert_eq;
+
+use super::*;
+use crate::{
+    State,
+    core::Runtime,
+    effect,
+    utils::sync::oneshot_broadcast::{Sender, oneshot_broadcast},
+};
+
+fn async_signal() -> (Signal<Poll<i32>>, Sender<i32>) {
+    let (sender, receiver) = oneshot_broadcast();
+    let s = Signal::from_async(async move |_| receiver.recv().await);
+    (s, sender)
+}
+
+#[test]
+fn pending_count() {
+    let mut rt = Runtime::new();
+    let suspense = Suspense::new();
+    let (s0, sender0) = async_signal();
+    let (s1, sender1) = async_signal();
+    let _e = effect({
+        let suspense = suspense.clone();
+        move |sc| {
+            suspense.scope(sc, |sc| {
+                let _ = s0.get(sc);
+                let _ = s1.get(sc);
+            })
+        }
+    });
+    rt.flush();
+    assert_eq!(suspense.pending_count().get(&mut rt.sc()), 2);
+    assert!(suspense.is_pending().get(&mut rt.sc()));
+
+    sender0.send(1);
+    rt.flush();
+    assert_eq!(suspense.pending_count().get(&mut rt.sc()), 1);
+    assert!(suspense.is_pending().get(&mut rt.sc()));
+
+    sender1.send(2);
+    rt.flush();
+    assert_eq!(suspense.pending_count().get(&mut rt.sc()), 0);
+    assert!(!suspense.is_pending().get(&mut rt.sc()));
+}
+
+#[test]
+fn signals_read_outside_scope_are_not_counted() {
+    let mut rt = Runtime::new();
+    let suspense = Suspense::new();
+    let (s, _sender) = async_signal();
+    let _e = effect(move |sc| {
+        let _ = s.get(sc);
+    });
+    rt.flush();
+    assert_eq!(suspense.pending_count().get(&mut rt.sc()), 0);
+}
+
+#[test]
+fn count_signals_read_through_other_signals() {
+    let mut rt = Runtime::new();
+    let suspense = Suspense::new();
+    let (s, sender) = async_signal();
+    let view = Signal::new(move |sc| s.get(sc).is_ready());
+    let _e = effect({
+        let suspense = suspense.clone();
+        move |sc| {
+            suspense.scope(sc, |sc| view.get(sc));
+        }
+    });
+    rt.flush();
+    assert_eq!(suspense.pending_count().get(&mut rt.sc()), 1);
+
+    sender.send(1);
+    rt.flush();
+    assert_eq!(suspense.pending_count().get(&mut rt.sc()), 0);
+}
+
+#[test]
+fn count_signals_that_become_pending_again() {
+    let mut rt = Runtime::new();
+    let suspense = Suspense::new();
+    let (sender0, receiver0) = oneshot_broadcast::<i32>();
+    let (sender1, receiver1) = oneshot_broadcast::<i32>();
+    let key = State::new(0);
+    let s = Signal::from_async({
+        let key = key.clone();
+        async move |sc| {
+            let receiver = if sc.with(|sc| key.get(sc)) == 0 {
+                receiver0.clone()
+            } else {
+                receiver1.clone()
+            };
+            receiver.recv().await
+        }
+    });
+    let _e = effect({
+        let suspense = suspense.clone();
+        move |sc| {
+            let _ = suspense.scope(sc, |sc| s.get(sc));
+        }
+    });
+    rt.flush();
+    sender0.send(1);
+    rt.flush();
+    assert_eq!(suspense.pending_count().get(&mut rt.sc()), 0);
+
+    key.set(1, rt.ac());
+    rt.flush();
+    assert_eq!(suspense.pending_count().get(&mut rt.sc()), 1);
+
+    sender1.send(2);
+    rt.flush();
+    assert_eq!(suspense.pending_count().get(&mut rt.sc()), 0);
+}
+
+#[test]
+fn discarded_signals_leave_boundary() {
+    let mut rt = Runtime::new();
+    let suspense = Suspense::new();
+    let (s, _sender) = async_signal();
+    let e = effect({
+        let suspense = suspense.clone();
+        move |sc| {
+            let _ = suspense.scope(sc, |sc| s.get(sc));
+        }
+    });
+    rt.flush();
+    assert_eq!(suspense.pending_count().get(&mut rt.sc()), 1);
+
+    drop(e);
+    rt.flush();
+    assert_eq!(suspense.pending_count().get(&mut rt.sc()), 0);
+}
+
+#[test]
+fn innermost_boundary_counts() {
+    let mut rt = Runtime::new();
+    let outer = Suspense::new();
+    let inner = Suspense::new();
+    let (s0, _sender0) = async_signal();
+    let (s1, _sender1) = async_signal();
+    let _e = effect({
+        let outer = outer.clone();
+        let inner = inner.clone();
+        move |sc| {
+            outer.scope(sc, |sc| {
+                let _ = s0.get(sc);
+                let _ = inner.scope(sc, |sc| s1.get(sc));
+            })
+        }
+    });
+    rt.flush();
+    assert_eq!(outer.pending_count().get(&mut rt.sc()), 1);
+    assert_eq!(inner.pending_count().get(&mut rt.sc()), 1);
+}
+
+#[test]
+fn hold_keeps_previous_value_while_pending() {
+    let mut rt = Runtime::new();
+    let suspense = Suspense::new();
+    let (sender0, receiver0) = oneshot_broadcast::<i32>();
+    let (sender1, receiver1) = oneshot_broadcast::<i32>();
+    let key = State::new(0);
+    let data = Signal::from_async({
+        let key = key.clone();
+        async move |sc| {
+            let receiver = if sc.with(|sc| key.get(sc)) == 0 {
+                receiver0.clone()
+            } else {
+                receiver1.clone()
+            };
+            receiver.recv().await
+        }
+    });
+    let view = Signal::new(move |sc| match data.get(sc) {
+        Poll::Ready(value) => format!("data {value}"),
+        Poll::Pending => "loading".to_string(),
+    });
+    let held = suspense.hold(view);
+    let _e = held.effect(|_| {});
+    rt.flush();
+    assert_eq!(held.get(&mut rt.sc()), "loading");
+
+    sender0.send(1);
+    rt.flush();
+    assert_eq!(held.get(&mut rt.sc()), "data 1");
+
+    key.set(1, rt.ac());
+    rt.flush();
+    assert_eq!(held.get(&mut rt.sc()), "data 1");
+    assert!(suspense.is_pending().get(&mut rt.sc()));
+
+    sender1.send(2);
+    rt.flush();
+    assert_eq!(held.get(&mut rt.sc()), "data 2");
+}
@@ -0,0 +1,40 @@
+use crate::{ActionContext, Signal, StateRef, spawn_action};
+
+#[cfg(test)]
+mod tests;
+
+/// Changes states in a transition.
+///
+/// The values set by `f` with [`State::set`](crate::State::set) and
+/// [`State::set_dedup`](crate::State::set_dedup) are held in the transition's fork, so signals
+/// and effects keep showing the values from before the transition, including those read or
+/// created while it is in progress. Asynchronous signals that are observed and depend on those
+/// values are evaluated in the fork in the background.
+///
+/// The transition commits when none of those asynchronous signals is `Pending`. Then the held
+/// values are written to the states and the asynchronous signals take over the values evaluated
+/// in the fork, so the effects see all of them at once.
+/// Asynchronous signals that are not observed or do not depend on the held values do not delay
+/// the commit. Other changes made by `f` are not held.
+///
+/// If another transition starts while one is in progress, they commit together.
+/// State changes made outside transitions are applied immediately, even while a transition is in
+/// progress.
+///
+/// # Panics
+///
+/// Panics if a [`Runtime`](crate::core::Runtime) exists and the default
+/// [`ActionPhase`](crate::ActionPhase) is not valid according to its
+/// [`RuntimeConfig`](crate::core::RuntimeConfig).
+pub fn start_transition(f: impl FnOnce(&mut ActionContext) + 'static) {
+    spawn_action(move |ac| ac.fork().clone().run(ac, f));
+}
+
+/// Returns a signal that is `true` while a transition started by [`start_transition`] is in
+/// progress in the current [`Runtime`](crate::core::Runtime).
+pub fn is_transitioning() -> Signal<bool> {
+    Signal::from_borrow((), |_, sc, _| {
+        let is_active = sc.fork().clone().is_active(sc);
+        StateRef::from_value(is_active, sc)
+    })
+}
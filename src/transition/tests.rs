@@ -0,0 +1,361 @@
+use std::{
+    collections::HashMap,
+    future::pending,
+    panic::{AssertUnwindSafe, catch_unwind},
+    task::Poll,
+};
+
+use assert_call::{CallRecorder, call};
+
+use super::*;
+use crate::{
+    State,
+    core::Runtime,
+    effect,
+    utils::sync::oneshot_broadcast::{Receiver, Sender, oneshot_broadcast},
+};
+
+fn tab_view(tab: &State<i32>) -> (Signal<String>, HashMap<i32, Sender<String>>) {
+    let mut senders = HashMap::new();
+    let mut receivers = HashMap::<i32, Receiver<String>>::new();
+    for id in [1, 2] {
+        let (sender, receiver) = oneshot_broadcast();
+        senders.insert(id, sender);
+        receivers.insert(id, receiver);
+    }
+    let data = Signal::from_async({
+        let tab = tab.clone();
+        async move |sc| {
+            let tab = sc.with(|sc| tab.get(sc));
+            receivers[&tab].recv().await
+        }
+    });
+    let view = Signal::new({
+        let tab = tab.clone();
+        move |sc| match data.get(sc) {
+            Poll::Ready(data) => format!("{} {data}", tab.get(sc)),
+            Poll::Pending => format!("{} loading", tab.get(sc)),
+        }
+    });
+    (view, senders)
+}
+
+#[test]
+fn effect_keeps_value_until_ready() {
+    let mut rt = Runtime::new();
+    let mut cr = CallRecorder::new();
+    let tab = State::new(1);
+    let (view, senders) = tab_view(&tab);
+    let _e = view.effect(|value| call!("{value}"));
+    rt.flush();
+    cr.verify("1 loading");
+    senders[&1].send("a".to_string());
+    rt.flush();
+    cr.verify("1 a");
+
+    start_transition({
+        let tab = tab.clone();
+        move |ac| tab.set(2, ac)
+    });
+    rt.flush();
+    cr.verify(());
+    assert!(is_transitioning().get(&mut rt.sc()));
+
+    senders[&2].send("b".to_string());
+    rt.flush();
+    cr.verify("2 b");
+    assert!(!is_transitioning().get(&mut rt.sc()));
+}
+
+#[test]
+fn signal_keeps_value_until_ready() {
+    let mut rt = Runtime::new();
+    let tab = State::new(1);
+    let (view, senders) = tab_view(&tab);
+    let _e = view.effect(|_| {});
+    senders[&1].send("a".to_string());
+    rt.flush();
+
+    start_transition({
+        let tab = tab.clone();
+        move |ac| tab.set(2, ac)
+    });
+    rt.flush();
+    assert_eq!(view.get(&mut rt.sc()), "1 a");
+    assert_eq!(tab.get(&mut rt.sc()), 1);
+
+    senders[&2].send("b".to_string());
+    rt.flush();
+    assert_eq!(view.get(&mut rt.sc()), "2 b");
+    assert_eq!(tab.get(&mut rt.sc()), 2);
+}
+
+#[test]
+fn effect_created_during_transition_shows_committed_value() {
+    let mut rt = Runtime::new();
+    let mut cr = CallRecorder::new();
+    let tab = State::new(1);
+    let (view, senders) = tab_view(&tab);
+    let _e0 = view.effect(|_| {});
+    senders[&1].send("a".to_string());
+    rt.flush();
+
+    start_transition({
+        let tab = tab.clone();
+        move |ac| tab.set(2, ac)
+    });
+    rt.flush();
+    let _e1 = view.effect(|value| call!("{value}"));
+    rt.flush();
+    cr.verify("1 a");
+
+    senders[&2].send("b".to_string());
+    rt.flush();
+    cr.verify("2 b");
+}
+
+#[test]
+fn urgent_change_is_shown_during_transition() {
+    let mut rt = Runtime::new();
+    let mut cr = CallRecorder::new();
+    let tab = State::new(1);
+    let (view, senders) = tab_view(&tab);
+    let other = State::new(1);
+    let _e = effect({
+        let other = other.clone();
+        move |sc| call!("{} other {}", view.get(sc), other.get(sc))
+    });
+    senders[&1].send("a".to_string());
+    rt.flush();
+    cr.verify("1 a other 1");
+
+    start_transition({
+        let tab = tab.clone();
+        move |ac| tab.set(2, ac)
+    });
+    rt.flush();
+    other.set(2, rt.ac());
+    rt.flush();
+    cr.verify("1 a other 2");
+
+    senders[&2].send("b".to_string());
+    rt.flush();
+    cr.verify("2 b other 2");
+}
+
+#[test]
+fn unobserved_pending_signal_does_not_delay_commit() {
+    let mut rt = Runtime::new();
+    let mut cr = CallRecorder::new();
+    let tab = State::new(1);
+    let (view, senders) = tab_view(&tab);
+    let unobserved = Signal::from_async({
+        let tab = tab.clone();
+        async move |sc| {
+            sc.with(|sc| tab.get(sc));
+            pending::<()>().await
+        }
+    });
+    let _e = view.effect(|value| call!("{value}"));
+    senders[&1].send("a".to_string());
+    rt.flush();
+    cr.verify("1 a");
+
+    let _ = unobserved.get(&mut rt.sc());
+    start_transition({
+        let tab = tab.clone();
+        move |ac| tab.set(2, ac)
+    });
+    rt.flush();
+    cr.verify(());
+
+    senders[&2].send("b".to_string());
+    rt.flush();
+    cr.verify("2 b");
+    assert!(!is_transitioning().get(&mut rt.sc()));
+}
+
+#[test]
+fn without_transition() {
+    let mut rt = Runtime::new();
+    let mut cr = CallRecorder::new();
+    let tab = State::new(1);
+    let (view, senders) = tab_view(&tab);
+    let _e = view.effect(|value| call!("{value}"));
+    rt.flush();
+    cr.verify("1 loading");
+    senders[&1].send("a".to_string());
+    rt.flush();
+    cr.verify("1 a");
+
+    tab.set(2, rt.ac());
+    rt.flush();
+    cr.verify("2 loading");
+}
+
+#[test]
+fn transition_without_pending_commits_immediately() {
+    let mut rt = Runtime::new();
+    let mut cr = CallRecorder::new();
+    let s = State::new(1);
+    let _e = s.to_signal().effect(|value| call!("s {value}"));
+    rt.flush();
+    cr.verify("s 1");
+
+    start_transition({
+        let s = s.clone();
+        move |ac| s.set(2, ac)
+    });
+    rt.flush();
+    cr.verify("s 2");
+    assert!(!is_transitioning().get(&mut rt.sc()));
+}
+
+#[test]
+fn is_transitioning_while_pending() {
+    let mut rt = Runtime::new();
+    let mut cr = CallRecorder::new();
+    let tab = State::new(1);
+    let (view, senders) = tab_view(&tab);
+    let _e0 = view.effect(|_| {});
+    let _e1 = effect(|sc| call!("transitioning {}", is_transitioning().get(sc)));
+    senders[&1].send("a".to_string());
+    rt.flush();
+    cr.verify("transitioning false");
+
+    start_transition({
+        let tab = tab.clone();
+        move |ac| tab.set(2, ac)
+    });
+    rt.flush();
+    cr.verify("transitioning true");
+
+    senders[&2].send("b".to_string());
+    rt.flush();
+    cr.verify("transitioning false");
+}
+
+#[test]
+fn pending_signal_outside_fork_does_not_delay_commit() {
+    let mut rt = Runtime::new();
+    let mut cr = CallRecorder::new();
+    let (_sender, receiver) = oneshot_broadcast::<i32>();
+    let other = Signal::from_async(async move |_| receiver.recv().await);
+    let _e0 = other.effect(|value| call!("other {value:?}"));
+    let s = State::new(1);
+    let _e1 = s.to_signal().effect(|value| call!("s {value}"));
+    rt.flush();
+    cr.verify(["other Pending", "s 1"]);
+
+    start_transition({
+        let s = s.clone();
+        move |ac| s.set(2, ac)
+    });
+    rt.flush();
+    cr.verify("s 2");
+    assert!(!is_transitioning().get(&mut rt.sc()));
+}
+
+#[test]
+fn effect_outside_fork_is_called_during_transition() {
+    let mut rt = Runtime::new();
+    let mut cr = CallRecorder::new();
+    let tab = State::new(1);
+    let (view, senders) = tab_view(&tab);
+    let _e0 = view.effect(|value| call!("{value}"));
+    let other = State::new(1);
+    let _e1 = other.to_signal().effect(|value| call!("other {value}"));
+    senders[&1].send("a".to_string());
+    rt.flush();
+    cr.verify(["1 a", "other 1"]);
+
+    start_transition({
+        let tab = tab.clone();
+        move |ac| tab.set(2, ac)
+    });
+    rt.flush();
+    other.set(2, rt.ac());
+    rt.flush();
+    cr.verify("other 2");
+
+    senders[&2].send("b".to_string());
+    rt.flush();
+    cr.verify("2 b");
+}
+
+#[test]
+fn panic_in_transition_commits() {
+    let mut rt = Runtime::new();
+    let mut cr = CallRecorder::new();
+    let s = State::new(1);
+    let _e = s.to_signal().effect(|value| call!("s {value}"));
+    rt.flush();
+    cr.verify("s 1");
+
+    start_transition({
+        let s = s.clone();
+        move |ac| {
+            s.set(2, ac);
+            panic!("failed");
+        }
+    });
+    let result = catch_unwind(AssertUnwindSafe(|| rt.flush()));
+    assert!(result.is_err());
+    rt.flush();
+    cr.verify("s 2");
+    assert!(!is_transitioning().get(&mut rt.sc()));
+}
+
+#[test]
+fn transition_is_scoped_to_runtime() {
+    let tab = State::new(1);
+    let (view, _senders) = tab_view(&tab);
+    {
+        let mut rt = Runtime::new();
+        let _e = view.effect(|_| {});
+        rt.flush();
+        start_transition({
+            let tab = tab.clone();
+            move |ac| tab.set(2, ac)
+        });
+        rt.flush();
+        assert!(is_transitioning().get(&mut rt.sc()));
+    }
+    let mut rt = Runtime::new();
+    assert!(!is_transitioning().get(&mut rt.sc()));
+}
+
+#[test]
+fn async_signal_reading_derived_signal_is_evaluated_in_fork() {
+    let mut rt = Runtime::new();
+    let mut cr = CallRecorder::new();
+    let tab = State::new(1);
+    let key = Signal::new({
+        let tab = tab.clone();
+        move |sc| tab.get(sc) * 10
+    });
+    let (sender, receiver) = oneshot_broadcast::<String>();
+    let data = Signal::from_async(async move |sc| {
+        let key = sc.with(|sc| key.get(sc));
+        if key == 10 {
+            format!("{key} a")
+        } else {
+            format!("{key} {}", receiver.recv().await)
+        }
+    });
+    let _e = data.effect(|value| call!("{value:?}"));
+    rt.flush();
+    cr.verify("Ready(\"10 a\")");
+
+    start_transition({
+        let tab = tab.clone();
+        move |ac| tab.set(2, ac)
+    });
+    rt.flush();
+    cr.verify(());
+    assert!(is_transitioning().get(&mut rt.sc()));
+
+    sender.send("b".to_string());
+    rt.flush();
+    cr.verify("Ready(\"20 b\")");
+}
@@ -89,12 +89,14 @@ impl std::ops::Index<usize> for IndexNewToOld {
 struct Change<T> {
     data: T,
     ref_count: usize,
+    size: usize,
 }
 
 pub(crate) struct Changes<T> {
     age_base: usize,
     items: VecDeque<Change<T>>,
     end_ref_count: usize,
+    size: usize,
 }
 impl<T> Changes<T> {
     pub fn new() -> Self {
@@ -102,12 +104,37 @@ impl<T> Changes<T> {
             age_base: 0,
             items: VecDeque::new(),
             end_ref_count: 0,
+            size: 0,
         }
     }
-    pub fn push(&mut self, data: T) {
+    pub fn push(&mut self, data: T, size: usize) {
         let ref_count = self.end_ref_count;
         self.end_ref_count = 0;
-        self.items.push_back(Change { data, ref_count });
+        self.size = self.size.saturating_add(size);
+        self.items.push_back(Change {
+            data,
+            ref_count,
+            size,
+        });
+    }
+    pub fn last_mut(&mut self) -> Option<&mut T> {
+        self.items.back_mut().map(|change| &mut change.data)
+    }
+    pub fn set_last_size(&mut self, size: usize) {
+        let change = self.items.back_mut().expect("no changes");
+        self.size = self.size.saturating_sub(change.size).saturating_add(size);
+        change.size = size;
+    }
+
+    /// Removes the last change. Cursors at the removed change move to the end.
+    pub fn pop(&mut self) -> Option<T> {
+        let change = self.items.pop_back()?;
+        self.end_ref_count = self
+            .end_ref_count
+            .checked_add(change.ref_count)
+            .expect("ref_count overflow");
+        self.size = self.size.saturating_sub(change.size);
+        Some(change.data)
     }
     fn increment_ref_count(&mut self, count: usize) {
         self.end_ref_count = self
@@ -116,10 +143,16 @@ impl<T> Changes<T> {
             .expect("ref_count overflow");
     }
     fn increment_ref_count_at(&mut self, age: usize) {
+        if self.is_expired(age) {
+            return;
+        }
         let ref_count = self.ref_count_mut(age);
         *ref_count = ref_count.checked_add(1).expect("ref_count overflow");
     }
     fn decrement_ref_count(&mut self, age: usize) {
+        if self.is_expired(age) {
+            return;
+        }
         let ref_count = self.ref_count_mut(age);
         *ref_count = ref_count
             .checked_sub(1)
@@ -133,6 +166,9 @@ impl<T> Changes<T> {
             &mut self.items[index].ref_count
         }
     }
+    pub fn is_end_referenced(&self) -> bool {
+        self.end_ref_count != 0
+    }
     pub fn end_age(&self) -> usize {
         self.age_base.wrapping_add(self.items.len())
     }
@@ -141,13 +177,22 @@ impl<T> Changes<T> {
         assert!(index <= self.items.len());
         index
     }
-    pub fn clean(&mut self, mut f: impl FnMut(T)) {
+
+    /// Returns whether the changes after `age` have been partly discarded by [`Self::clean`].
+    pub fn is_expired(&self, age: usize) -> bool {
+        age.wrapping_sub(self.age_base) > self.items.len()
+    }
+
+    /// Discards the oldest changes that are no longer referenced, and also referenced ones while
+    /// more than `max_len` changes or `max_size` in total size remain.
+    pub fn clean(&mut self, max_len: usize, max_size: usize, mut f: impl FnMut(T)) {
         while let Some(change) = self.items.front() {
-            if change.ref_count != 0 {
+            if change.ref_count != 0 && self.items.len() <= max_len && self.size <= max_size {
                 return;
             }
             let entry = self.items.pop_front().unwrap();
             self.age_base = self.age_base.wrapping_add(1);
+            self.size = self.size.saturating_sub(entry.size);
             f(entry.data);
         }
     }
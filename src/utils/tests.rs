@@ -31,20 +31,20 @@ fn changes_and_ref_count_ops() {
 
     ops.increment();
     ops.apply(&mut changes);
-    changes.push("a");
+    changes.push("a", 1);
 
     ops.increment_at(0);
     ops.decrement(Some(0));
     ops.apply(&mut changes);
 
     let mut cleaned = Vec::new();
-    changes.clean(|d| cleaned.push(d));
+    changes.clean(usize::MAX, usize::MAX, |d| cleaned.push(d));
     assert_eq!(cleaned, Vec::<&str>::new());
     assert_eq!(changes.items(0).copied().collect::<Vec<_>>(), vec!["a"]);
 
     ops.decrement(Some(0));
     ops.apply(&mut changes);
-    changes.clean(|d| cleaned.push(d));
+    changes.clean(usize::MAX, usize::MAX, |d| cleaned.push(d));
     assert_eq!(cleaned, vec!["a"]);
     let remaining = changes.items(changes.end_age()).next().is_none();
     assert!(remaining);
@@ -56,9 +56,10 @@ fn changes_ages_wrap_without_invalidating_cursors() {
         age_base: usize::MAX,
         items: VecDeque::new(),
         end_ref_count: 0,
+        size: 0,
     };
 
-    changes.push("change");
+    changes.push("change", 1);
 
     assert_eq!(changes.end_age(), 0);
     assert_eq!(
@@ -71,11 +72,63 @@ fn changes_ages_wrap_without_invalidating_cursors() {
     );
 
     let mut cleaned = Vec::new();
-    changes.clean(|change| cleaned.push(change));
+    changes.clean(usize::MAX, usize::MAX, |change| cleaned.push(change));
     assert_eq!(cleaned, vec!["change"]);
     assert_eq!(changes.end_age(), 0);
 }
 
+#[test]
+fn changes_clean_discards_referenced_changes_beyond_limits() {
+    let mut changes = Changes::new();
+    let mut ops = RefCountOps::new();
+    ops.increment();
+    ops.apply(&mut changes);
+    for (data, size) in [("a", 1), ("b", 2), ("c", 3), ("d", 4)] {
+        changes.push(data, size);
+    }
+    ops.increment_at(2);
+    ops.apply(&mut changes);
+
+    let mut cleaned = Vec::new();
+    changes.clean(3, usize::MAX, |d| cleaned.push(d));
+    assert_eq!(cleaned, vec!["a", "b"]);
+    assert!(changes.is_expired(0));
+    assert!(!changes.is_expired(2));
+
+    changes.clean(usize::MAX, 4, |d| cleaned.push(d));
+    assert_eq!(cleaned, vec!["a", "b", "c", "d"]);
+    assert!(changes.is_expired(2));
+    assert!(!changes.is_expired(changes.end_age()));
+
+    ops.decrement(Some(0));
+    ops.increment_at(2);
+    ops.decrement(Some(2));
+    ops.apply(&mut changes);
+}
+
+#[test]
+fn changes_pop_moves_cursors_to_end() {
+    let mut changes = Changes::new();
+    let mut ops = RefCountOps::new();
+    changes.push("a", 1);
+    ops.increment();
+    ops.apply(&mut changes);
+    changes.push("b", 2);
+    *changes.last_mut().unwrap() = "c";
+    changes.set_last_size(3);
+
+    assert_eq!(changes.pop(), Some("c"));
+    assert_eq!(changes.end_age(), 1);
+
+    let mut cleaned = Vec::new();
+    changes.clean(usize::MAX, usize::MAX, |d| cleaned.push(d));
+    assert_eq!(cleaned, vec!["a"]);
+    changes.clean(usize::MAX, 0, |d| cleaned.push(d));
+
+    ops.decrement(Some(1));
+    ops.apply(&mut changes);
+}
+
 #[test]
 fn to_range_variants() {
     assert_eq!(to_range(1..=3, 10), 1..4);